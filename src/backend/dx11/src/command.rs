@@ -19,7 +19,7 @@ use winapi::{FLOAT, INT, UINT, UINT8, DXGI_FORMAT,
              DXGI_FORMAT_R16_UINT, DXGI_FORMAT_R32_UINT,
              D3D11_CLEAR_FLAG, D3D11_PRIMITIVE_TOPOLOGY, D3D11_VIEWPORT, D3D11_RECT,
              ID3D11RasterizerState, ID3D11DepthStencilState, ID3D11BlendState};
-use core::{command, pso, shade, state, target, texture as tex};
+use core::{command, memory, pso, shade, state, target, texture as tex};
 use core::{IndexType, VertexCount};
 use core::{MAX_VERTEX_ATTRIBUTES, MAX_CONSTANT_BUFFERS,
            MAX_RESOURCE_VIEWS, MAX_UNORDERED_VIEWS,
@@ -198,6 +198,11 @@ impl<P: 'static + Parser> command::Buffer<Resources> for CommandBuffer<P> {
             let mut count = 0;
             for cbuf in cbs.iter() {
                 if cbuf.1.contains(mask) {
+                    if let Some((offset, _)) = cbuf.3 {
+                        if offset != 0 {
+                            error!("Constant buffer offsets are not supported");
+                        }
+                    }
                     buffers[cbuf.2 as usize] = (cbuf.0).0;
                     count += 1;
                 }
@@ -242,6 +247,12 @@ impl<P: 'static + Parser> command::Buffer<Resources> for CommandBuffer<P> {
         }
     }
 
+    fn bind_atomic_counter_buffers(&mut self, acbs: &[pso::AtomicCounterBufferParam<Resources>]) {
+        if !acbs.is_empty() {
+            error!("Atomic counter buffers are not supported");
+        }
+    }
+
     fn bind_samplers(&mut self, ss: &[pso::SamplerParam<Resources>]) {
         for &stage in shade::STAGES.iter() {
             let mut samplers = [native::Sampler(ptr::null_mut()); MAX_SAMPLERS];
@@ -288,9 +299,15 @@ impl<P: 'static + Parser> command::Buffer<Resources> for CommandBuffer<P> {
     }
 
     fn bind_index(&mut self, buf: Buffer, itype: IndexType) {
-        let format = match itype {
-            IndexType::U16 => DXGI_FORMAT_R16_UINT,
-            IndexType::U32 => DXGI_FORMAT_R32_UINT,
+        let (buf, format) = match itype {
+            IndexType::U8 => {
+                // unbind the previous buffer, the indices fetched from
+                // none are all zero so nothing is drawn
+                error!("8-bit indices are not supported");
+                (Buffer(native::Buffer(ptr::null_mut())), DXGI_FORMAT_R16_UINT)
+            },
+            IndexType::U16 => (buf, DXGI_FORMAT_R16_UINT),
+            IndexType::U32 => (buf, DXGI_FORMAT_R32_UINT),
         };
         self.parser.parse(Command::BindIndex(buf, format));
     }
@@ -395,4 +412,19 @@ impl<P: 'static + Parser> command::Buffer<Resources> for CommandBuffer<P> {
         });
     }
 
+    fn push_debug_group(&mut self, _: &str) {}
+
+    fn pop_debug_group(&mut self) {}
+
+    fn memory_barrier(&mut self, _: memory::Barrier) {
+        // the runtime tracks the hazards between shader writes and reads
+    }
+
+    fn begin_occlusion_query(&mut self, _: core::QuerySlot) {}
+
+    fn end_occlusion_query(&mut self, _: core::QuerySlot) {}
+
+    fn begin_timer_query(&mut self, _: core::QuerySlot) {}
+
+    fn end_timer_query(&mut self, _: core::QuerySlot) {}
 }
@@ -436,6 +436,7 @@ impl core::Factory<R> for Factory {
             textures: Vec::new(),
             unordereds: Vec::new(),
             samplers: Vec::new(),
+            atomic_counter_buffers: Vec::new(),
             outputs: Vec::new(),
             output_depth: false,
             knows_outputs: true,
@@ -730,7 +731,8 @@ impl core::Factory<R> for Factory {
             error!("Failed to create SRV from {:#?}, error {:x}", native_desc, hr);
             return Err(f::ResourceViewError::Unsupported);
         }
-        Ok(self.share.handles.borrow_mut().make_texture_srv(native::Srv(raw_view), htex))
+        Ok(self.share.handles.borrow_mut().make_texture_srv(native::Srv(raw_view), htex,
+                                                            desc.channel))
     }
 
     fn view_texture_as_unordered_access_raw(&mut self, _htex: &h::RawTexture<R>)
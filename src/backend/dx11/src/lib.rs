@@ -191,6 +191,9 @@ pub fn create(driver_type: winapi::D3D_DRIVER_TYPE, desc: &winapi::DXGI_SWAP_CHA
             max_index_count: 0,
             max_texture_size: 0,
             max_patch_size: 32, //hard-coded in D3D11
+            max_constant_buffer_size: 4096 * 16, //hard-coded in D3D11
+            max_color_targets: core::MAX_COLOR_TARGETS,
+            constant_buffer_offset_alignment: 0, //requires D3D11.1
             instance_base_supported: false,
             instance_call_supported: false,
             instance_rate_supported: false,
@@ -200,6 +203,18 @@ pub fn create(driver_type: winapi::D3D_DRIVER_TYPE, desc: &winapi::DXGI_SWAP_CHA
             unordered_access_view_supported: false,
             separate_blending_slots_supported: false,
             copy_buffer_supported: true,
+            polygon_mode_supported: true,
+            texture_1d_supported: true,
+            texture_multisample_supported: true,
+            texture_buffer_supported: true,
+            half_float_vertex_supported: true,
+            half_float_color_supported: true,
+            index_u8_supported: false,
+            atomic_counter_supported: false,
+            multiview_supported: false,
+            layered_rendering_supported: true,
+            occlusion_query_supported: false,
+            timer_query_supported: false,
         },
         handles: RefCell::new(h::Manager::new()),
     };
@@ -14,10 +14,11 @@
 
 #![allow(missing_docs)]
 
+use std::collections::HashMap;
 use gl;
 use core::{self as c, command, state as s};
 use core::target::{ColorValue, Depth, Mirror, Rect, Stencil};
-use {Buffer, BufferElement, Program, FrameBuffer, Texture,
+use {ArrayBuffer, Buffer, BufferElement, Program, FrameBuffer, Texture,
      NewTexture, Resources, PipelineState, ResourceView, TargetView};
 
 
@@ -40,6 +41,87 @@ fn primitive_to_gl(primitive: c::Primitive) -> gl::types::GLenum {
 
 pub type Access = gl::types::GLenum;
 
+/// Vertex attributes of a draw call, with their source buffers and the
+/// offsets they are read at.
+pub type VertexArray = [Option<(Buffer, BufferElement)>; c::MAX_VERTEX_ATTRIBUTES];
+
+/// Offsets of the vertex attributes in their buffers, in bytes.
+pub type VertexOffsets = [gl::types::GLuint; c::MAX_VERTEX_ATTRIBUTES];
+
+/// Vertex array objects of a device, keyed on the buffers, formats, strides
+/// and rates of their attributes only. The offsets are applied on each bind
+/// instead, so that drawing from one buffer at many offsets reuses a single
+/// object.
+pub struct VertexArrayCache {
+    arrays: HashMap<VertexArray, (ArrayBuffer, VertexOffsets)>,
+}
+
+impl VertexArrayCache {
+    /// Create a new empty cache.
+    pub fn new() -> VertexArrayCache {
+        VertexArrayCache {
+            arrays: HashMap::new(),
+        }
+    }
+
+    /// Return the number of cached objects.
+    pub fn len(&self) -> usize {
+        self.arrays.len()
+    }
+
+    /// Return true if no object is cached.
+    pub fn is_empty(&self) -> bool {
+        self.arrays.is_empty()
+    }
+
+    /// Look up the object of `vertex_array`, and return it along with the
+    /// offsets it was last bound at. The new offsets are recorded, and
+    /// the caller is expected to apply the ones that changed.
+    pub fn rebind(&mut self, vertex_array: &VertexArray) -> Option<(ArrayBuffer, VertexOffsets)> {
+        let (key, offsets) = split_offsets(vertex_array);
+        self.arrays.get_mut(&key).map(|entry| {
+            let previous = entry.1;
+            entry.1 = offsets;
+            (entry.0, previous)
+        })
+    }
+
+    /// Cache a newly specified object for `vertex_array`.
+    pub fn insert(&mut self, vertex_array: &VertexArray, vao: ArrayBuffer) {
+        let (key, offsets) = split_offsets(vertex_array);
+        self.arrays.insert(key, (vao, offsets));
+    }
+
+    /// Remove the objects for which `keep` returns false, and return them.
+    pub fn remove_unless<F>(&mut self, mut keep: F) -> Vec<ArrayBuffer> where
+        F: FnMut(&VertexArray) -> bool
+    {
+        let mut removed = Vec::new();
+        self.arrays.retain(|key, &mut (vao, _)| {
+            let kept = keep(key);
+            if !kept {
+                removed.push(vao);
+            }
+            kept
+        });
+        removed
+    }
+}
+
+/// Split the offsets out of a vertex array, leaving the part that keys its
+/// vertex array object.
+fn split_offsets(vertex_array: &VertexArray) -> (VertexArray, VertexOffsets) {
+    let mut key = *vertex_array;
+    let mut offsets = [0; c::MAX_VERTEX_ATTRIBUTES];
+    for (attrib, offset) in key.iter_mut().zip(offsets.iter_mut()) {
+        if let Some((_, ref mut bel)) = *attrib {
+            *offset = bel.elem.offset;
+            bel.elem.offset = 0;
+        }
+    }
+    (key, offsets)
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct RawOffset(pub *const gl::types::GLvoid);
 unsafe impl Send for RawOffset {}
@@ -81,15 +163,16 @@ pub enum Command {
     BindResourceView(c::pso::ResourceViewParam<Resources>),
     BindUnorderedView(c::pso::UnorderedViewParam<Resources>),
     BindSampler(c::pso::SamplerParam<Resources>, Option<gl::types::GLenum>),
+    BindAtomicCounterBuffer(c::pso::AtomicCounterBufferParam<Resources>),
     BindPixelTargets(c::pso::PixelTargetSet<Resources>),
     BindVao,
-    BindAttribute(c::AttributeSlot, Buffer, BufferElement),
-    UnbindAttribute(c::AttributeSlot),
+    BindVertexArray(usize),
     BindIndex(Buffer),
     BindFrameBuffer(Access, FrameBuffer),
     BindUniform(c::shade::Location, c::shade::UniformValue),
     SetDrawColorBuffers(c::ColorSlot),
     SetRasterizer(s::Rasterizer),
+    SetPointState(c::pso::PointInfo),
     SetViewport(Rect),
     SetScissor(Option<Rect>),
     SetDepthState(Option<s::Depth>),
@@ -128,6 +211,14 @@ pub enum Command {
                 c::VertexCount,
                 Option<command::InstanceParams>),
     _Blit(Rect, Rect, Mirror, usize),
+    MemoryBarrier(gl::types::GLbitfield),
+    BeginOcclusionQuery(c::QuerySlot),
+    EndOcclusionQuery,
+    BeginTimerQuery(c::QuerySlot),
+    EndTimerQuery,
+    // debugging
+    PushDebugGroup(DataPointer),
+    PopDebugGroup,
 }
 
 pub const COLOR_DEFAULT: s::Color = s::Color {
@@ -138,7 +229,6 @@ pub const COLOR_DEFAULT: s::Color = s::Color {
 pub const RESET: [Command; 14] = [
     Command::BindProgram(0),
     Command::BindVao,
-    // Command::UnbindAttribute, //not needed, handled by the cache
     Command::BindIndex(0),
     Command::BindFrameBuffer(gl::FRAMEBUFFER, 0),
     Command::SetRasterizer(s::Rasterizer {
@@ -164,11 +254,14 @@ pub const RESET: [Command; 14] = [
     Command::SetBlendColor([0f32; 4]),
 ];
 
+/// Shadow copy of the GL state, as it will be after the commands recorded
+/// so far are executed. Every state change is diffed against it, so that
+/// only the commands actually changing something end up in the buffer.
 struct Cache {
     primitive: gl::types::GLenum,
     index_type: c::IndexType,
-    current_vbs: Option<c::pso::VertexBufferSet<Resources>>,
     attributes: [Option<BufferElement>; c::MAX_VERTEX_ATTRIBUTES],
+    vertex_array: Option<VertexArray>,
     resource_binds: [Option<gl::types::GLenum>; c::MAX_RESOURCE_VIEWS],
     scissor: bool,
     target_dim: (u16, u16, u16),
@@ -176,18 +269,24 @@ struct Cache {
     // blend: Option<s::Blend>,
     cull_face: s::CullFace,
     draw_mask: u32,
+    /// The last bound pipeline, as long as none of its states got disturbed.
+    pipeline: Option<PipelineState>,
 
     program: Program,
-    constant_buffer: Option<c::pso::ConstantBufferParam<Resources>>,
-    resource_view: Option<c::pso::ResourceViewParam<Resources>>,
-    scissor_test: Option<Rect>,
-    depth_state: Option<s::Depth>,
-    blend_state: Option<(c::ColorSlot, s::Color)>,
+    constant_buffers: [Option<c::pso::ConstantBufferParam<Resources>>; c::MAX_CONSTANT_BUFFERS],
+    resource_views: [Option<c::pso::ResourceViewParam<Resources>>; c::MAX_RESOURCE_VIEWS],
+    samplers: [Option<(c::pso::SamplerParam<Resources>, Option<gl::types::GLenum>)>; c::MAX_SAMPLERS],
+    pixel_targets: Option<c::pso::PixelTargetSet<Resources>>,
+    scissor_test: Option<Option<Rect>>,
+    depth_state: Option<Option<s::Depth>>,
+    stencil_state: Option<(Option<s::Stencil>, (Stencil, Stencil), s::CullFace)>,
+    blend_states: [Option<s::Color>; c::MAX_COLOR_TARGETS],
     blend_color: Option<ColorValue>,
     viewport: Option<Rect>,
     rasterizer: Option<s::Rasterizer>,
+    point: Option<c::pso::PointInfo>,
     framebuffer: Option<(Access, FrameBuffer)>,
-    index: Buffer,
+    index: Option<Buffer>,
 }
 
 impl Cache {
@@ -195,8 +294,8 @@ impl Cache {
         Cache {
             primitive: 0,
             index_type: c::IndexType::U16,
-            current_vbs: None,
             attributes: [None; c::MAX_VERTEX_ATTRIBUTES],
+            vertex_array: None,
             resource_binds: [None; c::MAX_RESOURCE_VIEWS],
             scissor: false,
             target_dim: (0, 0, 0),
@@ -204,18 +303,23 @@ impl Cache {
             cull_face: s::CullFace::Nothing,
             // blend: None,
             draw_mask: 0,
+            pipeline: None,
 
             program: 0,
-            constant_buffer: None,
-            resource_view: None,
+            constant_buffers: [None; c::MAX_CONSTANT_BUFFERS],
+            resource_views: [None; c::MAX_RESOURCE_VIEWS],
+            samplers: [None; c::MAX_SAMPLERS],
+            pixel_targets: None,
             scissor_test: None,
             depth_state: None,
-            blend_state: None,
+            stencil_state: None,
+            blend_states: [None; c::MAX_COLOR_TARGETS],
             blend_color: None,
             viewport: None,
             rasterizer: None,
+            point: None,
             framebuffer: None,
-            index: 0,
+            index: None,
         }
     }
 
@@ -227,28 +331,61 @@ impl Cache {
         Some(Command::BindProgram(program))
     }
 
+    fn bind_vertex_array(&mut self, vertex_array: VertexArray) -> bool {
+        if self.vertex_array == Some(vertex_array) {
+            return false;
+        }
+        self.vertex_array = Some(vertex_array);
+        // the index buffer binding is a part of the VAO state
+        self.index = None;
+        true
+    }
+
     fn bind_constant_buffer(&mut self, constant_buffer: c::pso::ConstantBufferParam<Resources>) -> Option<Command> {
-        if self.constant_buffer == Some(constant_buffer) {
+        let bound = &mut self.constant_buffers[constant_buffer.2 as usize];
+        if *bound == Some(constant_buffer) {
             return None;
         }
-        self.constant_buffer = Some(constant_buffer);
+        *bound = Some(constant_buffer);
         Some(Command::BindConstantBuffer(constant_buffer))
     }
 
     fn bind_resource_view(&mut self, resource_view: c::pso::ResourceViewParam<Resources>) -> Option<Command> {
-        if self.resource_view == Some(resource_view) {
+        let slot = resource_view.2 as usize;
+        if self.resource_views[slot] == Some(resource_view) {
             return None;
         }
-        self.resource_view = Some(resource_view);
+        self.resource_views[slot] = Some(resource_view);
+        // without sampler objects, the sampler state lives in the texture
+        if slot < c::MAX_SAMPLERS {
+            self.samplers[slot] = None;
+        }
         Some(Command::BindResourceView(resource_view))
     }
 
+    fn bind_sampler(&mut self, sampler: c::pso::SamplerParam<Resources>, bind: Option<gl::types::GLenum>) -> Option<Command> {
+        let bound = &mut self.samplers[sampler.2 as usize];
+        if *bound == Some((sampler, bind)) {
+            return None;
+        }
+        *bound = Some((sampler, bind));
+        Some(Command::BindSampler(sampler, bind))
+    }
+
+    fn bind_pixel_targets(&mut self, pts: c::pso::PixelTargetSet<Resources>) -> bool {
+        if self.pixel_targets == Some(pts) {
+            return false;
+        }
+        self.pixel_targets = Some(pts);
+        true
+    }
+
     fn bind_index(&mut self, buffer: Buffer, itype: c::IndexType) -> Option<Command> {
-        if self.index == buffer && itype == self.index_type {
+        if self.index == Some(buffer) && itype == self.index_type {
             return None;
         }
         self.index_type = itype;
-        self.index = buffer;
+        self.index = Some(buffer);
         Some(Command::BindIndex(buffer))
     }
 
@@ -268,6 +405,14 @@ impl Cache {
         Some(Command::SetRasterizer(rasterizer))
     }
 
+    fn set_point_state(&mut self, point: c::pso::PointInfo) -> Option<Command> {
+        if self.point == Some(point) {
+            return None;
+        }
+        self.point = Some(point);
+        Some(Command::SetPointState(point))
+    }
+
     fn set_viewport(&mut self, rect: Rect) -> Option<Command> {
         if self.viewport == Some(rect) {
             return None;
@@ -277,37 +422,53 @@ impl Cache {
     }
 
     fn set_scissor(&mut self, rect: Option<Rect>) -> Option<Command> {
-        if self.scissor_test == rect {
+        if self.scissor_test == Some(rect) {
             return None;
         }
-        self.scissor_test = rect;
+        self.scissor_test = Some(rect);
         Some(Command::SetScissor(rect))
     }
     fn set_depth_state(&mut self, depth: Option<s::Depth>) -> Option<Command> {
-        if self.depth_state == depth {
+        if self.depth_state == Some(depth) {
             return None;
         }
-        self.depth_state = depth;
+        self.depth_state = Some(depth);
         Some(Command::SetDepthState(depth))
     }
     fn set_stencil_state(&mut self, option_stencil: Option<s::Stencil>, stencils: (Stencil, Stencil), cullface: s:: CullFace) -> Option<Command> {
-        // This is a little more complex 'cause if option_stencil
-        // is None the stencil state is disabled, it it's Some
-        // then it's enabled and parameters are set.
-        // That's actually bad because it makes it impossible
-        // to completely remove all redundant calls if the
-        // stencil is enabled;
-        // we'll be re-enabling it over and over.
-        // For now though, we just don't handle it.
+        let state = (option_stencil, stencils, cullface);
+        if self.stencil_state == Some(state) {
+            return None;
+        }
+        self.stencil_state = Some(state);
         Some(Command::SetStencilState(option_stencil, stencils, cullface))
     }
+    fn get_stencil_refs(&self) -> (Stencil, Stencil) {
+        self.stencil_state.map_or((0, 0), |(_, refs, _)| refs)
+    }
     fn set_blend_state(&mut self, color_slot: c::ColorSlot, color: s::Color) -> Option<Command> {
-        if self.blend_state == Some((color_slot, color)) {
+        let bound = &mut self.blend_states[color_slot as usize];
+        if *bound == Some(color) {
             return None;
         }
-        self.blend_state = Some((color_slot, color));
+        *bound = Some(color);
         Some(Command::SetBlendState(color_slot, color))
     }
+    /// Forget the state touched by a `Clear`, which overrides the write masks.
+    fn invalidate_masks(&mut self, color: bool, depth: bool, stencil: bool) {
+        if color || depth || stencil {
+            self.pipeline = None;
+        }
+        if color {
+            self.blend_states = [None; c::MAX_COLOR_TARGETS];
+        }
+        if depth {
+            self.depth_state = None;
+        }
+        if stencil {
+            self.stencil_state = None;
+        }
+    }
     fn set_blend_color(&mut self, color_value: ColorValue) -> Option<Command> {
         if self.blend_color == Some(color_value) {
             return None;
@@ -339,8 +500,8 @@ pub struct CommandBuffer {
     /// This framebuffer must exist and be configured correctly (with renderbuffer attachments,
     /// etc.) so that rendering to it can occur immediately.
     pub display_fb: FrameBuffer,
+    pub vertex_arrays: Vec<VertexArray>,
     cache: Cache,
-    active_attribs: usize,
 }
 
 impl CommandBuffer {
@@ -350,8 +511,8 @@ impl CommandBuffer {
             data: DataBuffer::new(),
             fbo: fbo,
             display_fb: 0 as FrameBuffer,
+            vertex_arrays: Vec::new(),
             cache: Cache::new(),
-            active_attribs: 0,
         }
     }
     fn is_main_target(&self, tv: Option<TargetView>) -> bool {
@@ -367,11 +528,15 @@ impl command::Buffer<Resources> for CommandBuffer {
     fn reset(&mut self) {
         self.buf.clear();
         self.data.0.clear();
+        self.vertex_arrays.clear();
         self.cache = Cache::new();
-        self.active_attribs = (1 << c::MAX_VERTEX_ATTRIBUTES) - 1;
     }
 
     fn bind_pipeline_state(&mut self, pso: PipelineState) {
+        if self.cache.pipeline == Some(pso) {
+            return;
+        }
+        self.cache.pipeline = Some(pso);
         let cull = pso.rasterizer.cull_face;
         self.cache.primitive = primitive_to_gl(pso.primitive);
         self.cache.attributes = pso.input;
@@ -381,8 +546,12 @@ impl command::Buffer<Resources> for CommandBuffer {
         self.buf.extend(self.cache.bind_program(pso.program));
         self.cache.scissor = pso.scissor;
         self.buf.extend(self.cache.set_rasterizer(pso.rasterizer));
+        if let c::Primitive::PointList = pso.primitive {
+            self.buf.extend(self.cache.set_point_state(pso.point));
+        }
         self.buf.extend(self.cache.set_depth_state(pso.output.depth));
-        self.buf.extend(self.cache.set_stencil_state(pso.output.stencil, (0, 0), cull));
+        let stencil_refs = self.cache.get_stencil_refs();
+        self.buf.extend(self.cache.set_stencil_state(pso.output.stencil, stencil_refs, cull));
         for i in 0..c::MAX_COLOR_TARGETS {
             if pso.output.draw_mask & (1 << i) != 0 {
                 self.buf.extend(self.cache.set_blend_state(i as c::ColorSlot,
@@ -395,10 +564,7 @@ impl command::Buffer<Resources> for CommandBuffer {
     }
 
     fn bind_vertex_buffers(&mut self, vbs: c::pso::VertexBufferSet<Resources>) {
-        if self.cache.current_vbs == Some(vbs) {
-            return;
-        }
-        self.cache.current_vbs = Some(vbs);
+        let mut vertex_array: VertexArray = [None; c::MAX_VERTEX_ATTRIBUTES];
         for i in 0..c::MAX_VERTEX_ATTRIBUTES {
             match (vbs.0[i], self.cache.attributes[i]) {
                 (None, Some(fm)) => {
@@ -406,19 +572,15 @@ impl command::Buffer<Resources> for CommandBuffer {
                 }
                 (Some((buffer, offset)), Some(mut bel)) => {
                     bel.elem.offset += offset as gl::types::GLuint;
-                    self.buf.push(Command::BindAttribute(
-                        i as c::AttributeSlot,
-                        buffer,
-                        bel));
-                    self.active_attribs |= 1 << i;
-                }
-                (_, None) if self.active_attribs & (1 << i) != 0 => {
-                    self.buf.push(Command::UnbindAttribute(i as c::AttributeSlot));
-                    self.active_attribs ^= 1 << i;
+                    vertex_array[i] = Some((buffer, bel));
                 }
                 (_, None) => (),
             }
         }
+        if self.cache.bind_vertex_array(vertex_array) {
+            self.buf.push(Command::BindVertexArray(self.vertex_arrays.len()));
+            self.vertex_arrays.push(vertex_array);
+        }
     }
 
     fn bind_constant_buffers(&mut self, cbs: &[c::pso::ConstantBufferParam<Resources>]) {
@@ -450,7 +612,13 @@ impl command::Buffer<Resources> for CommandBuffer {
     fn bind_samplers(&mut self, ss: &[c::pso::SamplerParam<Resources>]) {
         for param in ss.iter() {
             let bind = self.cache.resource_binds[param.2 as usize];
-            self.buf.push(Command::BindSampler(param.clone(), bind));
+            self.buf.extend(self.cache.bind_sampler(param.clone(), bind));
+        }
+    }
+
+    fn bind_atomic_counter_buffers(&mut self, acbs: &[c::pso::AtomicCounterBufferParam<Resources>]) {
+        for param in acbs.iter() {
+            self.buf.push(Command::BindAtomicCounterBuffer(param.clone()));
         }
     }
 
@@ -467,8 +635,12 @@ impl command::Buffer<Resources> for CommandBuffer {
                 .position(|c| c.is_none())
                 .unwrap_or(pts.colors.len()) as c::ColorSlot;
             self.buf.extend(self.cache.bind_framebuffer(gl::DRAW_FRAMEBUFFER, self.fbo));
-            self.buf.push(Command::BindPixelTargets(pts));
-            self.buf.push(Command::SetDrawColorBuffers(num));
+            // the attachments are part of the FBO state, so they persist
+            // while the main framebuffer is bound
+            if self.cache.bind_pixel_targets(pts) {
+                self.buf.push(Command::BindPixelTargets(pts));
+                self.buf.push(Command::SetDrawColorBuffers(num));
+            }
         }
         let view = pts.get_view();
         self.cache.target_dim = view;
@@ -585,6 +757,7 @@ impl command::Buffer<Resources> for CommandBuffer {
         pts.colors[0] = Some(target);
         self.bind_pixel_targets(pts);
         self.buf.push(Command::Clear(Some(value), None, None));
+        self.cache.invalidate_masks(true, false, false);
     }
 
     fn clear_depth_stencil(&mut self,
@@ -600,6 +773,7 @@ impl command::Buffer<Resources> for CommandBuffer {
         }
         self.bind_pixel_targets(pts);
         self.buf.push(Command::Clear(None, depth, stencil));
+        self.cache.invalidate_masks(false, depth.is_some(), stencil.is_some());
     }
 
     fn call_draw(&mut self,
@@ -615,6 +789,7 @@ impl command::Buffer<Resources> for CommandBuffer {
                          base: c::VertexCount,
                          instances: Option<command::InstanceParams>) {
         let (offset, gl_index) = match self.cache.index_type {
+            c::IndexType::U8 => (start, gl::UNSIGNED_BYTE),
             c::IndexType::U16 => (start * 2u32, gl::UNSIGNED_SHORT),
             c::IndexType::U32 => (start * 4u32, gl::UNSIGNED_INT),
         };
@@ -627,4 +802,55 @@ impl command::Buffer<Resources> for CommandBuffer {
                       base,
                       instances));
     }
+
+    fn memory_barrier(&mut self, barrier: c::memory::Barrier) {
+        use core::memory as m;
+        let mut bits = 0;
+        for &(flag, bit) in [
+            (m::VERTEX_BUFFER_BARRIER,    gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT),
+            (m::INDEX_BUFFER_BARRIER,     gl::ELEMENT_ARRAY_BARRIER_BIT),
+            (m::CONSTANT_BUFFER_BARRIER,  gl::UNIFORM_BARRIER_BIT),
+            (m::SHADER_RESOURCE_BARRIER,  gl::TEXTURE_FETCH_BARRIER_BIT),
+            (m::UNORDERED_ACCESS_BARRIER, gl::SHADER_IMAGE_ACCESS_BARRIER_BIT |
+                                          gl::SHADER_STORAGE_BARRIER_BIT),
+            (m::INDIRECT_BARRIER,         gl::COMMAND_BARRIER_BIT),
+            (m::TRANSFER_BARRIER,         gl::BUFFER_UPDATE_BARRIER_BIT |
+                                          gl::TEXTURE_UPDATE_BARRIER_BIT |
+                                          gl::PIXEL_BUFFER_BARRIER_BIT),
+            (m::RENDER_TARGET_BARRIER,    gl::FRAMEBUFFER_BARRIER_BIT),
+            (m::ATOMIC_COUNTER_BARRIER,   gl::ATOMIC_COUNTER_BARRIER_BIT),
+        ].iter() {
+            if barrier.contains(flag) {
+                bits |= bit;
+            }
+        }
+        if bits != 0 {
+            self.buf.push(Command::MemoryBarrier(bits));
+        }
+    }
+
+    fn begin_occlusion_query(&mut self, slot: c::QuerySlot) {
+        self.buf.push(Command::BeginOcclusionQuery(slot));
+    }
+
+    fn end_occlusion_query(&mut self, _: c::QuerySlot) {
+        self.buf.push(Command::EndOcclusionQuery);
+    }
+
+    fn begin_timer_query(&mut self, slot: c::QuerySlot) {
+        self.buf.push(Command::BeginTimerQuery(slot));
+    }
+
+    fn end_timer_query(&mut self, _: c::QuerySlot) {
+        self.buf.push(Command::EndTimerQuery);
+    }
+
+    fn push_debug_group(&mut self, name: &str) {
+        let ptr = self.data.add(name.as_bytes());
+        self.buf.push(Command::PushDebugGroup(ptr));
+    }
+
+    fn pop_debug_group(&mut self) {
+        self.buf.push(Command::PopDebugGroup);
+    }
 }
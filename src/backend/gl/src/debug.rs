@@ -0,0 +1,108 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Driver debug output, as provided by `KHR_debug`.
+
+use std::{process, slice, str};
+use std::panic::{self, AssertUnwindSafe};
+use std::os::raw::c_void;
+use gl;
+use gl::types::{GLchar, GLenum, GLsizei, GLuint};
+
+/// Severity of a debug message, from the least to the most important.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum DebugSeverity {
+    Notification,
+    Low,
+    Medium,
+    High,
+}
+
+impl DebugSeverity {
+    fn from_gl(severity: GLenum) -> DebugSeverity {
+        match severity {
+            gl::DEBUG_SEVERITY_HIGH => DebugSeverity::High,
+            gl::DEBUG_SEVERITY_MEDIUM => DebugSeverity::Medium,
+            gl::DEBUG_SEVERITY_LOW => DebugSeverity::Low,
+            _ => DebugSeverity::Notification,
+        }
+    }
+}
+
+/// What a debug message is about.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DebugType {
+    Error,
+    DeprecatedBehavior,
+    UndefinedBehavior,
+    Portability,
+    Performance,
+    Marker,
+    Other,
+}
+
+impl DebugType {
+    fn from_gl(ty: GLenum) -> DebugType {
+        match ty {
+            gl::DEBUG_TYPE_ERROR => DebugType::Error,
+            gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => DebugType::DeprecatedBehavior,
+            gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => DebugType::UndefinedBehavior,
+            gl::DEBUG_TYPE_PORTABILITY => DebugType::Portability,
+            gl::DEBUG_TYPE_PERFORMANCE => DebugType::Performance,
+            gl::DEBUG_TYPE_MARKER => DebugType::Marker,
+            _ => DebugType::Other,
+        }
+    }
+}
+
+/// A message reported by the driver.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DebugMessage<'a> {
+    pub severity: DebugSeverity,
+    pub ty: DebugType,
+    /// Implementation-specific identifier of the message.
+    pub id: u32,
+    pub text: &'a str,
+}
+
+/// User callback receiving the messages of at least a given severity.
+pub struct DebugCallback {
+    pub min_severity: DebugSeverity,
+    pub callback: Box<FnMut(&DebugMessage)>,
+}
+
+pub extern "system" fn receive(_source: GLenum, ty: GLenum, id: GLuint, severity: GLenum,
+                               length: GLsizei, text: *const GLchar, user: *mut c_void) {
+    let callback = unsafe { &mut *(user as *mut DebugCallback) };
+    let severity = DebugSeverity::from_gl(severity);
+    if severity < callback.min_severity {
+        return
+    }
+    let bytes = if length < 0 || text.is_null() {
+        &[][..]
+    } else {
+        unsafe { slice::from_raw_parts(text as *const u8, length as usize) }
+    };
+    let message = DebugMessage {
+        severity: severity,
+        ty: DebugType::from_gl(ty),
+        id: id,
+        text: str::from_utf8(bytes).unwrap_or("<invalid UTF-8>"),
+    };
+    // unwinding into the driver is undefined behavior
+    if panic::catch_unwind(AssertUnwindSafe(|| (callback.callback)(&message))).is_err() {
+        error!("GL: debug callback panicked on {:?}", message);
+        process::abort();
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cell::RefCell;
 use std::rc::Rc;
 use std::{slice, ptr};
 
@@ -23,9 +24,10 @@ use core::handle::{self, Producer};
 use core::target::{Layer, Level};
 
 use command::{CommandBuffer, COLOR_DEFAULT};
+use info::PrivateCaps;
 use {Resources as R, Share, OutputMerger};
 use {Buffer, BufferElement, FatSampler, NewTexture,
-     PipelineState, ResourceView, TargetView, Fence};
+     PipelineState, ResourceView, Sampler, TargetView, Texture, Fence};
 
 
 pub fn role_to_target(role: buffer::Role) -> gl::types::GLenum {
@@ -53,8 +55,29 @@ fn access_to_gl(access: memory::Access) -> gl::types::GLenum {
     }
 }
 
-pub fn update_sub_buffer(gl: &gl::Gl, buffer: Buffer, address: *const u8,
-                         size: usize, offset: usize, role: buffer::Role) {
+fn is_half_float(format: d::format::Format) -> bool {
+    use core::format::SurfaceType as S;
+    match format {
+        d::format::Format(S::R16, ChannelType::Float) |
+        d::format::Format(S::R16_G16, ChannelType::Float) |
+        d::format::Format(S::R16_G16_B16, ChannelType::Float) |
+        d::format::Format(S::R16_G16_B16_A16, ChannelType::Float) => true,
+        _ => false,
+    }
+}
+
+pub fn update_sub_buffer(gl: &gl::Gl, private_caps: &PrivateCaps, buffer: Buffer,
+                         address: *const u8, size: usize, offset: usize, role: buffer::Role) {
+    if private_caps.direct_state_access_supported {
+        unsafe {
+            gl.NamedBufferSubData(buffer,
+                offset as gl::types::GLintptr,
+                size as gl::types::GLsizeiptr,
+                address as *const gl::types::GLvoid
+            );
+        }
+        return
+    }
     let target = role_to_target(role);
     unsafe {
         gl.BindBuffer(target, buffer);
@@ -73,6 +96,117 @@ pub struct Factory {
     frame_handles: handle::Manager<R>,
 }
 
+/// A resource that can be given a debug label, shown by tools like
+/// RenderDoc and apitrace in place of its GL name.
+pub trait DebugObject {
+    /// Return the GL identifier and name of the object.
+    fn get_debug_name(&self) -> (gl::types::GLenum, gl::types::GLuint);
+}
+
+impl DebugObject for handle::RawBuffer<R> {
+    fn get_debug_name(&self) -> (gl::types::GLenum, gl::types::GLuint) {
+        (gl::BUFFER, *self.resource())
+    }
+}
+
+impl<T> DebugObject for handle::Buffer<R, T> {
+    fn get_debug_name(&self) -> (gl::types::GLenum, gl::types::GLuint) {
+        self.raw().get_debug_name()
+    }
+}
+
+impl DebugObject for handle::RawTexture<R> {
+    fn get_debug_name(&self) -> (gl::types::GLenum, gl::types::GLuint) {
+        match *self.resource() {
+            NewTexture::Surface(name) => (gl::RENDERBUFFER, name),
+            NewTexture::Texture(name) => (gl::TEXTURE, name),
+        }
+    }
+}
+
+impl<T> DebugObject for handle::Texture<R, T> {
+    fn get_debug_name(&self) -> (gl::types::GLenum, gl::types::GLuint) {
+        self.raw().get_debug_name()
+    }
+}
+
+impl DebugObject for handle::Program<R> {
+    fn get_debug_name(&self) -> (gl::types::GLenum, gl::types::GLuint) {
+        (gl::PROGRAM, *self.resource())
+    }
+}
+
+/// The value of a texture made resident with `ARB_bindless_texture`, to be
+/// passed to the shaders through a constant buffer instead of being bound
+/// to a slot.
+///
+/// In a constant structure it maps to a `uvec2`, which GLSL converts to a
+/// sampler with `sampler2D(handle)`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(C)]
+pub struct TextureHandle(pub u64);
+
+unsafe impl memory::Pod for TextureHandle {}
+
+impl d::shade::Formatted for TextureHandle {
+    fn get_format() -> d::shade::ConstFormat {
+        (d::shade::BaseType::U32, d::shade::ContainerType::Vector(2))
+    }
+}
+
+/// A texture view and sampler made resident, keeping both alive for as
+/// long as the shaders may reach them by its handle.
+#[derive(Debug)]
+pub struct ResidentTexture {
+    view: handle::RawShaderResourceView<R>,
+    sampler: handle::Sampler<R>,
+    handle: TextureHandle,
+}
+
+impl ResidentTexture {
+    /// Get the handle to write into the constant buffers.
+    pub fn get_handle(&self) -> TextureHandle {
+        self.handle
+    }
+
+    /// Get the texture view.
+    pub fn get_view(&self) -> &handle::RawShaderResourceView<R> {
+        &self.view
+    }
+
+    /// Get the sampler.
+    pub fn get_sampler(&self) -> &handle::Sampler<R> {
+        &self.sampler
+    }
+}
+
+/// A resident handle, with the GL names of its texture and sampler. It is
+/// made non-resident before any of them is deleted, and the number of
+/// `ResidentTexture`s sharing it is counted, as GL returns the same handle
+/// for the same texture and sampler.
+#[derive(Clone, Copy, Debug)]
+pub struct Residency {
+    pub handle: u64,
+    pub texture: Texture,
+    pub sampler: Sampler,
+    pub count: usize,
+}
+
+/// Make the handles using a texture or sampler about to be deleted
+/// non-resident, with `uses` telling which ones.
+pub fn release_residencies<F>(gl: &gl::Gl, residencies: &RefCell<Vec<Residency>>, uses: F)
+    where F: Fn(&Residency) -> bool
+{
+    residencies.borrow_mut().retain(|residency| {
+        if uses(residency) {
+            unsafe { gl.MakeTextureHandleNonResidentARB(residency.handle) };
+            false
+        } else {
+            true
+        }
+    });
+}
+
 impl Clone for Factory {
     fn clone(&self) -> Factory {
         Factory::new(self.share.clone())
@@ -92,6 +226,131 @@ impl Factory {
         CommandBuffer::new(self.create_fbo_internal())
     }
 
+    /// Give a debug label to a resource. Does nothing without `KHR_debug`.
+    pub fn set_label<T: DebugObject>(&mut self, object: &T, label: &str) {
+        if !self.share.private_caps.debug_supported {
+            return
+        }
+        let (identifier, name) = object.get_debug_name();
+        unsafe {
+            self.share.context.ObjectLabel(identifier, name,
+                label.len() as gl::types::GLsizei,
+                label.as_ptr() as *const gl::types::GLchar);
+        }
+    }
+
+    /// Check if textures can be made resident, see `make_texture_resident`.
+    pub fn is_bindless_supported(&self) -> bool {
+        self.share.private_caps.bindless_texture_supported
+    }
+
+    /// Make a texture view resident, sampled with `sampler`, and get the
+    /// handle the shaders can reach it by. Returns `None` without
+    /// `ARB_bindless_texture`.
+    ///
+    /// Neither the texture nor the sampler can be changed afterwards. Both
+    /// are kept alive by the returned `ResidentTexture`, and the handle is
+    /// made non-resident when the last of them is destroyed. Resident
+    /// textures are never bound, so a draw using only them costs no
+    /// texture binds at all.
+    pub fn make_texture_resident<T>(&mut self, view: &handle::ShaderResourceView<R, T>,
+                                    sampler: &handle::Sampler<R>) -> Option<ResidentTexture> {
+        if !self.is_bindless_supported() {
+            return None
+        }
+        let gl = &self.share.context;
+        let texture = self.frame_handles.ref_srv(view.raw()).object;
+        let sampler_name = self.frame_handles.ref_sampler(sampler).object;
+        let mut residencies = self.share.residencies.borrow_mut();
+        let handle = match residencies.iter_mut()
+                                      .find(|r| r.texture == texture && r.sampler == sampler_name) {
+            Some(residency) => {
+                residency.count += 1;
+                residency.handle
+            }
+            None => {
+                let handle = unsafe {
+                    let handle = match sampler_name {
+                        0 => gl.GetTextureHandleARB(texture),
+                        name => gl.GetTextureSamplerHandleARB(texture, name),
+                    };
+                    gl.MakeTextureHandleResidentARB(handle);
+                    handle
+                };
+                residencies.push(Residency {
+                    handle: handle,
+                    texture: texture,
+                    sampler: sampler_name,
+                    count: 1,
+                });
+                handle
+            }
+        };
+        Some(ResidentTexture {
+            view: view.raw().clone(),
+            sampler: sampler.clone(),
+            handle: TextureHandle(handle),
+        })
+    }
+
+    /// Make a texture non-resident again, unless other `ResidentTexture`s
+    /// share its handle, releasing its texture view and sampler.
+    pub fn make_texture_non_resident(&mut self, resident: ResidentTexture) {
+        let gl = &self.share.context;
+        let mut residencies = self.share.residencies.borrow_mut();
+        if let Some(pos) = residencies.iter().position(|r| r.handle == resident.handle.0) {
+            residencies[pos].count -= 1;
+            if residencies[pos].count == 0 {
+                unsafe { gl.MakeTextureHandleNonResidentARB(resident.handle.0) };
+                residencies.swap_remove(pos);
+            }
+        }
+    }
+
+    /// View all the layers of a 2D array texture as a color target of
+    /// `OVR_multiview` shaders, which render each view into its own layer
+    /// in a single draw. All the targets of such draws have to be multiview.
+    pub fn view_texture_as_multiview_render_target<T: d::format::RenderFormat>(
+        &mut self, tex: &handle::Texture<R, T::Surface>, level: Level)
+        -> Result<handle::RenderTargetView<R, T>, f::TargetViewError>
+    {
+        if !tex.get_info().bind.contains(memory::RENDER_TARGET) {
+            return Err(f::TargetViewError::NoBindFlag)
+        }
+        let view = try!(self.view_texture_as_multiview(tex.raw(), level));
+        let dim = tex.get_info().kind.get_level_dimensions(level);
+        Ok(Typed::new(self.share.handles.borrow_mut().make_rtv(view, tex.raw(), dim)))
+    }
+
+    /// View all the layers of a 2D array texture as the depth target of
+    /// `OVR_multiview` shaders, see `view_texture_as_multiview_render_target`.
+    pub fn view_texture_as_multiview_depth_stencil<T: d::format::DepthFormat>(
+        &mut self, tex: &handle::Texture<R, T::Surface>, level: Level)
+        -> Result<handle::DepthStencilView<R, T>, f::TargetViewError>
+    {
+        if !tex.get_info().bind.contains(memory::DEPTH_STENCIL) {
+            return Err(f::TargetViewError::NoBindFlag)
+        }
+        let view = try!(self.view_texture_as_multiview(tex.raw(), level));
+        let dim = tex.get_info().kind.get_level_dimensions(level);
+        Ok(Typed::new(self.share.handles.borrow_mut().make_dsv(view, tex.raw(), dim)))
+    }
+
+    fn view_texture_as_multiview(&mut self, htex: &handle::RawTexture<R>, level: Level)
+                                 -> Result<TargetView, f::TargetViewError> {
+        if !self.share.capabilities.multiview_supported {
+            return Err(f::TargetViewError::Unsupported)
+        }
+        if level >= htex.get_info().levels {
+            return Err(f::TargetViewError::Level(level))
+        }
+        match (self.frame_handles.ref_texture(htex), htex.get_info().kind) {
+            (&NewTexture::Texture(t), t::Kind::D2Array(_, _, layers, _)) =>
+                Ok(TargetView::Multiview(t, level, layers)),
+            _ => Err(f::TargetViewError::Unsupported),
+        }
+    }
+
     fn create_fbo_internal(&mut self) -> gl::types::GLuint {
         let gl = &self.share.context;
         let mut name = 0 as ::FrameBuffer;
@@ -319,6 +578,7 @@ impl f::Factory<R> for Factory {
         }
         let name = self.create_buffer_internal();
         let mapping = self.init_buffer(name, &info, None);
+        self.share.count_buffer(&info);
         Ok(self.share.handles.borrow_mut().make_buffer(name, info, mapping))
     }
 
@@ -333,6 +593,7 @@ impl f::Factory<R> for Factory {
             stride: stride,
         };
         let mapping = self.init_buffer(name, &info, Some(data));
+        self.share.count_buffer(&info);
         Ok(self.share.handles.borrow_mut().make_buffer(name, info, mapping))
     }
 
@@ -357,6 +618,16 @@ impl f::Factory<R> for Factory {
                 return Err(d::pso::CreationError),
             _ => ()
         }
+        if !caps.half_float_vertex_supported &&
+            desc.attributes.iter().any(|at| at.map_or(false, |at| is_half_float(at.1.format))) {
+            error!("Half-float vertex attributes are not supported by the device");
+            return Err(d::pso::CreationError)
+        }
+        if !caps.half_float_color_supported &&
+            desc.color_targets.iter().any(|ct| ct.map_or(false, |ct| is_half_float(ct.0))) {
+            error!("Half-float color targets are not supported by the device");
+            return Err(d::pso::CreationError)
+        }
         let mut output = OutputMerger {
             draw_mask: 0,
             stencil: match desc.depth_stencil {
@@ -394,6 +665,7 @@ impl f::Factory<R> for Factory {
             input: inputs,
             scissor: desc.scissor,
             rasterizer: desc.rasterizer,
+            point: desc.point,
             output: output,
         };
         Ok(self.share.handles.borrow_mut().make_pso(pso, program))
@@ -414,16 +686,27 @@ impl f::Factory<R> for Factory {
         if dim.1 as usize > max_size {
             return Err(CreationError::Size(dim.1));
         }
+        let dev_caps = &self.share.capabilities;
+        match desc.kind {
+            t::Kind::D1(_) | t::Kind::D1Array(_, _) if !dev_caps.texture_1d_supported =>
+                return Err(CreationError::Kind),
+            _ => (),
+        }
         let cty = hint.unwrap_or(ChannelType::Uint); //careful here
         let gl = &self.share.context;
         let object = if desc.bind.intersects(SHADER_RESOURCE | UNORDERED_ACCESS) || data_opt.is_some() {
+            // multisampled render surfaces don't need texture support
+            let aa = dim.3;
+            if aa != t::AaMode::Single && !dev_caps.texture_multisample_supported {
+                return Err(CreationError::Samples(aa))
+            }
             let name = if caps.immutable_storage_supported {
                 try!(tex::make_with_storage(gl, &desc, cty))
             } else {
                 try!(tex::make_without_storage(gl, &desc, cty))
             };
             if let Some(data) = data_opt {
-                try!(tex::init_texture_data(gl, name, desc, cty, data));
+                try!(tex::init_texture_data(gl, caps, name, desc, cty, data));
             }
             NewTexture::Texture(name)
         }else {
@@ -433,11 +716,15 @@ impl f::Factory<R> for Factory {
         if let Err(err) = self.share.check() {
             panic!("Error {:?} creating texture: {:?}, hint: {:?}", err, desc, hint)
         }
+        self.share.count_texture(&desc);
         Ok(self.share.handles.borrow_mut().make_texture(object, desc))
     }
 
     fn view_buffer_as_shader_resource_raw(&mut self, hbuf: &handle::RawBuffer<R>)
                                       -> Result<handle::RawShaderResourceView<R>, f::ResourceViewError> {
+        if !self.share.capabilities.texture_buffer_supported {
+            return Err(f::ResourceViewError::Unsupported)
+        }
         let gl = &self.share.context;
         let mut name = 0 as gl::types::GLuint;
         let buf_name = *self.frame_handles.ref_buffer(hbuf);
@@ -459,14 +746,14 @@ impl f::Factory<R> for Factory {
         Err(f::ResourceViewError::Unsupported) //TODO
     }
 
-    fn view_texture_as_shader_resource_raw(&mut self, htex: &handle::RawTexture<R>, _desc: t::ResourceDesc)
+    fn view_texture_as_shader_resource_raw(&mut self, htex: &handle::RawTexture<R>, desc: t::ResourceDesc)
                                        -> Result<handle::RawShaderResourceView<R>, f::ResourceViewError> {
         match self.frame_handles.ref_texture(htex) {
             &NewTexture::Surface(_) => Err(f::ResourceViewError::NoBindFlag),
             &NewTexture::Texture(t) => {
                 //TODO: use the view descriptor
                 let view = ResourceView::new_texture(t, htex.get_info().kind);
-                Ok(self.share.handles.borrow_mut().make_texture_srv(view, htex))
+                Ok(self.share.handles.borrow_mut().make_texture_srv(view, htex, desc.channel))
             },
         }
     }
@@ -478,6 +765,10 @@ impl f::Factory<R> for Factory {
 
     fn view_texture_as_render_target_raw(&mut self, htex: &handle::RawTexture<R>, desc: t::RenderDesc)
                                          -> Result<handle::RawRenderTargetView<R>, f::TargetViewError> {
+        let format = d::format::Format(htex.get_info().format, desc.channel);
+        if is_half_float(format) && !self.share.capabilities.half_float_color_supported {
+            return Err(f::TargetViewError::Unsupported)
+        }
         self.view_texture_as_target(htex, desc.level, desc.layer)
             .map(|view| {
                 let dim = htex.get_info().kind.get_level_dimensions(desc.level);
@@ -553,6 +844,14 @@ impl f::Factory<R> for Factory {
     }
 }
 
+/// Check if the GPU has reached a fence, without blocking.
+pub fn is_fence_signaled(fence: &Fence, gl: &gl::Gl) -> bool {
+    match unsafe { gl.ClientWaitSync(fence.0, 0, 0) } {
+        gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => true,
+        _ => false,
+    }
+}
+
 pub fn wait_fence(fence: &Fence, gl: &gl::Gl) {
     let timeout = 1_000_000_000_000;
     // TODO: use the return value of this call
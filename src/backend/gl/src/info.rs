@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::collections::HashSet;
-use std::{ffi, fmt, mem, str};
+use std::{cmp, ffi, fmt, mem, str};
 use gl;
 use core::Capabilities;
 
@@ -172,6 +172,14 @@ pub struct PrivateCaps {
     pub clear_buffer_supported: bool,
     pub frag_data_location_supported: bool,
     pub sampler_lod_bias_supported: bool,
+    pub get_tex_image_supported: bool,
+    pub sync_supported: bool,
+    pub debug_supported: bool,
+    pub memory_barrier_supported: bool,
+    pub pipeline_statistics_supported: bool,
+    pub bindless_texture_supported: bool,
+    pub direct_state_access_supported: bool,
+    pub robustness_supported: bool,
 }
 
 /// OpenGL implementation information
@@ -255,12 +263,27 @@ pub fn get(gl: &gl::Gl) -> (Info, Capabilities, PrivateCaps) {
     use self::Requirement::*;
     let info = Info::get(gl);
     let tessellation_supported =           info.is_supported(&[Core(4,0),
+                                                               Es  (3,2),
                                                                Ext("GL_ARB_tessellation_shader")]);
+    let constant_buffer_supported = info.is_supported(&[Core(3,1),
+                                                        Es  (3,0),
+                                                        Ext ("GL_ARB_uniform_buffer_object")]);
+    let frame_buffer_supported = info.is_supported(&[Core(3,0),
+                                                     Es  (2,0),
+                                                     Ext ("GL_ARB_framebuffer_object")]);
+    let max_color_targets = if frame_buffer_supported {
+        cmp::min(get_usize(gl, gl::MAX_COLOR_ATTACHMENTS), core::MAX_COLOR_TARGETS)
+    } else {
+        1
+    };
     let caps = Capabilities {
         max_vertex_count: get_usize(gl, gl::MAX_ELEMENTS_VERTICES),
         max_index_count:  get_usize(gl, gl::MAX_ELEMENTS_INDICES),
         max_texture_size: get_usize(gl, gl::MAX_TEXTURE_SIZE),
         max_patch_size: if tessellation_supported { get_usize(gl, gl::MAX_PATCH_VERTICES) } else {0},
+        max_constant_buffer_size: if constant_buffer_supported { get_usize(gl, gl::MAX_UNIFORM_BLOCK_SIZE) } else {0},
+        max_color_targets: max_color_targets,
+        constant_buffer_offset_alignment: if constant_buffer_supported { get_usize(gl, gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT) } else {0},
 
         instance_base_supported:           info.is_supported(&[Core(4,2),
                                                                Ext ("GL_ARB_base_instance")]),
@@ -275,9 +298,7 @@ pub fn get(gl: &gl::Gl) -> (Info, Capabilities, PrivateCaps) {
                                                                Ext ("GL_ARB_draw_elements_base_vertex")]),
         srgb_color_supported:              info.is_supported(&[Core(3,2),
                                                                Ext ("GL_ARB_framebuffer_sRGB")]),
-        constant_buffer_supported:         info.is_supported(&[Core(3,1),
-                                                               Es  (3,0),
-                                                               Ext ("GL_ARB_uniform_buffer_object")]),
+        constant_buffer_supported:         constant_buffer_supported,
         unordered_access_view_supported:   info.is_supported(&[Core(4,0)]), //TODO: extension
         separate_blending_slots_supported: info.is_supported(&[Core(4,0),
                                                                Es  (3,0),
@@ -286,15 +307,45 @@ pub fn get(gl: &gl::Gl) -> (Info, Capabilities, PrivateCaps) {
                                                                Es  (3,0),
                                                                Ext ("GL_ARB_copy_buffer"),
                                                                Ext ("GL_NV_copy_buffer")]),
+        polygon_mode_supported:            !info.version.is_embedded,
+        texture_1d_supported:              !info.version.is_embedded,
+        texture_multisample_supported:     info.is_supported(&[Core(3,2),
+                                                               Es  (3,1),
+                                                               Ext ("GL_ARB_texture_multisample")]),
+        texture_buffer_supported:          info.is_supported(&[Core(3,1),
+                                                               Es  (3,2),
+                                                               Ext ("GL_ARB_texture_buffer_object"),
+                                                               Ext ("GL_EXT_texture_buffer")]),
+        half_float_vertex_supported:       info.is_supported(&[Core(3,0),
+                                                               Es  (3,0),
+                                                               Ext ("GL_ARB_half_float_vertex")]),
+        half_float_color_supported:        info.is_supported(&[Core(3,0),
+                                                               Es  (3,2),
+                                                               Ext ("GL_EXT_color_buffer_half_float"),
+                                                               Ext ("GL_EXT_color_buffer_float")]),
+        index_u8_supported:                true,
+        atomic_counter_supported:          info.is_supported(&[Core(4,2),
+                                                               Es  (3,1),
+                                                               Ext ("GL_ARB_shader_atomic_counters")]),
+        multiview_supported:               info.is_supported(&[Ext ("GL_OVR_multiview")]),
+        layered_rendering_supported:       info.is_supported(&[Core(3,2),
+                                                               Es  (3,2),
+                                                               Ext ("GL_ARB_geometry_shader4"),
+                                                               Ext ("GL_EXT_geometry_shader")]),
+        occlusion_query_supported:         info.is_supported(&[Core(1,5),
+                                                               Es  (3,0),
+                                                               Ext ("GL_ARB_occlusion_query"),
+                                                               Ext ("GL_EXT_occlusion_query_boolean")]),
+        timer_query_supported:             info.is_supported(&[Core(3,3),
+                                                               Ext ("GL_ARB_timer_query")]),
     };
     let private = PrivateCaps {
         array_buffer_supported:            info.is_supported(&[Core(3,0),
                                                                Es  (3,0),
                                                                Ext ("GL_ARB_vertex_array_object")]),
-        frame_buffer_supported:            info.is_supported(&[Core(3,0),
-                                                               Es  (2,0),
-                                                               Ext ("GL_ARB_framebuffer_object")]),
+        frame_buffer_supported:            frame_buffer_supported,
         immutable_storage_supported:       info.is_supported(&[Core(3,2),
+                                                               Es  (3,0),
                                                                Ext ("GL_ARB_texture_storage")]),
         sampler_objects_supported:         info.is_supported(&[Core(3,3),
                                                                Es  (3,0),
@@ -307,6 +358,24 @@ pub fn get(gl: &gl::Gl) -> (Info, Capabilities, PrivateCaps) {
                                                                Es  (3,0)]),
         frag_data_location_supported:      !info.version.is_embedded,
         sampler_lod_bias_supported:        !info.version.is_embedded,
+        get_tex_image_supported:           !info.version.is_embedded,
+        sync_supported:                    info.is_supported(&[Core(3,2),
+                                                               Es  (3,0),
+                                                               Ext ("GL_ARB_sync")]),
+        debug_supported:                   info.is_supported(&[Core(4,3),
+                                                               Es  (3,2),
+                                                               Ext ("GL_KHR_debug")]),
+        memory_barrier_supported:          info.is_supported(&[Core(4,2),
+                                                               Es  (3,1),
+                                                               Ext ("GL_ARB_shader_image_load_store")]),
+        pipeline_statistics_supported:     info.is_supported(&[Core(4,6),
+                                                               Ext ("GL_ARB_pipeline_statistics_query")]),
+        bindless_texture_supported:        info.is_supported(&[Ext ("GL_ARB_bindless_texture")]),
+        direct_state_access_supported:     info.is_supported(&[Core(4,5),
+                                                               Ext ("GL_ARB_direct_state_access")]),
+        robustness_supported:              info.is_supported(&[Core(4,5),
+                                                               Es  (3,2),
+                                                               Ext ("GL_KHR_robustness")]),
     };
     (info, caps, private)
 }
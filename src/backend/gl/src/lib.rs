@@ -23,20 +23,27 @@ extern crate log;
 extern crate gfx_gl as gl;
 extern crate gfx_core as core;
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::mem;
 use std::rc::Rc;
 use core::{self as c, handle, state as s, format, pso, texture, memory, command as com, buffer};
 use core::target::{Layer, Level};
-use command::{Command, DataBuffer};
+use command::{Command, DataBuffer, VertexArray, VertexArrayCache};
 use factory::MappingKind;
 
-pub use self::command::CommandBuffer;
-pub use self::factory::Factory;
+pub use self::command::{CommandBuffer, VertexArrayCache};
+pub use self::debug::{DebugMessage, DebugSeverity, DebugType};
+pub use self::factory::{DebugObject, Factory, ResidentTexture, TextureHandle};
 pub use self::info::{Info, PlatformName, Version};
+pub use self::loader::{create_loader, Loader, Upload};
 
 mod command;
+mod debug;
 mod factory;
 mod info;
+mod loader;
+mod query;
 mod shade;
 mod state;
 mod tex;
@@ -95,6 +102,7 @@ pub struct PipelineState {
     input: [Option<BufferElement>; c::MAX_VERTEX_ATTRIBUTES],
     scissor: bool,
     rasterizer: s::Rasterizer,
+    point: pso::PointInfo,
     output: OutputMerger,
 }
 
@@ -139,6 +147,8 @@ pub enum TargetView {
     Surface(Surface),
     Texture(Texture, Level),
     TextureLayer(Texture, Level, Layer),
+    /// All the layers of an array texture, one per view of `OVR_multiview`.
+    Multiview(Texture, Level, Layer),
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -207,6 +217,52 @@ pub fn create_main_targets_raw(dim: texture::Dimensions, color_format: format::S
     (m_color, m_ds)
 }
 
+/// Create the target views (RTV and DSV) of an offscreen main framebuffer,
+/// backed by textures attached to an FBO instead of the window surface.
+/// This is what headless contexts render into, since they may have no
+/// default framebuffer at all. The color texture is returned as well,
+/// so that the results can be copied back to a buffer.
+pub fn create_offscreen_targets_raw(factory: &mut Factory, dim: texture::Dimensions,
+                                    color_format: format::Format, depth_format: format::Format)
+                                    -> Result<(handle::RawTexture<Resources>,
+                                               handle::RawRenderTargetView<Resources>,
+                                               handle::RawDepthStencilView<Resources>),
+                                              c::factory::CombinedError> {
+    use c::Factory as CoreFactory;
+    let kind = texture::Kind::D2(dim.0, dim.1, dim.3);
+    let color_tex = try!(factory.create_texture_raw(
+        texture::Info {
+            levels: 1,
+            kind: kind,
+            format: color_format.0,
+            bind: memory::RENDER_TARGET | memory::TRANSFER_SRC,
+            usage: memory::Usage::Data,
+        },
+        Some(color_format.1), None));
+    let depth_tex = try!(factory.create_texture_raw(
+        texture::Info {
+            levels: 1,
+            kind: kind,
+            format: depth_format.0,
+            bind: memory::DEPTH_STENCIL,
+            usage: memory::Usage::Data,
+        },
+        Some(depth_format.1), None));
+    let color_view = try!(factory.view_texture_as_render_target_raw(&color_tex,
+        texture::RenderDesc {
+            channel: color_format.1,
+            level: 0,
+            layer: None,
+        }));
+    let depth_view = try!(factory.view_texture_as_depth_stencil_raw(&depth_tex,
+        texture::DepthStencilDesc {
+            level: 0,
+            layer: None,
+            flags: texture::DepthStencilFlags::empty(),
+        }));
+    Ok((color_tex, color_view, depth_view))
+}
+
 /// Internal struct of shared data between the device and its factories.
 #[doc(hidden)]
 pub struct Share {
@@ -214,12 +270,95 @@ pub struct Share {
     capabilities: c::Capabilities,
     private_caps: info::PrivateCaps,
     handles: RefCell<handle::Manager<Resources>>,
+    stats: RefCell<DeviceStats>,
+    /// Whether the errors of this context are forwarded to a debug callback.
+    /// Each context has its own `Share`, so the `GetError` checks of a loader
+    /// are kept when only the device has a callback, and the other way around.
+    debug_output: Cell<bool>,
+    residencies: RefCell<Vec<factory::Residency>>,
+}
+
+/// The reason of a context loss, as reported by the driver.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ContextLoss {
+    /// The context caused the reset, like with a shader running too long.
+    Guilty,
+    /// Another context caused the reset.
+    Innocent,
+    /// The cause is not known, like when the GPU got removed or the system
+    /// reclaimed the context of a background application.
+    Unknown,
+}
+
+/// Resource and workload statistics of a device, for budget overlays and
+/// leak detection. Sizes are estimates, the driver may allocate more.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DeviceStats {
+    /// Number of live buffers.
+    pub buffer_count: usize,
+    /// Total size of the live buffers, in bytes.
+    pub buffer_bytes: usize,
+    /// Number of live textures.
+    pub texture_count: usize,
+    /// Estimated size of the live textures, in bytes.
+    pub texture_bytes: usize,
+    /// Bytes uploaded by buffer and texture updates during the last frame.
+    pub upload_bytes: usize,
+    /// Number of draw calls during the last frame.
+    pub draw_count: usize,
+    /// Number of state changes during the last frame, after the redundant
+    /// ones got filtered out.
+    pub state_change_count: usize,
+}
+
+impl DeviceStats {
+    fn count_command(&mut self, cmd: &Command, data_buf: &DataBuffer) {
+        match *cmd {
+            Command::Draw(..) | Command::DrawIndexed(..) => self.draw_count += 1,
+            Command::UpdateBuffer(_, pointer, _) |
+            Command::UpdateTexture(_, _, _, pointer, _) => {
+                self.upload_bytes += data_buf.get(pointer).len();
+            },
+            Command::Clear(..) |
+            Command::CopyBuffer(..) |
+            Command::CopyBufferToTexture(..) |
+            Command::CopyTextureToBuffer(..) |
+            Command::GenerateMipmap(..) |
+            Command::_Blit(..) |
+            Command::MemoryBarrier(..) |
+            Command::BeginOcclusionQuery(..) |
+            Command::EndOcclusionQuery |
+            Command::BeginTimerQuery(..) |
+            Command::EndTimerQuery |
+            Command::PushDebugGroup(..) |
+            Command::PopDebugGroup => (),
+            _ => self.state_change_count += 1,
+        }
+    }
+}
+
+/// Estimate the memory taken by a texture, including its mipmaps.
+fn get_texture_size(info: &texture::Info) -> usize {
+    use std::cmp::max;
+    let (_, _, layers, aa) = info.kind.get_dimensions();
+    let is_3d = match info.kind {
+        texture::Kind::D3(..) => true,
+        _ => false,
+    };
+    let texels = (0 .. max(info.levels, 1)).map(|level| {
+        let (w, h, d, _) = info.kind.get_level_dimensions(level);
+        let depth = if is_3d { d } else { max(layers, 1) };
+        w as usize * max(h, 1) as usize * depth as usize
+    }).fold(0, |sum, count| sum + count);
+    let bytes = (info.format.get_total_bits() as usize + 7) / 8;
+    texels * bytes * aa.get_num_fragments() as usize
 }
 
 impl Share {
     /// Fails during a debug build if the implementation's error flag was set.
+    /// Errors are reported to the debug callback instead, when there is one.
     pub fn check(&self) -> Result<(), Error> {
-        if cfg!(debug_assertions) {
+        if cfg!(debug_assertions) && !self.debug_output.get() {
             let gl = &self.context;
             let err = Error::from_error_code(unsafe { gl.GetError() });
             if err != Error::NoError {
@@ -228,6 +367,60 @@ impl Share {
         }
         Ok(())
     }
+
+    fn count_buffer(&self, info: &buffer::Info) {
+        let mut stats = self.stats.borrow_mut();
+        stats.buffer_count += 1;
+        stats.buffer_bytes += info.size;
+    }
+
+    fn count_texture(&self, info: &texture::Info) {
+        let mut stats = self.stats.borrow_mut();
+        stats.texture_count += 1;
+        stats.texture_bytes += get_texture_size(info);
+    }
+}
+
+/// Get the component count and the GL type of a vertex attribute.
+fn attribute_format(format: format::Format) -> Option<(gl::types::GLint, gl::types::GLenum)> {
+    use core::format::SurfaceType as S;
+    use core::format::ChannelType as C;
+    let (fm8, fm16, fm32) = match format.1 {
+        C::Int | C::Inorm =>
+            (gl::BYTE, gl::SHORT, gl::INT),
+        C::Uint | C::Unorm =>
+            (gl::UNSIGNED_BYTE, gl::UNSIGNED_SHORT, gl::UNSIGNED_INT),
+        C::Float => (gl::ZERO, gl::HALF_FLOAT, gl::FLOAT),
+        C::Srgb => {
+            error!("Unsupported Srgb channel type");
+            return None
+        }
+    };
+    Some(match format.0 {
+        S::R8              => (1, fm8),
+        S::R8_G8           => (2, fm8),
+        S::R8_G8_B8_A8     => (4, fm8),
+        S::R10_G10_B10_A2  => (4, match format.1 {
+            C::Inorm => gl::INT_2_10_10_10_REV,
+            C::Unorm => gl::UNSIGNED_INT_2_10_10_10_REV,
+            _ => {
+                error!("Packed attributes have to be normalized, got {:?}", format.1);
+                return None
+            }
+        }),
+        S::R16             => (1, fm16),
+        S::R16_G16         => (2, fm16),
+        S::R16_G16_B16     => (3, fm16),
+        S::R16_G16_B16_A16 => (4, fm16),
+        S::R32             => (1, fm32),
+        S::R32_G32         => (2, fm32),
+        S::R32_G32_B32     => (3, fm32),
+        S::R32_G32_B32_A32 => (4, fm32),
+        _ => {
+            error!("Unsupported element type: {:?}", format.0);
+            return None
+        }
+    })
 }
 
 /// An OpenGL device with GLSL shaders.
@@ -235,10 +428,82 @@ pub struct Device {
     info: Info,
     share: Rc<Share>,
     vao: ArrayBuffer,
+    vertex_arrays: VertexArrayCache,
+    /// Textures bound to each unit, and the sampler states applied to the
+    /// textures themselves when sampler objects are not supported.
+    texture_units: [Option<Texture>; c::MAX_RESOURCE_VIEWS],
+    texture_samplers: HashMap<Texture, texture::SamplerInfo>,
+    /// The program in use, and the values last uploaded to the uniforms
+    /// of each program, which keep them until they are changed.
+    program: Program,
+    uniform_values: HashMap<(Program, c::shade::Location), c::shade::UniformValue>,
     frame_handles: handle::Manager<Resources>,
+    pending_frames: VecDeque<(Fence, handle::Manager<Resources>)>,
+    frame_stats: DeviceStats,
+    last_frame_stats: DeviceStats,
+    statistics: Option<query::StatisticsQueries>,
+    occlusion_queries: query::OcclusionQueries,
+    timer_queries: query::TimerQueries,
+    context_loss: Option<ContextLoss>,
+    debug_callback: Option<Box<debug::DebugCallback>>,
     max_resource_count: Option<usize>,
 }
 
+/// Load the OpenGL symbols of the current context, detect its driver
+/// information, and initialize its permanent states. Returns the main
+/// VAO, bound to the context, along with the shared data.
+fn init_context<F>(fn_proc: F) -> (Info, Share, gl::types::GLuint) where
+    F: FnMut(&str) -> *const std::os::raw::c_void
+{
+    let gl = gl::Gl::load_with(fn_proc);
+    // query information
+    let (info, caps, private) = info::get(&gl);
+    info!("Vendor: {:?}", info.platform_name.vendor);
+    info!("Renderer: {:?}", info.platform_name.renderer);
+    info!("Version: {:?}", info.version);
+    info!("Shading Language: {:?}", info.shading_language);
+    debug!("Loaded Extensions:");
+    for extension in info.extensions.iter() {
+        debug!("- {}", *extension);
+    }
+    // initialize permanent states
+    if caps.srgb_color_supported {
+        unsafe {
+            gl.Enable(gl::FRAMEBUFFER_SRGB);
+        }
+    }
+    unsafe {
+        gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+
+        if !info.version.is_embedded {
+            gl.Enable(gl::PROGRAM_POINT_SIZE);
+        }
+    }
+    // create main VAO and bind it
+    let mut vao = 0;
+    if private.array_buffer_supported {
+        unsafe {
+            gl.GenVertexArrays(1, &mut vao);
+            gl.BindVertexArray(vao);
+        }
+    }
+    // create the shared context
+    let handles = handle::Manager::new();
+    let share = Share {
+        context: gl,
+        capabilities: caps,
+        private_caps: private,
+        handles: RefCell::new(handles),
+        stats: RefCell::new(DeviceStats::default()),
+        debug_output: Cell::new(false),
+        residencies: RefCell::new(Vec::new()),
+    };
+    if let Err(err) = share.check() {
+        panic!("Error {:?} after initialization", err)
+    }
+    (info, share, vao)
+}
+
 impl Device {
     /// Create a new device. Each GL context can only have a single
     /// Device on GFX side to represent it. //TODO: enforce somehow
@@ -246,55 +511,26 @@ impl Device {
     fn new<F>(fn_proc: F) -> Device where
         F: FnMut(&str) -> *const std::os::raw::c_void
     {
-        let gl = gl::Gl::load_with(fn_proc);
-        // query information
-        let (info, caps, private) = info::get(&gl);
-        info!("Vendor: {:?}", info.platform_name.vendor);
-        info!("Renderer: {:?}", info.platform_name.renderer);
-        info!("Version: {:?}", info.version);
-        info!("Shading Language: {:?}", info.shading_language);
-        debug!("Loaded Extensions:");
-        for extension in info.extensions.iter() {
-            debug!("- {}", *extension);
-        }
-        // initialize permanent states
-        if caps.srgb_color_supported {
-            unsafe {
-                gl.Enable(gl::FRAMEBUFFER_SRGB);
-            }
-        }
-        unsafe {
-            gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
-
-            if !info.version.is_embedded {
-                gl.Enable(gl::PROGRAM_POINT_SIZE);
-            }
-        }
-        // create main VAO and bind it
-        let mut vao = 0;
-        if private.array_buffer_supported {
-            unsafe {
-                gl.GenVertexArrays(1, &mut vao);
-                gl.BindVertexArray(vao);
-            }
-        }
-        // create the shared context
-        let handles = handle::Manager::new();
-        let share = Share {
-            context: gl,
-            capabilities: caps,
-            private_caps: private,
-            handles: RefCell::new(handles),
-        };
-        if let Err(err) = share.check() {
-            panic!("Error {:?} after initialization", err)
-        }
+        let (info, share, vao) = init_context(fn_proc);
         // create the device
         Device {
             info: info,
             share: Rc::new(share),
             vao: vao,
+            vertex_arrays: VertexArrayCache::new(),
+            texture_units: [None; c::MAX_RESOURCE_VIEWS],
+            texture_samplers: HashMap::new(),
+            program: 0,
+            uniform_values: HashMap::new(),
             frame_handles: handle::Manager::new(),
+            pending_frames: VecDeque::new(),
+            frame_stats: DeviceStats::default(),
+            last_frame_stats: DeviceStats::default(),
+            statistics: None,
+            occlusion_queries: query::OcclusionQueries::new(),
+            timer_queries: query::TimerQueries::new(),
+            context_loss: None,
+            debug_callback: None,
             max_resource_count: Some(999999),
         }
     }
@@ -311,36 +547,87 @@ impl Device {
         &self.info
     }
 
-    fn bind_attribute(&mut self, slot: c::AttributeSlot, buffer: Buffer, bel: BufferElement) {
-        use core::format::SurfaceType as S;
-        use core::format::ChannelType as C;
-        let (fm8, fm16, fm32) = match bel.elem.format.1 {
-            C::Int | C::Inorm =>
-                (gl::BYTE, gl::SHORT, gl::INT),
-            C::Uint | C::Unorm =>
-                (gl::UNSIGNED_BYTE, gl::UNSIGNED_SHORT, gl::UNSIGNED_INT),
-            C::Float => (gl::ZERO, gl::HALF_FLOAT, gl::FLOAT),
-            C::Srgb => {
-                error!("Unsupported Srgb channel type");
-                return
+    /// Forward the driver debug messages of at least `min_severity` to a
+    /// callback, replacing the previous one. Messages are delivered
+    /// synchronously, from within the GL call that caused them. Returns
+    /// `false` if the driver doesn't support `KHR_debug`.
+    pub fn set_debug_callback<F>(&mut self, min_severity: DebugSeverity, callback: F) -> bool where
+        F: FnMut(&DebugMessage) + 'static
+    {
+        if !self.share.private_caps.debug_supported {
+            return false
+        }
+        let mut boxed = Box::new(debug::DebugCallback {
+            min_severity: min_severity,
+            callback: Box::new(callback),
+        });
+        unsafe {
+            let gl = &self.share.context;
+            gl.Enable(gl::DEBUG_OUTPUT);
+            gl.Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+            gl.DebugMessageCallback(Some(debug::receive),
+                &mut *boxed as *mut debug::DebugCallback as *const std::os::raw::c_void);
+        }
+        self.debug_callback = Some(boxed);
+        self.share.debug_output.set(true);
+        true
+    }
+
+    /// Stop forwarding the driver debug messages.
+    pub fn clear_debug_callback(&mut self) {
+        if self.debug_callback.take().is_some() {
+            unsafe {
+                let gl = &self.share.context;
+                gl.DebugMessageCallback(None, std::ptr::null());
+                gl.Disable(gl::DEBUG_OUTPUT);
+                // the errors were reported to the callback, they must not
+                // show up in the next check
+                while gl.GetError() != gl::NO_ERROR {}
             }
-        };
-        let (count, gl_type) = match bel.elem.format.0 {
-            S::R8              => (1, fm8),
-            S::R8_G8           => (2, fm8),
-            S::R8_G8_B8_A8     => (4, fm8),
-            S::R16             => (1, fm16),
-            S::R16_G16         => (2, fm16),
-            S::R16_G16_B16     => (3, fm16),
-            S::R16_G16_B16_A16 => (4, fm16),
-            S::R32             => (1, fm32),
-            S::R32_G32         => (2, fm32),
-            S::R32_G32_B32     => (3, fm32),
-            S::R32_G32_B32_A32 => (4, fm32),
-            _ => {
-                error!("Unsupported element type: {:?}", bel.elem.format.0);
-                return
+            self.share.debug_output.set(false);
+        }
+    }
+
+    /// Get the live resource statistics, along with the workload of the
+    /// last frame, as delimited by calls to `cleanup`.
+    pub fn get_stats(&self) -> DeviceStats {
+        let last = &self.last_frame_stats;
+        DeviceStats {
+            upload_bytes: last.upload_bytes,
+            draw_count: last.draw_count,
+            state_change_count: last.state_change_count,
+            .. *self.share.stats.borrow()
+        }
+    }
+
+    /// Check whether the context has been lost, which is also done by
+    /// `cleanup` every frame. A lost context stays unusable: the device has
+    /// to be replaced, along with all the resources, which can be kept in a
+    /// `gfx::recreate::ResourceRegistry` for that purpose.
+    ///
+    /// Losses are only reported with `KHR_robustness`, by contexts created
+    /// with the `LOSE_CONTEXT_ON_RESET` notification strategy, which is
+    /// the case of ANGLE and most mobile drivers.
+    pub fn check_context_loss(&mut self) -> Option<ContextLoss> {
+        if self.context_loss.is_none() && self.share.private_caps.robustness_supported {
+            self.context_loss = match unsafe { self.share.context.GetGraphicsResetStatus() } {
+                gl::NO_ERROR => None,
+                gl::GUILTY_CONTEXT_RESET => Some(ContextLoss::Guilty),
+                gl::INNOCENT_CONTEXT_RESET => Some(ContextLoss::Innocent),
+                _ => Some(ContextLoss::Unknown),
+            };
+            if let Some(loss) = self.context_loss {
+                error!("GL: context lost: {:?}", loss);
             }
+        }
+        self.context_loss
+    }
+
+    fn bind_attribute(&mut self, slot: c::AttributeSlot, buffer: Buffer, bel: BufferElement) {
+        use core::format::ChannelType as C;
+        let (count, gl_type) = match attribute_format(bel.elem.format) {
+            Some(f) => f,
+            None => return,
         };
         let gl = &self.share.context;
         unsafe { gl.BindBuffer(gl::ARRAY_BUFFER, buffer) };
@@ -374,6 +661,36 @@ impl Device {
         }
     }
 
+    /// Specify a vertex attribute of `vao` without binding anything, with
+    /// one buffer binding point per attribute slot.
+    fn set_vertex_array_attribute(&mut self, vao: ArrayBuffer, slot: c::AttributeSlot,
+                                  buffer: Buffer, bel: BufferElement) {
+        use core::format::ChannelType as C;
+        let (count, gl_type) = match attribute_format(bel.elem.format) {
+            Some(f) => f,
+            None => return,
+        };
+        let gl = &self.share.context;
+        let slot = slot as gl::types::GLuint;
+        unsafe {
+            gl.VertexArrayVertexBuffer(vao, slot, buffer,
+                bel.elem.offset as gl::types::GLintptr,
+                bel.desc.stride as gl::types::GLsizei);
+            match bel.elem.format.1 {
+                C::Int | C::Uint =>
+                    gl.VertexArrayAttribIFormat(vao, slot, count, gl_type, 0),
+                C::Inorm | C::Unorm =>
+                    gl.VertexArrayAttribFormat(vao, slot, count, gl_type, gl::TRUE, 0),
+                C::Float =>
+                    gl.VertexArrayAttribFormat(vao, slot, count, gl_type, gl::FALSE, 0),
+                C::Srgb => (),
+            }
+            gl.VertexArrayAttribBinding(vao, slot, slot);
+            gl.EnableVertexArrayAttrib(vao, slot);
+            gl.VertexArrayBindingDivisor(vao, slot, bel.desc.rate as gl::types::GLuint);
+        }
+    }
+
     fn bind_target(&mut self, point: gl::types::GLenum, attachment: gl::types::GLenum, view: &TargetView) {
         let gl = &self.share.context;
         match view {
@@ -389,6 +706,11 @@ impl Device {
                                            level as gl::types::GLint,
                                            layer as gl::types::GLint);
             },
+            &TargetView::Multiview(texture, level, count) => unsafe {
+                gl.FramebufferTextureMultiviewOVR(point, attachment, texture,
+                                                  level as gl::types::GLint, 0,
+                                                  count as gl::types::GLsizei);
+            },
         }
     }
 
@@ -400,11 +722,72 @@ impl Device {
     fn reset_state(&mut self) {
         let data = DataBuffer::new();
         for com in command::RESET.iter() {
-            self.process(com, &data);
+            self.process(com, &data, &[]);
         }
     }
 
-    fn process(&mut self, cmd: &Command, data_buf: &DataBuffer) {
+    /// Bind the vertex attributes of a draw call. With VAO support, each
+    /// distinct set of attributes is specified once and cached in its own
+    /// VAO, until one of the referenced buffers is destroyed. Draws that only
+    /// differ in their buffer offsets share a VAO, and the offsets that
+    /// changed are set again on each bind.
+    fn bind_vertex_array(&mut self, vertex_array: &VertexArray) {
+        if !self.share.private_caps.array_buffer_supported {
+            for (slot, attrib) in vertex_array.iter().enumerate() {
+                match *attrib {
+                    Some((buffer, bel)) => self.bind_attribute(slot as c::AttributeSlot, buffer, bel),
+                    None => unsafe {
+                        self.share.context.DisableVertexAttribArray(slot as gl::types::GLuint);
+                    },
+                }
+            }
+            return
+        }
+        if let Some((vao, offsets)) = self.vertex_arrays.rebind(vertex_array) {
+            unsafe { self.share.context.BindVertexArray(vao) };
+            for (slot, attrib) in vertex_array.iter().enumerate() {
+                let (buffer, bel) = match *attrib {
+                    Some((buffer, bel)) if bel.elem.offset != offsets[slot] => (buffer, bel),
+                    _ => continue,
+                };
+                if self.share.private_caps.direct_state_access_supported {
+                    unsafe {
+                        self.share.context.VertexArrayVertexBuffer(vao,
+                            slot as gl::types::GLuint, buffer,
+                            bel.elem.offset as gl::types::GLintptr,
+                            bel.desc.stride as gl::types::GLsizei);
+                    }
+                } else {
+                    self.bind_attribute(slot as c::AttributeSlot, buffer, bel);
+                }
+            }
+            return
+        }
+        let mut vao = 0;
+        if self.share.private_caps.direct_state_access_supported {
+            unsafe { self.share.context.CreateVertexArrays(1, &mut vao) };
+            for (slot, attrib) in vertex_array.iter().enumerate() {
+                if let Some((buffer, bel)) = *attrib {
+                    self.set_vertex_array_attribute(vao, slot as c::AttributeSlot, buffer, bel);
+                }
+            }
+            unsafe { self.share.context.BindVertexArray(vao) };
+        } else {
+            unsafe {
+                let gl = &self.share.context;
+                gl.GenVertexArrays(1, &mut vao);
+                gl.BindVertexArray(vao);
+            }
+            for (slot, attrib) in vertex_array.iter().enumerate() {
+                if let Some((buffer, bel)) = *attrib {
+                    self.bind_attribute(slot as c::AttributeSlot, buffer, bel);
+                }
+            }
+        }
+        self.vertex_arrays.insert(vertex_array, vao);
+    }
+
+    fn process(&mut self, cmd: &Command, data_buf: &DataBuffer, vertex_arrays: &[VertexArray]) {
         match *cmd {
             Command::Clear(color, depth, stencil) => {
                 let gl = &self.share.context;
@@ -456,7 +839,11 @@ impl Device {
                         flags |= gl::DEPTH_BUFFER_BIT;
                         unsafe  {
                             gl.DepthMask(gl::TRUE);
-                            gl.ClearDepth(d as gl::types::GLdouble);
+                            if self.info.version.is_embedded {
+                                gl.ClearDepthf(d as gl::types::GLfloat);
+                            } else {
+                                gl.ClearDepth(d as gl::types::GLdouble);
+                            }
                         }
                     }
                     if let Some(s) = stencil {
@@ -471,15 +858,29 @@ impl Device {
                     }
                 }
             },
-            Command::BindProgram(program) => unsafe {
-                self.share.context.UseProgram(program);
+            Command::BindProgram(program) => {
+                unsafe { self.share.context.UseProgram(program) };
+                self.program = program;
             },
-            Command::BindConstantBuffer(pso::ConstantBufferParam(buffer, _, slot)) => unsafe {
-                self.share.context.BindBufferBase(gl::UNIFORM_BUFFER, slot as gl::types::GLuint, buffer);
+            Command::BindConstantBuffer(pso::ConstantBufferParam(buffer, _, slot, range)) => unsafe {
+                let gl = &self.share.context;
+                match range {
+                    Some((offset, size)) => gl.BindBufferRange(gl::UNIFORM_BUFFER,
+                        slot as gl::types::GLuint, buffer,
+                        offset as gl::types::GLintptr, size as gl::types::GLsizeiptr),
+                    None => gl.BindBufferBase(gl::UNIFORM_BUFFER, slot as gl::types::GLuint, buffer),
+                }
+            },
+            Command::BindAtomicCounterBuffer(pso::AtomicCounterBufferParam(buffer, _, slot)) => unsafe {
+                self.share.context.BindBufferBase(gl::ATOMIC_COUNTER_BUFFER,
+                    slot as gl::types::GLuint, buffer);
             },
-            Command::BindResourceView(pso::ResourceViewParam(view, _, slot)) => unsafe {
-                self.share.context.ActiveTexture(gl::TEXTURE0 + slot as gl::types::GLenum);
-                self.share.context.BindTexture(view.bind, view.object);
+            Command::BindResourceView(pso::ResourceViewParam(view, _, slot)) => {
+                unsafe {
+                    self.share.context.ActiveTexture(gl::TEXTURE0 + slot as gl::types::GLenum);
+                    self.share.context.BindTexture(view.bind, view.object);
+                }
+                self.texture_units[slot as usize] = Some(view.object);
             },
             Command::BindUnorderedView(_uav) => unimplemented!(),
             Command::BindSampler(pso::SamplerParam(sampler, _, slot), bind_opt) => {
@@ -489,10 +890,17 @@ impl Device {
                 } else {
                     assert!(c::MAX_SAMPLERS <= c::MAX_RESOURCE_VIEWS);
                     debug_assert_eq!(sampler.object, 0);
-                    if let Some(bind) = bind_opt {
-                        tex::bind_sampler(gl, bind, &sampler.info, &self.share.private_caps);
-                    }else {
-                        error!("Trying to bind a sampler to slot {}, when sampler objects are not supported, and no texture is bound there", slot);
+                    match (bind_opt, self.texture_units[slot as usize]) {
+                        (Some(bind), Some(texture)) => {
+                            // the sampler state sticks to the texture, so it only
+                            // needs to be set again when a different one is used
+                            if self.texture_samplers.get(&texture) != Some(&sampler.info) {
+                                unsafe { gl.ActiveTexture(gl::TEXTURE0 + slot as gl::types::GLenum) };
+                                tex::bind_sampler(gl, bind, &sampler.info, &self.share.private_caps);
+                                self.texture_samplers.insert(texture, sampler.info);
+                            }
+                        },
+                        _ => error!("Trying to bind a sampler to slot {}, when sampler objects are not supported, and no texture is bound there", slot),
                     }
                 }
             },
@@ -519,11 +927,8 @@ impl Device {
                     gl.BindVertexArray(self.vao);
                 }
             },
-            Command::BindAttribute(slot, buffer,  bel) => {
-                self.bind_attribute(slot, buffer, bel);
-            },
-            Command::UnbindAttribute(slot) => unsafe {
-                self.share.context.DisableVertexAttribArray(slot as gl::types::GLuint);
+            Command::BindVertexArray(index) => {
+                self.bind_vertex_array(&vertex_arrays[index]);
             },
             Command::BindIndex(buffer) => {
                 let gl = &self.share.context;
@@ -538,8 +943,12 @@ impl Device {
                 }
             },
             Command::BindUniform(loc, uniform) => {
-                let gl = &self.share.context;
-                shade::bind_uniform(gl, loc as gl::types::GLint, uniform);
+                let unchanged = self.program != 0 &&
+                    self.uniform_values.insert((self.program, loc), uniform) == Some(uniform);
+                if !unchanged {
+                    let gl = &self.share.context;
+                    shade::bind_uniform(gl, loc as gl::types::GLint, uniform);
+                }
             },
             Command::SetDrawColorBuffers(num) => {
                 let mask = (1 << (num as usize)) - 1;
@@ -548,6 +957,11 @@ impl Device {
             Command::SetRasterizer(rast) => {
                 state::bind_rasterizer(&self.share.context, &rast, self.info.version.is_embedded);
             },
+            Command::SetPointState(point) => {
+                if !self.info.version.is_embedded {
+                    state::bind_point(&self.share.context, &point);
+                }
+            },
             Command::SetViewport(rect) => {
                 state::bind_viewport(&self.share.context, rect);
             },
@@ -598,19 +1012,21 @@ impl Device {
                 }
             },
             Command::CopyTextureToBuffer(src, kind, face, img, dst, dst_offset) => {
-                match tex::copy_to_buffer(&self.share.context, src, kind, face, &img, dst, dst_offset) {
+                match tex::copy_to_buffer(&self.share.context, &self.share.private_caps,
+                                          src, kind, face, &img, dst, dst_offset) {
                     Ok(_) => (),
                     Err(e) => error!("GL: {:?} failed: {:?}", cmd, e)
                 }
             },
             Command::UpdateBuffer(buffer, pointer, offset) => {
                 let data = data_buf.get(pointer);
-                factory::update_sub_buffer(&self.share.context, buffer,
+                factory::update_sub_buffer(&self.share.context, &self.share.private_caps, buffer,
                     data.as_ptr(), data.len(), offset, buffer::Role::Vertex);
             },
             Command::UpdateTexture(texture, kind, face, pointer, ref image) => {
                 let data = data_buf.get(pointer);
-                match tex::update_texture(&self.share.context, texture, kind, face, image, data) {
+                match tex::update_texture(&self.share.context, &self.share.private_caps,
+                                         texture, kind, face, image, data) {
                     Ok(_) => (),
                     Err(e) => error!("GL: Texture({}) update failed: {:?}", texture, e),
                 }
@@ -618,6 +1034,47 @@ impl Device {
             Command::GenerateMipmap(view) => {
                 tex::generate_mipmap(&self.share.context, view.object, view.bind);
             },
+            Command::MemoryBarrier(bits) => {
+                if self.share.private_caps.memory_barrier_supported {
+                    unsafe { self.share.context.MemoryBarrier(bits) };
+                }
+            },
+            Command::BeginOcclusionQuery(slot) => {
+                if self.share.capabilities.occlusion_query_supported {
+                    self.occlusion_queries.begin(&self.share.context, self.info.version.is_embedded,
+                                                 slot as usize);
+                }
+            },
+            Command::EndOcclusionQuery => {
+                if self.share.capabilities.occlusion_query_supported {
+                    self.occlusion_queries.end(&self.share.context, self.info.version.is_embedded);
+                }
+            },
+            Command::BeginTimerQuery(slot) => {
+                if self.share.capabilities.timer_query_supported {
+                    self.timer_queries.begin(&self.share.context, slot as usize);
+                }
+            },
+            Command::EndTimerQuery => {
+                if self.share.capabilities.timer_query_supported {
+                    self.timer_queries.end(&self.share.context);
+                }
+            },
+            Command::PushDebugGroup(pointer) => {
+                if self.share.private_caps.debug_supported {
+                    let name = data_buf.get(pointer);
+                    unsafe {
+                        self.share.context.PushDebugGroup(gl::DEBUG_SOURCE_APPLICATION, 0,
+                            name.len() as gl::types::GLsizei,
+                            name.as_ptr() as *const gl::types::GLchar);
+                    }
+                }
+            },
+            Command::PopDebugGroup => {
+                if self.share.private_caps.debug_supported {
+                    unsafe { self.share.context.PopDebugGroup() };
+                }
+            },
             Command::Draw(primitive, start, count, instances) => {
                 let gl = &self.share.context;
                 match instances {
@@ -770,7 +1227,12 @@ impl Device {
     fn no_fence_submit(&mut self, cb: &mut command::CommandBuffer) {
         self.reset_state();
         for com in &cb.buf {
-            self.process(com, &cb.data);
+            self.frame_stats.count_command(com, &cb.data);
+            self.process(com, &cb.data, &cb.vertex_arrays);
+        }
+        // keep the factory from altering the index binding of a cached VAO
+        if self.share.private_caps.array_buffer_supported {
+            unsafe { self.share.context.BindVertexArray(self.vao) };
         }
     }
 
@@ -844,6 +1306,35 @@ impl Device {
         unsafe { gl.MemoryBarrier(gl::CLIENT_MAPPED_BUFFER_BARRIER_BIT); }
     }
 
+    /// Release the handles used by the submissions since the last cleanup.
+    /// With sync objects, they are kept alive until the GPU is done with
+    /// the frame, so that resources dropped by the user in the meantime
+    /// are only destroyed once they are no longer in use.
+    fn release_frame_handles(&mut self) {
+        let frame_handles = mem::replace(&mut self.frame_handles, handle::Manager::new());
+        if !self.share.private_caps.sync_supported {
+            return
+        }
+        let gl = &self.share.context;
+        if frame_handles.count() != 0 {
+            let fence = unsafe { gl.FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+            self.pending_frames.push_back((Fence(fence), frame_handles));
+        }
+        // frames complete in submission order
+        while self.pending_frames.front().map_or(false, |&(ref fence, _)| {
+            factory::is_fence_signaled(fence, gl)
+        }) {
+            let (fence, _) = self.pending_frames.pop_front().unwrap();
+            unsafe { gl.DeleteSync(fence.0) };
+        }
+    }
+
+    /// Get the number of frames whose resources are still kept alive,
+    /// waiting for the GPU to finish them.
+    pub fn get_pending_frame_count(&self) -> usize {
+        self.pending_frames.len()
+    }
+
     fn place_fence(&mut self) -> handle::Fence<Resources> {
         use core::handle::Producer;
 
@@ -868,6 +1359,21 @@ impl Device {
     }
 }
 
+impl Drop for Device {
+    fn drop(&mut self) {
+        // the driver must not call into a freed callback
+        self.clear_debug_callback();
+        if let Some(ref mut queries) = self.statistics {
+            queries.delete(&self.share.context);
+        }
+        self.occlusion_queries.delete(&self.share.context);
+        self.timer_queries.delete(&self.share.context);
+        for vao in self.vertex_arrays.remove_unless(|_| false) {
+            unsafe { self.share.context.DeleteVertexArrays(1, &vao) };
+        }
+    }
+}
+
 impl c::Device for Device {
     type Resources = Resources;
     type CommandBuffer = command::CommandBuffer;
@@ -922,11 +1428,58 @@ impl c::Device for Device {
                             &self.share.context);
     }
 
+    fn get_occlusion_result(&mut self, slot: c::QuerySlot) -> Option<bool> {
+        self.occlusion_queries.get_result(&self.share.context, slot as usize)
+    }
+
+    fn get_timer_result(&mut self, slot: c::QuerySlot) -> Option<u64> {
+        self.timer_queries.get_result(&self.share.context, slot as usize)
+    }
+
+    fn set_pipeline_statistics(&mut self, enabled: bool) -> bool {
+        if !self.share.private_caps.pipeline_statistics_supported {
+            return false
+        }
+        let gl = &self.share.context;
+        match (enabled, self.statistics.take()) {
+            (true, None) => {
+                let mut queries = query::StatisticsQueries::new();
+                queries.begin(gl);
+                self.statistics = Some(queries);
+            },
+            (true, Some(queries)) => self.statistics = Some(queries),
+            (false, Some(mut queries)) => queries.delete(gl),
+            (false, None) => (),
+        }
+        true
+    }
+
+    fn get_pipeline_statistics(&self) -> Option<c::PipelineStatistics> {
+        self.statistics.as_ref().and_then(|q| q.get_last())
+    }
+
     fn cleanup(&mut self) {
         use core::handle::Producer;
-        self.frame_handles.clear();
+        self.release_frame_handles();
+        self.last_frame_stats = mem::replace(&mut self.frame_stats, DeviceStats::default());
+        self.check_context_loss();
+        if let Some(ref mut queries) = self.statistics {
+            let gl = &self.share.context;
+            queries.end(gl);
+            queries.poll(gl);
+            queries.begin(gl);
+        }
+        let deleted_buffers = RefCell::new(Vec::new());
+        let deleted_textures = RefCell::new(Vec::new());
+        let deleted_programs = RefCell::new(Vec::new());
+        let stats = &self.share.stats;
+        let residencies = &self.share.residencies;
         self.share.handles.borrow_mut().clean_with(&mut &self.share.context,
             |gl, buffer| {
+                deleted_buffers.borrow_mut().push(*buffer.resource());
+                let mut stats = stats.borrow_mut();
+                stats.buffer_count -= 1;
+                stats.buffer_bytes -= buffer.get_info().size;
                 buffer.mapping().map(|raw| {
                     // we have exclusive access because it's the last reference
                     let mapping = unsafe { raw.use_access() };
@@ -944,20 +1497,57 @@ impl c::Device for Device {
                 unsafe { gl.DeleteBuffers(1, buffer.resource()) }
             },
             |gl, v| unsafe { gl.DeleteShader(*v) },
-            |gl, program| unsafe { gl.DeleteProgram(*program.resource()) },
+            |gl, program| {
+                deleted_programs.borrow_mut().push(*program.resource());
+                unsafe { gl.DeleteProgram(*program.resource()) }
+            },
             |_, _| {}, //PSO
-            |gl, raw_texture| match raw_texture.resource() {
-                &NewTexture::Surface(ref suf) => unsafe { gl.DeleteRenderbuffers(1, suf) },
-                &NewTexture::Texture(ref tex) => unsafe { gl.DeleteTextures(1, tex) },
+            |gl, raw_texture| {
+                let mut stats = stats.borrow_mut();
+                stats.texture_count -= 1;
+                stats.texture_bytes -= get_texture_size(raw_texture.get_info());
+                match raw_texture.resource() {
+                    &NewTexture::Surface(ref suf) => unsafe { gl.DeleteRenderbuffers(1, suf) },
+                    &NewTexture::Texture(ref tex) => {
+                        deleted_textures.borrow_mut().push(*tex);
+                        factory::release_residencies(gl, residencies, |r| r.texture == *tex);
+                        unsafe { gl.DeleteTextures(1, tex) }
+                    },
+                }
             }, // new texture
             |gl, v| if v.owned {
+                deleted_textures.borrow_mut().push(v.object);
+                factory::release_residencies(gl, residencies, |r| r.texture == v.object);
                 unsafe { gl.DeleteTextures(1, &v.object) }
             }, //SRV
             |_, _| {}, //UAV
             |_, _| {}, //RTV
             |_, _| {}, //DSV
-            |gl, v| unsafe { if v.object != 0 { gl.DeleteSamplers(1, &v.object) }},
+            |gl, v| if v.object != 0 {
+                factory::release_residencies(gl, residencies, |r| r.sampler == v.object);
+                unsafe { gl.DeleteSamplers(1, &v.object) }
+            },
             |gl, fence| unsafe { gl.DeleteSync(fence.0) },
         );
+        // the names of deleted programs, textures and buffers may be reused by new ones
+        for texture in deleted_textures.into_inner() {
+            self.texture_samplers.remove(&texture);
+        }
+        let deleted_programs = deleted_programs.into_inner();
+        if !deleted_programs.is_empty() {
+            self.uniform_values.retain(|&(program, _), _| !deleted_programs.contains(&program));
+        }
+        let deleted_buffers = deleted_buffers.into_inner();
+        if !deleted_buffers.is_empty() {
+            let stale = self.vertex_arrays.remove_unless(|vertex_array| {
+                vertex_array.iter().all(|attrib| match *attrib {
+                    Some((buffer, _)) => !deleted_buffers.contains(&buffer),
+                    None => true,
+                })
+            });
+            for vao in stale {
+                unsafe { self.share.context.DeleteVertexArrays(1, &vao) };
+            }
+        }
     }
 }
@@ -0,0 +1,152 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resource creation on a secondary context, sharing its objects with the
+//! context of the device, typically to stream textures from a background
+//! thread:
+//!
+//! ```ignore
+//! // on the loader thread, with the shared context current
+//! let (mut loader, mut factory) = gfx_device_gl::create_loader(|s| window.get_proc_address(s));
+//! let texture = factory.create_texture_immutable_u8::<Srgba8>(kind, &[&pixels]);
+//! sender.send((texture, loader.finish()));
+//!
+//! // on the render thread
+//! let (texture, upload) = receiver.recv();
+//! device.accept_upload(upload);
+//! ```
+
+use std::mem;
+use std::rc::Rc;
+use core::handle;
+use gl;
+use {init_context, Device, DeviceStats, Factory, Fence, Info, Resources, Share};
+
+/// The resources created by a `Loader` since its last `finish`, along with
+/// the fence signaled once they are complete.
+///
+/// It has to be passed to `Device::accept_upload` on the render thread
+/// before the resources are used there. Dropping it instead leaks them.
+pub struct Upload {
+    fence: Option<Fence>,
+    handles: handle::Manager<Resources>,
+    stats: DeviceStats,
+}
+
+/// A secondary context, sharing its objects with the one of a `Device`.
+///
+/// Unlike the device, it doesn't submit command buffers: its factory
+/// creates and updates resources, which are then handed over to the device
+/// with `finish`.
+pub struct Loader {
+    info: Info,
+    share: Rc<Share>,
+    vao: gl::types::GLuint,
+}
+
+/// Create a loader, with its factory, for the current context, which has to
+/// share its objects with the context of the device.
+///
+/// The loader context keeps its own state, including the error checks: a
+/// debug callback set on the device doesn't receive its messages.
+pub fn create_loader<F>(fn_proc: F) -> (Loader, Factory) where
+    F: FnMut(&str) -> *const ::std::os::raw::c_void
+{
+    let (info, share, vao) = init_context(fn_proc);
+    let share = Rc::new(share);
+    let factory = Factory::new(share.clone());
+    let loader = Loader {
+        info: info,
+        share: share,
+        vao: vao,
+    };
+    (loader, factory)
+}
+
+impl Loader {
+    /// Get the OpenGL-specific driver information of the loader context.
+    pub fn get_info(&self) -> &Info {
+        &self.info
+    }
+
+    /// Hand over the resources created since the last call. The commands
+    /// issued so far are flushed, so that the fence placed after them gets
+    /// signaled, or waited for when sync objects are not supported.
+    pub fn finish(&mut self) -> Upload {
+        let gl = &self.share.context;
+        let fence = if self.share.private_caps.sync_supported {
+            unsafe {
+                let fence = gl.FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
+                // the fence has to reach the GPU before another context waits for it
+                gl.Flush();
+                Some(Fence(fence))
+            }
+        } else {
+            unsafe { gl.Finish() };
+            None
+        };
+        let handles = mem::replace(&mut *self.share.handles.borrow_mut(), handle::Manager::new());
+        let mut stats = self.share.stats.borrow_mut();
+        let moved = DeviceStats {
+            buffer_count: stats.buffer_count,
+            buffer_bytes: stats.buffer_bytes,
+            texture_count: stats.texture_count,
+            texture_bytes: stats.texture_bytes,
+            .. DeviceStats::default()
+        };
+        *stats = DeviceStats::default();
+        Upload {
+            fence: fence,
+            handles: handles,
+            stats: moved,
+        }
+    }
+}
+
+impl Drop for Loader {
+    fn drop(&mut self) {
+        if self.vao != 0 {
+            unsafe { self.share.context.DeleteVertexArrays(1, &self.vao) };
+        }
+    }
+}
+
+impl Upload {
+    /// Check whether the upload has no resources.
+    pub fn is_empty(&self) -> bool {
+        self.handles.count() == 0
+    }
+}
+
+impl Device {
+    /// Take over the resources of an upload, making the GPU wait for their
+    /// completion before executing the following commands. The CPU doesn't
+    /// block.
+    pub fn accept_upload(&mut self, upload: Upload) {
+        if let Some(fence) = upload.fence {
+            let gl = &self.share.context;
+            unsafe {
+                gl.WaitSync(fence.0, 0, gl::TIMEOUT_IGNORED);
+                // the deletion is deferred until the wait is over
+                gl.DeleteSync(fence.0);
+            }
+        }
+        self.share.handles.borrow_mut().extend(&upload.handles);
+        let mut stats = self.share.stats.borrow_mut();
+        stats.buffer_count += upload.stats.buffer_count;
+        stats.buffer_bytes += upload.stats.buffer_bytes;
+        stats.texture_count += upload.stats.texture_count;
+        stats.texture_bytes += upload.stats.texture_bytes;
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pipeline statistics, as provided by `ARB_pipeline_statistics_query`,
+//! occlusion queries and timer queries.
+
+use std::collections::VecDeque;
+use core::PipelineStatistics;
+use gl;
+use gl::types::{GLenum, GLuint, GLuint64};
+
+// not part of the generated bindings
+const VERTICES_SUBMITTED: GLenum          = 0x82EE;
+const PRIMITIVES_SUBMITTED: GLenum        = 0x82EF;
+const FRAGMENT_SHADER_INVOCATIONS: GLenum = 0x82F4;
+const CLIPPING_INPUT_PRIMITIVES: GLenum   = 0x82F6;
+const CLIPPING_OUTPUT_PRIMITIVES: GLenum  = 0x82F7;
+
+const TARGETS: [GLenum; 5] = [
+    VERTICES_SUBMITTED,
+    PRIMITIVES_SUBMITTED,
+    CLIPPING_INPUT_PRIMITIVES,
+    CLIPPING_OUTPUT_PRIMITIVES,
+    FRAGMENT_SHADER_INVOCATIONS,
+];
+
+type QuerySet = [GLuint; 5];
+
+/// The queries of the current frame, and the ones of the previous frames
+/// whose results are not available yet. Reading them back is never
+/// blocking, so the statistics lag a few frames behind.
+pub struct StatisticsQueries {
+    current: Option<QuerySet>,
+    pending: VecDeque<QuerySet>,
+    free: Vec<QuerySet>,
+    last: Option<PipelineStatistics>,
+}
+
+impl StatisticsQueries {
+    pub fn new() -> StatisticsQueries {
+        StatisticsQueries {
+            current: None,
+            pending: VecDeque::new(),
+            free: Vec::new(),
+            last: None,
+        }
+    }
+
+    /// Get the statistics of the latest frame whose queries completed.
+    pub fn get_last(&self) -> Option<PipelineStatistics> {
+        self.last
+    }
+
+    /// Start counting the work of a new frame.
+    pub fn begin(&mut self, gl: &gl::Gl) {
+        debug_assert!(self.current.is_none());
+        let set = self.free.pop().unwrap_or_else(|| {
+            let mut set = [0; 5];
+            unsafe { gl.GenQueries(set.len() as gl::types::GLsizei, set.as_mut_ptr()) };
+            set
+        });
+        for (&target, &query) in TARGETS.iter().zip(set.iter()) {
+            unsafe { gl.BeginQuery(target, query) };
+        }
+        self.current = Some(set);
+    }
+
+    /// Stop counting the work of the current frame.
+    pub fn end(&mut self, gl: &gl::Gl) {
+        if let Some(set) = self.current.take() {
+            for &target in TARGETS.iter() {
+                unsafe { gl.EndQuery(target) };
+            }
+            self.pending.push_back(set);
+        }
+    }
+
+    /// Read back the results of the frames that are complete.
+    pub fn poll(&mut self, gl: &gl::Gl) {
+        // queries complete in submission order
+        while let Some(set) = self.pending.front().cloned() {
+            let mut available = 0;
+            unsafe {
+                gl.GetQueryObjectuiv(set[TARGETS.len() - 1], gl::QUERY_RESULT_AVAILABLE,
+                                     &mut available)
+            };
+            if available == 0 {
+                break
+            }
+            let mut results: [GLuint64; 5] = [0; 5];
+            for (&query, result) in set.iter().zip(results.iter_mut()) {
+                unsafe { gl.GetQueryObjectui64v(query, gl::QUERY_RESULT, result) };
+            }
+            self.last = Some(PipelineStatistics {
+                vertices_submitted: results[0],
+                primitives_submitted: results[1],
+                clipping_input_primitives: results[2],
+                clipping_output_primitives: results[3],
+                fragment_shader_invocations: results[4],
+            });
+            self.pending.pop_front();
+            self.free.push(set);
+        }
+    }
+
+    /// End the current frame and delete all the queries.
+    pub fn delete(&mut self, gl: &gl::Gl) {
+        self.end(gl);
+        for set in self.pending.drain(..).chain(self.free.drain(..)) {
+            unsafe { gl.DeleteQueries(set.len() as gl::types::GLsizei, set.as_ptr()) };
+        }
+    }
+}
+
+/// The occlusion queries, created on their first use and indexed by slot.
+pub struct OcclusionQueries {
+    names: Vec<GLuint>,
+}
+
+impl OcclusionQueries {
+    pub fn new() -> OcclusionQueries {
+        OcclusionQueries {
+            names: Vec::new(),
+        }
+    }
+
+    /// Get the target counting the samples, which has to be boolean on GLES.
+    fn target(embedded: bool) -> GLenum {
+        if embedded { gl::ANY_SAMPLES_PASSED } else { gl::SAMPLES_PASSED }
+    }
+
+    /// Start counting the samples passing the depth test into `slot`.
+    pub fn begin(&mut self, gl: &gl::Gl, embedded: bool, slot: usize) {
+        if slot >= self.names.len() {
+            self.names.resize(slot + 1, 0);
+        }
+        if self.names[slot] == 0 {
+            unsafe { gl.GenQueries(1, &mut self.names[slot]) };
+        }
+        unsafe { gl.BeginQuery(OcclusionQueries::target(embedded), self.names[slot]) };
+    }
+
+    /// Stop counting the samples into the current query.
+    pub fn end(&mut self, gl: &gl::Gl, embedded: bool) {
+        unsafe { gl.EndQuery(OcclusionQueries::target(embedded)) };
+    }
+
+    /// Get whether any sample passed, if the result of `slot` is available.
+    pub fn get_result(&self, gl: &gl::Gl, slot: usize) -> Option<bool> {
+        let name = match self.names.get(slot) {
+            Some(&name) if name != 0 => name,
+            _ => return None,
+        };
+        let mut value = 0;
+        unsafe { gl.GetQueryObjectuiv(name, gl::QUERY_RESULT_AVAILABLE, &mut value) };
+        if value == 0 {
+            return None
+        }
+        unsafe { gl.GetQueryObjectuiv(name, gl::QUERY_RESULT, &mut value) };
+        Some(value != 0)
+    }
+
+    /// Delete all the queries.
+    pub fn delete(&mut self, gl: &gl::Gl) {
+        let names: Vec<_> = self.names.drain(..).filter(|&name| name != 0).collect();
+        unsafe { gl.DeleteQueries(names.len() as gl::types::GLsizei, names.as_ptr()) };
+    }
+}
+
+/// The timer queries, created on their first use and indexed by slot.
+pub struct TimerQueries {
+    names: Vec<GLuint>,
+}
+
+impl TimerQueries {
+    pub fn new() -> TimerQueries {
+        TimerQueries {
+            names: Vec::new(),
+        }
+    }
+
+    /// Start measuring the time elapsed into `slot`.
+    pub fn begin(&mut self, gl: &gl::Gl, slot: usize) {
+        if slot >= self.names.len() {
+            self.names.resize(slot + 1, 0);
+        }
+        if self.names[slot] == 0 {
+            unsafe { gl.GenQueries(1, &mut self.names[slot]) };
+        }
+        unsafe { gl.BeginQuery(gl::TIME_ELAPSED, self.names[slot]) };
+    }
+
+    /// Stop measuring the time into the current query.
+    pub fn end(&mut self, gl: &gl::Gl) {
+        unsafe { gl.EndQuery(gl::TIME_ELAPSED) };
+    }
+
+    /// Get the time elapsed in nanoseconds, if the result of `slot` is
+    /// available.
+    pub fn get_result(&self, gl: &gl::Gl, slot: usize) -> Option<u64> {
+        let name = match self.names.get(slot) {
+            Some(&name) if name != 0 => name,
+            _ => return None,
+        };
+        let mut available = 0;
+        unsafe { gl.GetQueryObjectuiv(name, gl::QUERY_RESULT_AVAILABLE, &mut available) };
+        if available == 0 {
+            return None
+        }
+        let mut value: GLuint64 = 0;
+        unsafe { gl.GetQueryObjectui64v(name, gl::QUERY_RESULT, &mut value) };
+        Some(value)
+    }
+
+    /// Delete all the queries.
+    pub fn delete(&mut self, gl: &gl::Gl) {
+        let names: Vec<_> = self.names.drain(..).filter(|&name| name != 0).collect();
+        unsafe { gl.DeleteQueries(names.len() as gl::types::GLsizei, names.as_ptr()) };
+    }
+}
@@ -87,6 +87,7 @@ pub fn create_shader(gl: &gl::Gl, stage: s::Stage, data: &[u8])
 enum StorageType {
     Var(s::BaseType, s::ContainerType),
     Sampler(s::BaseType, s::TextureType, s::SamplerType),
+    AtomicCounter,
     Unknown,
 }
 
@@ -165,6 +166,8 @@ impl StorageType {
             gl::UNSIGNED_INT_SAMPLER_CUBE_MAP_ARRAY       => Sampler(BaseType::U32, TextureType::Cube(Array),   SamplerType(NoCompare, NoRect)),
             gl::UNSIGNED_INT_SAMPLER_BUFFER               => Sampler(BaseType::U32, TextureType::Buffer,        SamplerType(NoCompare, NoRect)),
 
+            gl::UNSIGNED_INT_ATOMIC_COUNTER => AtomicCounter,
+
             _ => Unknown,
         }
     }
@@ -312,6 +315,63 @@ fn query_blocks(gl: &gl::Gl, caps: &c::Capabilities, prog: super::Program,
     }).collect()
 }
 
+fn query_atomic_counters(gl: &gl::Gl, caps: &c::Capabilities, prog: super::Program, usage: s::Usage)
+                         -> Vec<s::AtomicCounterBufferVar> {
+    if !caps.atomic_counter_supported {
+        return Vec::new()
+    }
+    let num = get_program_iv(gl, prog, gl::ACTIVE_ATOMIC_COUNTER_BUFFERS);
+    let max_len = get_program_iv(gl, prog, gl::ACTIVE_UNIFORM_MAX_LENGTH);
+    let mut name = String::with_capacity(max_len as usize);
+    name.extend(repeat('\0').take(max_len as usize));
+    (0..num as gl::types::GLuint).map(|idx| {
+        let get = |query| {
+            let mut value = 0;
+            unsafe { gl.GetActiveAtomicCounterBufferiv(prog, idx, query, &mut value) };
+            value
+        };
+        let slot = get(gl::ATOMIC_COUNTER_BUFFER_BINDING);
+        let size = get(gl::ATOMIC_COUNTER_BUFFER_DATA_SIZE);
+        let num_counters = get(gl::ATOMIC_COUNTER_BUFFER_ACTIVE_ATOMIC_COUNTERS);
+        let mut indices = vec![0 as gl::types::GLint; num_counters as usize];
+        if num_counters != 0 {
+            unsafe {
+                gl.GetActiveAtomicCounterBufferiv(prog, idx,
+                    gl::ATOMIC_COUNTER_BUFFER_ACTIVE_ATOMIC_COUNTER_INDICES,
+                    indices.as_mut_ptr());
+            }
+        }
+        info!("		Atomic counter buffer[{}] of size {}", slot, size);
+        let counters = indices.iter().map(|&i| {
+            let index = i as gl::types::GLuint;
+            let mut length = 0;
+            let mut count = 0;
+            let mut storage = 0;
+            let mut offset = 0;
+            unsafe {
+                let raw = (&name[..]).as_ptr() as *mut gl::types::GLchar;
+                gl.GetActiveUniform(prog, index, max_len, &mut length, &mut count, &mut storage, raw);
+                gl.GetActiveUniformsiv(prog, 1, &index, gl::UNIFORM_OFFSET, &mut offset);
+            }
+            let real_name = name[..length as usize].to_string();
+            info!("			Counter at {}\t= '{}'", offset, real_name);
+            s::ConstVar {
+                name: real_name,
+                location: offset as s::Location,
+                count: count as usize,
+                base_type: s::BaseType::U32,
+                container: s::ContainerType::Single,
+            }
+        }).collect();
+        s::AtomicCounterBufferVar {
+            slot: slot as c::AtomicCounterSlot,
+            size: size as usize,
+            usage: usage,
+            counters: counters,
+        }
+    }).collect()
+}
+
 fn query_parameters(gl: &gl::Gl, caps: &c::Capabilities, prog: super::Program, usage: s::Usage)
                     -> (Vec<s::ConstVar>, Vec<s::TextureVar>, Vec<s::SamplerVar>, Vec<gl::types::GLint>, Vec<gl::types::GLint>) {
     let mut uniforms = Vec::new();
@@ -387,6 +447,7 @@ fn query_parameters(gl: &gl::Gl, caps: &c::Capabilities, prog: super::Program, u
                     });
                 }
             },
+            StorageType::AtomicCounter => (), // see `query_atomic_counters`
             StorageType::Unknown => {
                 error!("Unrecognized uniform storage: {}", storage);
             },
@@ -510,6 +571,7 @@ pub fn create_program(gl: &gl::Gl, caps: &c::Capabilities, private: &PrivateCaps
             textures: textures,
             unordereds: Vec::new(), //TODO
             samplers: samplers,
+            atomic_counter_buffers: query_atomic_counters(gl, caps, name, usage),
             outputs: Vec::new(),
             output_depth: false,
             knows_outputs: false,
@@ -532,6 +594,7 @@ pub fn bind_uniform(gl: &gl::Gl, loc: gl::types::GLint, uniform: s::UniformValue
     use core::shade::UniformValue;
     match uniform {
         UniformValue::I32(val) => unsafe { gl.Uniform1i(loc, val) },
+        UniformValue::U32(val) => unsafe { gl.Uniform1ui(loc, val) },
         UniformValue::F32(val) => unsafe { gl.Uniform1f(loc, val) },
 
         UniformValue::I32Vector2(val) => unsafe { gl.Uniform2iv(loc, 1, val.as_ptr()) },
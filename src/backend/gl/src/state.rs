@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use core::{MAX_COLOR_TARGETS, ColorSlot};
+use core::pso::{PointInfo, PointOrigin};
 use core::state as s;
 use core::state::{BlendValue, Comparison, CullFace, Equation,
                   Offset, RasterMethod, StencilOp, FrontFace};
@@ -44,6 +45,22 @@ pub fn bind_raster_method(gl: &gl::Gl, method: s::RasterMethod, offset: Option<s
     }
 }
 
+pub fn bind_point(gl: &gl::Gl, p: &PointInfo) {
+    unsafe {
+        if p.program_size {
+            gl.Enable(gl::PROGRAM_POINT_SIZE);
+        } else {
+            gl.Disable(gl::PROGRAM_POINT_SIZE);
+            gl.PointSize(p.size as gl::types::GLfloat);
+        }
+        gl.PointParameterf(gl::POINT_FADE_THRESHOLD_SIZE, p.fade_threshold as gl::types::GLfloat);
+        gl.PointParameteri(gl::POINT_SPRITE_COORD_ORIGIN, match p.origin {
+            PointOrigin::UpperLeft => gl::UPPER_LEFT,
+            PointOrigin::LowerLeft => gl::LOWER_LEFT,
+        } as gl::types::GLint);
+    }
+}
+
 pub fn bind_rasterizer(gl: &gl::Gl, r: &s::Rasterizer, is_embedded: bool) {
     unsafe {
         gl.FrontFace(match r.front_face {
@@ -66,10 +83,19 @@ pub fn bind_rasterizer(gl: &gl::Gl, r: &s::Rasterizer, is_embedded: bool) {
 
     if !is_embedded {
         bind_raster_method(gl, r.method, r.offset);
-    }
-    match r.samples {
-        Some(_) => unsafe { gl.Enable(gl::MULTISAMPLE) },
-        None => unsafe { gl.Disable(gl::MULTISAMPLE) },
+        // GLES always rasterizes multisampled targets with multisampling
+        match r.samples {
+            Some(_) => unsafe { gl.Enable(gl::MULTISAMPLE) },
+            None => unsafe { gl.Disable(gl::MULTISAMPLE) },
+        }
+    } else if let Some(Offset(factor, units)) = r.offset {
+        unsafe {
+            gl.Enable(gl::POLYGON_OFFSET_FILL);
+            gl.PolygonOffset(factor as gl::types::GLfloat,
+                             units as gl::types::GLfloat);
+        }
+    } else {
+        unsafe { gl.Disable(gl::POLYGON_OFFSET_FILL) };
     }
 }
 
@@ -664,6 +664,7 @@ pub fn copy_from_buffer(gl: &gl::Gl,
 }
 
 pub fn copy_to_buffer(gl: &gl::Gl,
+                      private_caps: &PrivateCaps,
                       src: NewTexture,
                       kind: t::Kind,
                       face: Option<t::CubeFace>,
@@ -681,6 +682,39 @@ pub fn copy_to_buffer(gl: &gl::Gl,
     };
 
     match src {
+        NewTexture::Texture(t) if !private_caps.get_tex_image_supported => {
+            // GLES has no `glGetTexImage`, so the texture level is
+            // attached to a temporary framebuffer and read from there.
+            let mut fbo = 0;
+            unsafe {
+                gl.GenFramebuffers(1, &mut fbo);
+                gl.BindFramebuffer(gl::READ_FRAMEBUFFER, fbo);
+            }
+            match kind {
+                t::Kind::D2(_, _, t::AaMode::Single) | t::Kind::Cube(_) => unsafe {
+                    gl.FramebufferTexture2D(gl::READ_FRAMEBUFFER, gl::COLOR_ATTACHMENT0,
+                                            kind_face_to_gl(kind, face), t, img.mipmap as GLint);
+                },
+                t::Kind::D2Array(_, _, _, t::AaMode::Single) | t::Kind::D3(_, _, _) => unsafe {
+                    gl.FramebufferTextureLayer(gl::READ_FRAMEBUFFER, gl::COLOR_ATTACHMENT0,
+                                               t, img.mipmap as GLint, img.zoffset as GLint);
+                },
+                _ => {
+                    unsafe { gl.DeleteFramebuffers(1, &fbo); }
+                    return Err(t::CreationError::Kind)
+                },
+            }
+            unsafe {
+                gl.ReadPixels(img.xoffset as GLint,
+                              img.yoffset as GLint,
+                              img.width as GLint,
+                              img.height as GLint,
+                              pixel_format,
+                              data_type,
+                              data);
+                gl.DeleteFramebuffers(1, &fbo);
+            }
+        }
         NewTexture::Texture(t) => {
             let target = kind_to_gl(kind);
             unsafe { gl.BindTexture(target, t); }
@@ -719,7 +753,73 @@ pub fn copy_to_buffer(gl: &gl::Gl,
     Ok(())
 }
 
-pub fn update_texture(gl: &gl::Gl, name: Texture,
+/// Same as `tex_sub_image`, but addressing the texture by name instead of
+/// through a binding point. Cube faces are the layers of the texture.
+fn texture_sub_image<F>(gl: &gl::Gl, name: Texture, kind: t::Kind, face: Option<t::CubeFace>,
+                        pix: GLenum, typ: GLenum, img: &t::ImageInfoCommon<F>, data: *const GLvoid)
+                        -> Result<(), t::CreationError> {
+    Ok(match kind {
+        t::Kind::D1(_) => unsafe {
+            gl.TextureSubImage1D(
+                name,
+                img.mipmap as GLint,
+                img.xoffset as GLint,
+                img.width as GLint,
+                pix,
+                typ,
+                data
+            );
+        },
+        t::Kind::D1Array(_, _) | t::Kind::D2(_, _, t::AaMode::Single) => unsafe {
+            gl.TextureSubImage2D(
+                name,
+                img.mipmap as GLint,
+                img.xoffset as GLint,
+                img.yoffset as GLint,
+                img.width as GLint,
+                img.height as GLint,
+                pix,
+                typ,
+                data
+            );
+        },
+        t::Kind::D2Array(_, _, _, t::AaMode::Single) | t::Kind::D3(_, _, _) => unsafe {
+            gl.TextureSubImage3D(
+                name,
+                img.mipmap as GLint,
+                img.xoffset as GLint,
+                img.yoffset as GLint,
+                img.zoffset as GLint,
+                img.width as GLint,
+                img.height as GLint,
+                img.depth as GLint,
+                pix,
+                typ,
+                data
+            );
+        },
+        t::Kind::Cube(_) => unsafe {
+            gl.TextureSubImage3D(
+                name,
+                img.mipmap as GLint,
+                img.xoffset as GLint,
+                img.yoffset as GLint,
+                face.map_or(0, |f| f as GLint),
+                img.width as GLint,
+                img.height as GLint,
+                1,
+                pix,
+                typ,
+                data
+            );
+        },
+        t::Kind::CubeArray(_, _) => return Err(t::CreationError::Kind),
+        t::Kind::D2(_, _, aa) => return Err(t::CreationError::Samples(aa)),
+        t::Kind::D2Array(_, _, _, aa) => return Err(t::CreationError::Samples(aa)),
+    })
+}
+
+pub fn update_texture(gl: &gl::Gl, private_caps: &PrivateCaps, name: Texture,
                       kind: t::Kind, face: Option<t::CubeFace>,
                       img: &t::RawImageInfo, slice: &[u8])
                           -> Result<(), t::CreationError> {
@@ -731,6 +831,10 @@ pub fn update_texture(gl: &gl::Gl, name: Texture,
         Err(_) => return Err(t::CreationError::Format(img.format.0, Some(img.format.1))),
     };
 
+    if private_caps.direct_state_access_supported {
+        return texture_sub_image(gl, name, kind, face, pixel_format, data_type, img, data)
+    }
+
     let target = kind_to_gl(kind);
     unsafe { gl.BindTexture(target, name) };
 
@@ -738,8 +842,8 @@ pub fn update_texture(gl: &gl::Gl, name: Texture,
     tex_sub_image(gl, kind, target, pixel_format, data_type, img, data)
 }
 
-pub fn init_texture_data(gl: &gl::Gl, name: Texture, desc: t::Info, channel: ChannelType,
-                         data: &[&[u8]]) -> Result<(), t::CreationError> {
+pub fn init_texture_data(gl: &gl::Gl, private_caps: &PrivateCaps, name: Texture, desc: t::Info,
+                         channel: ChannelType, data: &[&[u8]]) -> Result<(), t::CreationError> {
     let opt_slices = desc.kind.get_num_slices();
     let num_slices = opt_slices.unwrap_or(1) as usize;
     let num_mips = desc.levels as usize;
@@ -767,7 +871,7 @@ pub fn init_texture_data(gl: &gl::Gl, name: Texture, desc: t::Info, channel: Cha
                     image.zoffset = i as t::Size;
                     image.depth = 1;
                 }
-                try!(update_texture(gl, name, desc.kind, face, &image, sub));
+                try!(update_texture(gl, private_caps, name, desc.kind, face, &image, sub));
             }
         }
     }
@@ -16,7 +16,7 @@
 
 //use cocoa::foundation::NSRange;
 
-use core::{pso, shade, state, target, texture, command};
+use core::{memory, pso, shade, state, target, texture, command};
 use core::{IndexType, VertexCount};
 use core::{MAX_VERTEX_ATTRIBUTES, MAX_CONSTANT_BUFFERS, MAX_RESOURCE_VIEWS,
            MAX_SAMPLERS, MAX_COLOR_TARGETS};
@@ -131,12 +131,13 @@ impl command::Buffer<Resources> for CommandBuffer {
             let mask = stage.into();
             for cb in cbs.iter() {
                 if cb.1.contains(mask) {
+                    let offset = cb.3.map_or(0, |(offset, _)| offset as u64);
                     match stage {
                         Stage::Vertex => {
-                            self.encoder.set_vertex_buffer(cb.2 as u64, 0, unsafe { *((cb.0).0).0 });
+                            self.encoder.set_vertex_buffer(cb.2 as u64, offset, unsafe { *((cb.0).0).0 });
                         },
                         Stage::Pixel => {
-                            self.encoder.set_fragment_buffer(cb.2 as u64, 0, unsafe { *((cb.0).0).0 });
+                            self.encoder.set_fragment_buffer(cb.2 as u64, offset, unsafe { *((cb.0).0).0 });
                         },
                         _ => { unimplemented!() }
                     }
@@ -172,6 +173,12 @@ impl command::Buffer<Resources> for CommandBuffer {
         // TODO: UAVs
     }
 
+    fn bind_atomic_counter_buffers(&mut self, acbs: &[pso::AtomicCounterBufferParam<Resources>]) {
+        if !acbs.is_empty() {
+            error!("Atomic counter buffers are not supported");
+        }
+    }
+
     fn bind_samplers(&mut self, ss: &[pso::SamplerParam<Resources>]) {
         use std::f32;
 
@@ -296,8 +303,15 @@ impl command::Buffer<Resources> for CommandBuffer {
     fn bind_index(&mut self, buf: Buffer, idx_type: IndexType) {
         use map::map_index_type;
 
-        // TODO(fkaa): pass wrapper instead
-        self.encoder.set_index_buffer(unsafe { *(buf.0).0 }, map_index_type(idx_type));
+        match map_index_type(idx_type) {
+            // TODO(fkaa): pass wrapper instead
+            Some(ty) => self.encoder.set_index_buffer(unsafe { *(buf.0).0 }, ty),
+            None => {
+                // skip the indexed draws rather than reading a stale buffer
+                error!("{:?} indices are not supported", idx_type);
+                self.encoder.clear_index_buffer();
+            }
+        }
     }
 
     fn set_scissor(&mut self, rect: target::Rect) {
@@ -465,4 +479,18 @@ impl command::Buffer<Resources> for CommandBuffer {
             }
         }
     }
+
+    fn push_debug_group(&mut self, _: &str) {}
+
+    fn pop_debug_group(&mut self) {}
+
+    fn memory_barrier(&mut self, _: memory::Barrier) {}
+
+    fn begin_occlusion_query(&mut self, _: core::QuerySlot) {}
+
+    fn end_occlusion_query(&mut self, _: core::QuerySlot) {}
+
+    fn begin_timer_query(&mut self, _: core::QuerySlot) {}
+
+    fn end_timer_query(&mut self, _: core::QuerySlot) {}
 }
@@ -470,6 +470,10 @@ impl MetalEncoder {
         self.cache.index_buffer = Some((buf, idx_type));
     }
 
+    pub fn clear_index_buffer(&mut self) {
+        self.cache.index_buffer = None;
+    }
+
     pub fn draw(&mut self, start: u64, count: u64) {
         self.render.draw_primitives(MTLPrimitiveType::Triangle, start, count);
     }
@@ -280,6 +280,7 @@ impl core::Factory<Resources> for Factory {
                     textures: Vec::new(),
                     unordereds: Vec::new(),
                     samplers: Vec::new(),
+                    atomic_counter_buffers: Vec::new(),
                     outputs: Vec::new(),
                     output_depth: false,
                     knows_outputs: false,
@@ -634,7 +635,7 @@ impl core::Factory<Resources> for Factory {
     fn view_texture_as_shader_resource_raw
         (&mut self,
          htex: &handle::RawTexture<Resources>,
-         _desc: core::texture::ResourceDesc)
+         desc: core::texture::ResourceDesc)
          -> Result<handle::RawShaderResourceView<Resources>, factory::ResourceViewError> {
         // use winapi::UINT;
         // use core::texture::{AaMode, Kind};
@@ -687,7 +688,8 @@ impl core::Factory<Resources> for Factory {
         // }
         // Ok(self.share.handles.borrow_mut().make_texture_srv(native::Srv(raw_view), htex))
         let raw_tex = self.frame_handles.ref_texture(htex).0;
-        Ok(self.share.handles.borrow_mut().make_texture_srv(native::Srv(raw_tex.0), htex))
+        Ok(self.share.handles.borrow_mut().make_texture_srv(native::Srv(raw_tex.0), htex,
+                                                            desc.channel))
     }
 
     fn view_texture_as_unordered_access_raw
@@ -309,6 +309,9 @@ pub fn create(format: core::format::Format,
             max_index_count: 0,
             max_texture_size: 0,
             max_patch_size: 0,
+            max_constant_buffer_size: 0,
+            max_color_targets: core::MAX_COLOR_TARGETS,
+            constant_buffer_offset_alignment: 256,
             instance_base_supported: false,
             instance_call_supported: false,
             instance_rate_supported: false,
@@ -318,6 +321,18 @@ pub fn create(format: core::format::Format,
             unordered_access_view_supported: false,
             separate_blending_slots_supported: false,
             copy_buffer_supported: true,
+            polygon_mode_supported: false,
+            texture_1d_supported: true,
+            texture_multisample_supported: true,
+            texture_buffer_supported: false,
+            half_float_vertex_supported: true,
+            half_float_color_supported: true,
+            index_u8_supported: false,
+            atomic_counter_supported: false,
+            multiview_supported: false,
+            layered_rendering_supported: false,
+            occlusion_query_supported: false,
+            timer_query_supported: false,
         },
         handles: RefCell::new(handle::Manager::new()),
     };
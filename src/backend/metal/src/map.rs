@@ -57,10 +57,11 @@ pub fn map_fill(fill: state::RasterMethod) -> MTLTriangleFillMode {
     }
 }
 
-pub fn map_index_type(ty: IndexType) -> MTLIndexType {
+pub fn map_index_type(ty: IndexType) -> Option<MTLIndexType> {
     match ty {
-        IndexType::U16 => MTLIndexType::UInt16,
-        IndexType::U32 => MTLIndexType::UInt32,
+        IndexType::U8 => None,
+        IndexType::U16 => Some(MTLIndexType::UInt16),
+        IndexType::U32 => Some(MTLIndexType::UInt32),
     }
 }
 
@@ -15,7 +15,7 @@
 use std::{mem, ptr};
 use std::collections::hash_map::{HashMap, Entry};
 use vk;
-use core::{self, pso, shade, target, texture as tex, handle};
+use core::{self, memory, pso, shade, target, texture as tex, handle};
 use core::command::{self, AccessInfo, AccessGuard};
 use core::state::RefValues;
 use core::{IndexType, VertexCount, SubmissionResult};
@@ -144,6 +144,7 @@ impl command::Buffer<Resources> for Buffer {
     fn bind_resource_views(&mut self, _: &[pso::ResourceViewParam<Resources>]) {}
     fn bind_unordered_views(&mut self, _: &[pso::UnorderedViewParam<Resources>]) {}
     fn bind_samplers(&mut self, _: &[pso::SamplerParam<Resources>]) {}
+    fn bind_atomic_counter_buffers(&mut self, _: &[pso::AtomicCounterBufferParam<Resources>]) {}
 
     fn bind_pixel_targets(&mut self, pts: pso::PixelTargetSet<Resources>) {
         let (dev, vk) = self.share.get_device();
@@ -293,6 +294,13 @@ impl command::Buffer<Resources> for Buffer {
     fn call_draw(&mut self, _: VertexCount, _: VertexCount, _: Option<command::InstanceParams>) {}
     fn call_draw_indexed(&mut self, _: VertexCount, _: VertexCount,
                          _: VertexCount, _: Option<command::InstanceParams>) {}
+    fn push_debug_group(&mut self, _: &str) {}
+    fn pop_debug_group(&mut self) {}
+    fn memory_barrier(&mut self, _: memory::Barrier) {}
+    fn begin_occlusion_query(&mut self, _: core::QuerySlot) {}
+    fn end_occlusion_query(&mut self, _: core::QuerySlot) {}
+    fn begin_timer_query(&mut self, _: core::QuerySlot) {}
+    fn end_timer_query(&mut self, _: core::QuerySlot) {}
 }
 
 
@@ -305,12 +313,16 @@ pub struct GraphicsQueue {
 
 impl GraphicsQueue {
     #[doc(hidden)]
-    pub fn new(share: SharePointer, q: vk::Queue, qf_id: u32) -> GraphicsQueue {
+    pub fn new(share: SharePointer, q: vk::Queue, qf_id: u32,
+               features: &vk::PhysicalDeviceFeatures) -> GraphicsQueue {
         let caps = core::Capabilities {
             max_vertex_count: 0,
             max_index_count: 0,
             max_texture_size: 0,
             max_patch_size: 0,
+            max_constant_buffer_size: 0,
+            max_color_targets: core::MAX_COLOR_TARGETS,
+            constant_buffer_offset_alignment: 0,
             instance_base_supported: false,
             instance_call_supported: false,
             instance_rate_supported: false,
@@ -320,6 +332,18 @@ impl GraphicsQueue {
             unordered_access_view_supported: false,
             separate_blending_slots_supported: false,
             copy_buffer_supported: true,
+            polygon_mode_supported: features.fillModeNonSolid != 0,
+            texture_1d_supported: true,
+            texture_multisample_supported: true,
+            texture_buffer_supported: false,
+            half_float_vertex_supported: false,
+            half_float_color_supported: false,
+            index_u8_supported: false,
+            atomic_counter_supported: false,
+            multiview_supported: false,
+            layered_rendering_supported: false,
+            occlusion_query_supported: false,
+            timer_query_supported: false,
         };
         GraphicsQueue {
             share: share,
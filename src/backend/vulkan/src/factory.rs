@@ -382,6 +382,7 @@ impl core::Factory<R> for Factory {
             textures: Vec::new(),
             unordereds: Vec::new(),
             samplers: Vec::new(),
+            atomic_counter_buffers: Vec::new(),
             outputs: Vec::new(),
             output_depth: false,
             knows_outputs: false,
@@ -834,7 +835,7 @@ impl core::Factory<R> for Factory {
                                        -> Result<h::RawShaderResourceView<R>, f::ResourceViewError> {
         use core::handle::Producer;
         self.view_texture(htex, desc, false).map(|view|
-            self.share.handles.lock().unwrap().make_texture_srv(view, htex))
+            self.share.handles.lock().unwrap().make_texture_srv(view, htex, desc.channel))
     }
 
     fn view_texture_as_unordered_access_raw(&mut self, _htex: &h::RawTexture<R>)
@@ -38,7 +38,7 @@ struct PhysicalDeviceInfo {
     _properties: vk::PhysicalDeviceProperties,
     queue_families: Vec<vk::QueueFamilyProperties>,
     memory: vk::PhysicalDeviceMemoryProperties,
-    _features: vk::PhysicalDeviceFeatures,
+    features: vk::PhysicalDeviceFeatures,
 }
 
 impl PhysicalDeviceInfo {
@@ -63,7 +63,7 @@ impl PhysicalDeviceInfo {
                 vk.GetPhysicalDeviceMemoryProperties(dev, &mut out);
                 out
             },
-            _features: unsafe {
+            features: unsafe {
                 let mut out = mem::zeroed();
                 vk.GetPhysicalDeviceFeatures(dev, &mut out);
                 out
@@ -220,6 +220,12 @@ pub fn create(app_name: &str, app_version: u32, layers: &[&str], extensions: &[&
                                         && (mt.propertyFlags & vk::MEMORY_PROPERTY_HOST_VISIBLE_BIT != 0))
                             .unwrap() as u32;
 
+    // line and point fill modes are optional
+    let features = vk::PhysicalDeviceFeatures {
+        fillModeNonSolid: dev.features.fillModeNonSolid,
+        .. unsafe { mem::zeroed() }
+    };
+
     let device = {
         let cstrings = dev_extensions.iter()
                                      .map(|&s| CString::new(s).unwrap())
@@ -235,8 +241,6 @@ pub fn create(app_name: &str, app_version: u32, layers: &[&str], extensions: &[&
             queueCount: 1,
             pQueuePriorities: &1.0,
         };
-        let features = unsafe{ mem::zeroed() };
-
         let dev_info = vk::DeviceCreateInfo {
             sType: vk::STRUCTURE_TYPE_DEVICE_CREATE_INFO,
             pNext: ptr::null(),
@@ -275,7 +279,7 @@ pub fn create(app_name: &str, app_version: u32, layers: &[&str], extensions: &[&
         physical_device: dev.device,
         handles: Mutex::new(core::handle::Manager::new()),
     });
-    let gfx_device = command::GraphicsQueue::new(share.clone(), queue, qf_id as u32);
+    let gfx_device = command::GraphicsQueue::new(share.clone(), queue, qf_id as u32, &features);
     let gfx_factory = factory::Factory::new(share.clone(), qf_id as u32, mvid_id, msys_id);
 
     (gfx_device, gfx_factory, share)
@@ -0,0 +1,605 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command buffer capture and replay
+//!
+//! A `Recorder` is a command buffer that doesn't talk to any device, but
+//! keeps the calls it receives as a `Capture`. Resources are not stored
+//! directly: each one is given a `ResourceId` in the order of appearance,
+//! and the actual resources are collected into a `ResourceTable`.
+//!
+//! The buffers, textures and samplers can be described to the recorder as
+//! they are created, so that the capture also holds what is needed to
+//! recreate them, including the initial data of the immutable ones.
+//!
+//! With the `serialize` feature, a `Capture` can be written to a file with
+//! `save`, loaded back with `load`, and replayed later into any command
+//! buffer, given a table of resources recreated in the same order. This is
+//! useful for reproducing driver bugs and for regression testing without
+//! the original application.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+#[cfg(feature = "serialize")]
+use std::io::{Read, Write};
+#[cfg(feature = "serialize")]
+use bincode;
+use {MAX_COLOR_TARGETS, MAX_VERTEX_ATTRIBUTES};
+use {Resources, IndexType, VertexCount,
+     ConstantBufferSlot, ResourceViewSlot, UnorderedViewSlot, SamplerSlot,
+     AtomicCounterSlot, QuerySlot};
+use {buffer, command, format, handle, memory, pso, shade, state, target, texture};
+use command::{ClearColor, InstanceParams};
+use shade::Usage;
+
+/// Index of a resource in the `ResourceTable` of its kind.
+pub type ResourceId = usize;
+
+/// A captured command buffer call, referring to resources by their id.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Command {
+    BindPipelineState(ResourceId),
+    BindVertexBuffers([Option<(ResourceId, pso::BufferOffset)>; MAX_VERTEX_ATTRIBUTES]),
+    BindConstantBuffers(Vec<(ResourceId, Usage, ConstantBufferSlot, Option<(usize, usize)>)>),
+    BindGlobalConstant(shade::Location, shade::UniformValue),
+    BindResourceViews(Vec<(ResourceId, Usage, ResourceViewSlot)>),
+    BindUnorderedViews(Vec<(ResourceId, Usage, UnorderedViewSlot)>),
+    BindSamplers(Vec<(ResourceId, Usage, SamplerSlot)>),
+    BindAtomicCounterBuffers(Vec<(ResourceId, Usage, AtomicCounterSlot)>),
+    BindPixelTargets {
+        colors: [Option<ResourceId>; MAX_COLOR_TARGETS],
+        depth: Option<ResourceId>,
+        stencil: Option<ResourceId>,
+        dimensions: Option<texture::Dimensions>,
+    },
+    BindIndex(ResourceId, IndexType),
+    SetScissor(target::Rect),
+    SetRefValues(state::RefValues),
+    CopyBuffer(ResourceId, ResourceId, usize, usize, usize),
+    CopyBufferToTexture(ResourceId, usize, ResourceId, texture::Kind,
+                        Option<texture::CubeFace>, texture::RawImageInfo),
+    CopyTextureToBuffer(ResourceId, texture::Kind, Option<texture::CubeFace>,
+                        texture::RawImageInfo, ResourceId, usize),
+    UpdateBuffer(ResourceId, Vec<u8>, usize),
+    UpdateTexture(ResourceId, texture::Kind, Option<texture::CubeFace>,
+                  Vec<u8>, texture::RawImageInfo),
+    GenerateMipmap(ResourceId),
+    ClearColor(ResourceId, ClearColor),
+    ClearDepthStencil(ResourceId, Option<target::Depth>, Option<target::Stencil>),
+    Draw(VertexCount, VertexCount, Option<InstanceParams>),
+    DrawIndexed(VertexCount, VertexCount, VertexCount, Option<InstanceParams>),
+    MemoryBarrier(memory::Barrier),
+    BeginOcclusionQuery(QuerySlot),
+    EndOcclusionQuery(QuerySlot),
+    BeginTimerQuery(QuerySlot),
+    EndTimerQuery(QuerySlot),
+    PushDebugGroup(String),
+    PopDebugGroup,
+}
+
+/// How a captured buffer was created.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct BufferDesc {
+    /// Creation info
+    pub info: buffer::Info,
+    /// Initial data, kept for the immutable buffers only, since the
+    /// others get their contents from the captured updates.
+    pub data: Option<Vec<u8>>,
+}
+
+/// How a captured texture was created.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct TextureDesc {
+    /// Creation info
+    pub info: texture::Info,
+    /// Channel type the texture was created for, if any
+    pub channel: Option<format::ChannelType>,
+    /// Initial data of each layer and level, kept for the immutable
+    /// textures only.
+    pub data: Option<Vec<Vec<u8>>>,
+}
+
+/// A recorded sequence of commands, along with the descriptors of the
+/// resources they refer to. The descriptors are indexed by `ResourceId`,
+/// and `None` for the resources that were not described to the recorder.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Capture {
+    /// Commands in the recorded order
+    pub commands: Vec<Command>,
+    /// Descriptors of the buffers
+    pub buffers: Vec<Option<BufferDesc>>,
+    /// Descriptors of the textures
+    pub textures: Vec<Option<TextureDesc>>,
+    /// Descriptors of the samplers
+    pub samplers: Vec<Option<texture::SamplerInfo>>,
+}
+
+/// Store a descriptor at the index of its resource id.
+fn set_desc<T>(list: &mut Vec<Option<T>>, id: ResourceId, desc: T) {
+    while list.len() <= id {
+        list.push(None);
+    }
+    list[id] = Some(desc);
+}
+
+/// Resources referenced by a `Capture`, indexed by `ResourceId`.
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub struct ResourceTable<R: Resources> {
+    pub buffers: Vec<R::Buffer>,
+    pub pipeline_states: Vec<R::PipelineStateObject>,
+    pub textures: Vec<R::Texture>,
+    pub resource_views: Vec<R::ShaderResourceView>,
+    pub unordered_views: Vec<R::UnorderedAccessView>,
+    pub samplers: Vec<R::Sampler>,
+    pub render_targets: Vec<R::RenderTargetView>,
+    pub depth_stencils: Vec<R::DepthStencilView>,
+}
+
+impl<R: Resources> ResourceTable<R> {
+    /// Create an empty table.
+    pub fn new() -> ResourceTable<R> {
+        ResourceTable {
+            buffers: Vec::new(),
+            pipeline_states: Vec::new(),
+            textures: Vec::new(),
+            resource_views: Vec::new(),
+            unordered_views: Vec::new(),
+            samplers: Vec::new(),
+            render_targets: Vec::new(),
+            depth_stencils: Vec::new(),
+        }
+    }
+}
+
+impl Capture {
+    /// Create an empty capture.
+    pub fn new() -> Capture {
+        Capture {
+            commands: Vec::new(),
+            buffers: Vec::new(),
+            textures: Vec::new(),
+            samplers: Vec::new(),
+        }
+    }
+
+    /// Write the capture, with its descriptors, to `writer`.
+    #[cfg(feature = "serialize")]
+    pub fn save<W: Write>(&self, writer: &mut W) -> Result<(), bincode::Error> {
+        bincode::serialize_into(writer, self, bincode::Infinite)
+    }
+
+    /// Read a capture written by `save` from `reader`.
+    #[cfg(feature = "serialize")]
+    pub fn load<R: Read>(reader: &mut R) -> Result<Capture, bincode::Error> {
+        bincode::deserialize_from(reader, bincode::Infinite)
+    }
+
+    /// Replay the captured commands into a command buffer, with the resource
+    /// ids mapped through the given table.
+    ///
+    /// Panics if the table is missing any of the referenced resources.
+    pub fn replay<R, C>(&self, table: &ResourceTable<R>, cb: &mut C) where
+        R: Resources,
+        C: command::Buffer<R>,
+    {
+        for com in &self.commands {
+            match *com {
+                Command::BindPipelineState(id) =>
+                    cb.bind_pipeline_state(table.pipeline_states[id].clone()),
+                Command::BindVertexBuffers(ref vbs) => {
+                    let mut set = pso::VertexBufferSet::new();
+                    for (slot, vb) in set.0.iter_mut().zip(vbs.iter()) {
+                        *slot = vb.map(|(id, offset)| (table.buffers[id], offset));
+                    }
+                    cb.bind_vertex_buffers(set);
+                },
+                Command::BindConstantBuffers(ref params) => {
+                    let params: Vec<_> = params.iter().map(|&(id, usage, slot, range)|
+                        pso::ConstantBufferParam(table.buffers[id], usage, slot, range)
+                    ).collect();
+                    cb.bind_constant_buffers(&params);
+                },
+                Command::BindGlobalConstant(loc, value) =>
+                    cb.bind_global_constant(loc, value),
+                Command::BindResourceViews(ref params) => {
+                    let params: Vec<_> = params.iter().map(|&(id, usage, slot)|
+                        pso::ResourceViewParam(table.resource_views[id], usage, slot)
+                    ).collect();
+                    cb.bind_resource_views(&params);
+                },
+                Command::BindUnorderedViews(ref params) => {
+                    let params: Vec<_> = params.iter().map(|&(id, usage, slot)|
+                        pso::UnorderedViewParam(table.unordered_views[id], usage, slot)
+                    ).collect();
+                    cb.bind_unordered_views(&params);
+                },
+                Command::BindAtomicCounterBuffers(ref params) => {
+                    let params: Vec<_> = params.iter().map(|&(id, usage, slot)|
+                        pso::AtomicCounterBufferParam(table.buffers[id], usage, slot)
+                    ).collect();
+                    cb.bind_atomic_counter_buffers(&params);
+                },
+                Command::BindSamplers(ref params) => {
+                    let params: Vec<_> = params.iter().map(|&(id, usage, slot)|
+                        pso::SamplerParam(table.samplers[id], usage, slot)
+                    ).collect();
+                    cb.bind_samplers(&params);
+                },
+                Command::BindPixelTargets { ref colors, depth, stencil, dimensions } => {
+                    let mut set = pso::PixelTargetSet::new();
+                    for (slot, color) in set.colors.iter_mut().zip(colors.iter()) {
+                        *slot = color.map(|id| table.render_targets[id]);
+                    }
+                    set.depth = depth.map(|id| table.depth_stencils[id].clone());
+                    set.stencil = stencil.map(|id| table.depth_stencils[id].clone());
+                    set.dimensions = dimensions;
+                    cb.bind_pixel_targets(set);
+                },
+                Command::BindIndex(id, ty) =>
+                    cb.bind_index(table.buffers[id], ty),
+                Command::SetScissor(rect) =>
+                    cb.set_scissor(rect),
+                Command::SetRefValues(rv) =>
+                    cb.set_ref_values(rv),
+                Command::CopyBuffer(src, dst, src_offset, dst_offset, size) =>
+                    cb.copy_buffer(table.buffers[src], table.buffers[dst],
+                                   src_offset, dst_offset, size),
+                Command::CopyBufferToTexture(src, src_offset, dst, kind, face, info) =>
+                    cb.copy_buffer_to_texture(table.buffers[src], src_offset,
+                                              table.textures[dst].clone(), kind, face, info),
+                Command::CopyTextureToBuffer(src, kind, face, info, dst, dst_offset) =>
+                    cb.copy_texture_to_buffer(table.textures[src].clone(), kind, face, info,
+                                              table.buffers[dst], dst_offset),
+                Command::UpdateBuffer(id, ref data, offset) =>
+                    cb.update_buffer(table.buffers[id], data, offset),
+                Command::UpdateTexture(id, kind, face, ref data, info) =>
+                    cb.update_texture(table.textures[id].clone(), kind, face, data, info),
+                Command::GenerateMipmap(id) =>
+                    cb.generate_mipmap(table.resource_views[id]),
+                Command::ClearColor(id, color) =>
+                    cb.clear_color(table.render_targets[id], color),
+                Command::ClearDepthStencil(id, depth, stencil) =>
+                    cb.clear_depth_stencil(table.depth_stencils[id].clone(), depth, stencil),
+                Command::Draw(start, count, instances) =>
+                    cb.call_draw(start, count, instances),
+                Command::DrawIndexed(start, count, base, instances) =>
+                    cb.call_draw_indexed(start, count, base, instances),
+                Command::MemoryBarrier(barrier) =>
+                    cb.memory_barrier(barrier),
+                Command::BeginOcclusionQuery(slot) =>
+                    cb.begin_occlusion_query(slot),
+                Command::EndOcclusionQuery(slot) =>
+                    cb.end_occlusion_query(slot),
+                Command::BeginTimerQuery(slot) =>
+                    cb.begin_timer_query(slot),
+                Command::EndTimerQuery(slot) =>
+                    cb.end_timer_query(slot),
+                Command::PushDebugGroup(ref name) =>
+                    cb.push_debug_group(name),
+                Command::PopDebugGroup =>
+                    cb.pop_debug_group(),
+            }
+        }
+    }
+}
+
+/// Assigns consecutive ids to the resources of one kind.
+struct Registry<T> {
+    list: Vec<T>,
+    ids: HashMap<T, ResourceId>,
+}
+
+impl<T: Clone + Hash + Eq> Registry<T> {
+    fn new() -> Registry<T> {
+        Registry {
+            list: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, res: &T) -> ResourceId {
+        if let Some(&id) = self.ids.get(res) {
+            return id
+        }
+        let id = self.list.len();
+        self.list.push(res.clone());
+        self.ids.insert(res.clone(), id);
+        id
+    }
+}
+
+/// A command buffer that captures the calls instead of executing them.
+pub struct Recorder<R: Resources> {
+    capture: Capture,
+    buffers: Registry<R::Buffer>,
+    pipeline_states: Registry<R::PipelineStateObject>,
+    textures: Registry<R::Texture>,
+    resource_views: Registry<R::ShaderResourceView>,
+    unordered_views: Registry<R::UnorderedAccessView>,
+    samplers: Registry<R::Sampler>,
+    render_targets: Registry<R::RenderTargetView>,
+    depth_stencils: Registry<R::DepthStencilView>,
+}
+
+impl<R: Resources> Recorder<R> {
+    /// Create a new empty recorder.
+    pub fn new() -> Recorder<R> {
+        Recorder {
+            capture: Capture::new(),
+            buffers: Registry::new(),
+            pipeline_states: Registry::new(),
+            textures: Registry::new(),
+            resource_views: Registry::new(),
+            unordered_views: Registry::new(),
+            samplers: Registry::new(),
+            render_targets: Registry::new(),
+            depth_stencils: Registry::new(),
+        }
+    }
+
+    /// Get the commands captured since the last reset, along with the
+    /// descriptors of all the resources described so far.
+    pub fn get_capture(&self) -> &Capture {
+        &self.capture
+    }
+
+    /// Describe a buffer, so that the capture holds what is needed to
+    /// recreate it. The initial data of an immutable buffer has to be
+    /// given, it's ignored for the other ones.
+    pub fn describe_buffer(&mut self, buf: &handle::RawBuffer<R>, data: Option<&[u8]>)
+                           -> ResourceId {
+        let id = self.buffers.get(buf.resource());
+        let info = *buf.get_info();
+        let data = match info.usage {
+            memory::Usage::Data => data.map(|d| d.to_vec()),
+            _ => None,
+        };
+        set_desc(&mut self.capture.buffers, id, BufferDesc {
+            info: info,
+            data: data,
+        });
+        id
+    }
+
+    /// Describe a texture, so that the capture holds what is needed to
+    /// recreate it. The initial data of an immutable texture has to be
+    /// given, it's ignored for the other ones.
+    pub fn describe_texture(&mut self, tex: &handle::RawTexture<R>,
+                            channel: Option<format::ChannelType>, data: Option<&[&[u8]]>)
+                            -> ResourceId {
+        let id = self.textures.get(tex.resource());
+        let info = *tex.get_info();
+        let data = match info.usage {
+            memory::Usage::Data => data.map(|d| d.iter().map(|level| level.to_vec()).collect()),
+            _ => None,
+        };
+        set_desc(&mut self.capture.textures, id, TextureDesc {
+            info: info,
+            channel: channel,
+            data: data,
+        });
+        id
+    }
+
+    /// Describe a sampler, so that the capture holds what is needed to
+    /// recreate it.
+    pub fn describe_sampler(&mut self, sampler: &handle::Sampler<R>) -> ResourceId {
+        let id = self.samplers.get(sampler.resource());
+        set_desc(&mut self.capture.samplers, id, *sampler.get_info());
+        id
+    }
+
+    /// Get the resources referenced by the captured commands.
+    /// Ids stay valid across resets, so the table only grows.
+    pub fn get_resources(&self) -> ResourceTable<R> {
+        ResourceTable {
+            buffers: self.buffers.list.clone(),
+            pipeline_states: self.pipeline_states.list.clone(),
+            textures: self.textures.list.clone(),
+            resource_views: self.resource_views.list.clone(),
+            unordered_views: self.unordered_views.list.clone(),
+            samplers: self.samplers.list.clone(),
+            render_targets: self.render_targets.list.clone(),
+            depth_stencils: self.depth_stencils.list.clone(),
+        }
+    }
+
+    fn push(&mut self, com: Command) {
+        self.capture.commands.push(com);
+    }
+}
+
+impl<R: Resources> command::Buffer<R> for Recorder<R> {
+    fn reset(&mut self) {
+        self.capture.commands.clear();
+    }
+
+    fn bind_pipeline_state(&mut self, pso: R::PipelineStateObject) {
+        let id = self.pipeline_states.get(&pso);
+        self.push(Command::BindPipelineState(id));
+    }
+
+    fn bind_vertex_buffers(&mut self, vbs: pso::VertexBufferSet<R>) {
+        let mut ids = [None; MAX_VERTEX_ATTRIBUTES];
+        for (id, vb) in ids.iter_mut().zip(vbs.0.iter()) {
+            *id = vb.map(|(buf, offset)| (self.buffers.get(&buf), offset));
+        }
+        self.push(Command::BindVertexBuffers(ids));
+    }
+
+    fn bind_constant_buffers(&mut self, cbs: &[pso::ConstantBufferParam<R>]) {
+        let params = cbs.iter().map(|&pso::ConstantBufferParam(buf, usage, slot, range)|
+            (self.buffers.get(&buf), usage, slot, range)
+        ).collect();
+        self.push(Command::BindConstantBuffers(params));
+    }
+
+    fn bind_global_constant(&mut self, loc: shade::Location, value: shade::UniformValue) {
+        self.push(Command::BindGlobalConstant(loc, value));
+    }
+
+    fn bind_resource_views(&mut self, srvs: &[pso::ResourceViewParam<R>]) {
+        let params = srvs.iter().map(|&pso::ResourceViewParam(view, usage, slot)|
+            (self.resource_views.get(&view), usage, slot)
+        ).collect();
+        self.push(Command::BindResourceViews(params));
+    }
+
+    fn bind_unordered_views(&mut self, uavs: &[pso::UnorderedViewParam<R>]) {
+        let params = uavs.iter().map(|&pso::UnorderedViewParam(view, usage, slot)|
+            (self.unordered_views.get(&view), usage, slot)
+        ).collect();
+        self.push(Command::BindUnorderedViews(params));
+    }
+
+    fn bind_samplers(&mut self, ss: &[pso::SamplerParam<R>]) {
+        let params = ss.iter().map(|&pso::SamplerParam(sampler, usage, slot)|
+            (self.samplers.get(&sampler), usage, slot)
+        ).collect();
+        self.push(Command::BindSamplers(params));
+    }
+
+    fn bind_atomic_counter_buffers(&mut self, acbs: &[pso::AtomicCounterBufferParam<R>]) {
+        let params = acbs.iter().map(|&pso::AtomicCounterBufferParam(buf, usage, slot)|
+            (self.buffers.get(&buf), usage, slot)
+        ).collect();
+        self.push(Command::BindAtomicCounterBuffers(params));
+    }
+
+    fn bind_pixel_targets(&mut self, pts: pso::PixelTargetSet<R>) {
+        let mut colors = [None; MAX_COLOR_TARGETS];
+        for (id, color) in colors.iter_mut().zip(pts.colors.iter()) {
+            *id = color.map(|view| self.render_targets.get(&view));
+        }
+        let depth = pts.depth.as_ref().map(|view| self.depth_stencils.get(view));
+        let stencil = pts.stencil.as_ref().map(|view| self.depth_stencils.get(view));
+        self.push(Command::BindPixelTargets {
+            colors: colors,
+            depth: depth,
+            stencil: stencil,
+            dimensions: pts.dimensions,
+        });
+    }
+
+    fn bind_index(&mut self, buf: R::Buffer, ty: IndexType) {
+        let id = self.buffers.get(&buf);
+        self.push(Command::BindIndex(id, ty));
+    }
+
+    fn set_scissor(&mut self, rect: target::Rect) {
+        self.push(Command::SetScissor(rect));
+    }
+
+    fn set_ref_values(&mut self, rv: state::RefValues) {
+        self.push(Command::SetRefValues(rv));
+    }
+
+    fn copy_buffer(&mut self, src: R::Buffer, dst: R::Buffer,
+                   src_offset_bytes: usize, dst_offset_bytes: usize,
+                   size_bytes: usize) {
+        let src = self.buffers.get(&src);
+        let dst = self.buffers.get(&dst);
+        self.push(Command::CopyBuffer(src, dst, src_offset_bytes, dst_offset_bytes, size_bytes));
+    }
+
+    fn copy_buffer_to_texture(&mut self,
+                              src: R::Buffer, src_offset_bytes: usize,
+                              dst: R::Texture, kind: texture::Kind,
+                              face: Option<texture::CubeFace>, info: texture::RawImageInfo) {
+        let src = self.buffers.get(&src);
+        let dst = self.textures.get(&dst);
+        self.push(Command::CopyBufferToTexture(src, src_offset_bytes, dst, kind, face, info));
+    }
+
+    fn copy_texture_to_buffer(&mut self,
+                              src: R::Texture, kind: texture::Kind,
+                              face: Option<texture::CubeFace>, info: texture::RawImageInfo,
+                              dst: R::Buffer, dst_offset_bytes: usize) {
+        let src = self.textures.get(&src);
+        let dst = self.buffers.get(&dst);
+        self.push(Command::CopyTextureToBuffer(src, kind, face, info, dst, dst_offset_bytes));
+    }
+
+    fn update_buffer(&mut self, buf: R::Buffer, data: &[u8], offset: usize) {
+        let id = self.buffers.get(&buf);
+        self.push(Command::UpdateBuffer(id, data.to_vec(), offset));
+    }
+
+    fn update_texture(&mut self, tex: R::Texture, kind: texture::Kind,
+                      face: Option<texture::CubeFace>, data: &[u8],
+                      info: texture::RawImageInfo) {
+        let id = self.textures.get(&tex);
+        self.push(Command::UpdateTexture(id, kind, face, data.to_vec(), info));
+    }
+
+    fn generate_mipmap(&mut self, srv: R::ShaderResourceView) {
+        let id = self.resource_views.get(&srv);
+        self.push(Command::GenerateMipmap(id));
+    }
+
+    fn clear_color(&mut self, target: R::RenderTargetView, value: ClearColor) {
+        let id = self.render_targets.get(&target);
+        self.push(Command::ClearColor(id, value));
+    }
+
+    fn clear_depth_stencil(&mut self, target: R::DepthStencilView,
+                           depth: Option<target::Depth>, stencil: Option<target::Stencil>) {
+        let id = self.depth_stencils.get(&target);
+        self.push(Command::ClearDepthStencil(id, depth, stencil));
+    }
+
+    fn call_draw(&mut self, start: VertexCount, count: VertexCount,
+                 instances: Option<InstanceParams>) {
+        self.push(Command::Draw(start, count, instances));
+    }
+
+    fn call_draw_indexed(&mut self, start: VertexCount, count: VertexCount,
+                         base: VertexCount, instances: Option<InstanceParams>) {
+        self.push(Command::DrawIndexed(start, count, base, instances));
+    }
+
+    fn memory_barrier(&mut self, barrier: memory::Barrier) {
+        self.push(Command::MemoryBarrier(barrier));
+    }
+
+    fn begin_occlusion_query(&mut self, slot: QuerySlot) {
+        self.push(Command::BeginOcclusionQuery(slot));
+    }
+
+    fn end_occlusion_query(&mut self, slot: QuerySlot) {
+        self.push(Command::EndOcclusionQuery(slot));
+    }
+
+    fn begin_timer_query(&mut self, slot: QuerySlot) {
+        self.push(Command::BeginTimerQuery(slot));
+    }
+
+    fn end_timer_query(&mut self, slot: QuerySlot) {
+        self.push(Command::EndTimerQuery(slot));
+    }
+
+    fn push_debug_group(&mut self, name: &str) {
+        self.push(Command::PushDebugGroup(name.to_string()));
+    }
+
+    fn pop_debug_group(&mut self) {
+        self.push(Command::PopDebugGroup);
+    }
+}
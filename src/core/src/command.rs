@@ -16,9 +16,9 @@
 
 use std::ops::Deref;
 use std::collections::hash_set::{self, HashSet};
-use {Resources, IndexType, InstanceCount, VertexCount,
+use {Resources, IndexType, InstanceCount, VertexCount, QuerySlot,
      SubmissionResult, SubmissionError};
-use {state, target, pso, shade, texture, handle};
+use {state, target, pso, shade, texture, handle, memory};
 
 /// A universal clear color supporting integet formats
 /// as well as the standard floating-point.
@@ -56,6 +56,8 @@ pub trait Buffer<R: Resources>: 'static + Send {
     fn bind_unordered_views(&mut self, &[pso::UnorderedViewParam<R>]);
     /// Bind a complete set of samplers
     fn bind_samplers(&mut self, &[pso::SamplerParam<R>]);
+    /// Bind a complete set of atomic counter buffers
+    fn bind_atomic_counter_buffers(&mut self, &[pso::AtomicCounterBufferParam<R>]);
     /// Bind a complete set of pixel targets, including multiple
     /// colors views and an optional depth/stencil view.
     fn bind_pixel_targets(&mut self, pso::PixelTargetSet<R>);
@@ -93,6 +95,20 @@ pub trait Buffer<R: Resources>: 'static + Send {
     fn call_draw(&mut self, VertexCount, VertexCount, Option<InstanceParams>);
     /// Draw a primitive with index buffer
     fn call_draw_indexed(&mut self, VertexCount, VertexCount, VertexCount, Option<InstanceParams>);
+    /// Make the preceding shader writes visible to the given kinds of accesses
+    fn memory_barrier(&mut self, memory::Barrier);
+    /// Start counting the samples passing the depth test into an occlusion query
+    fn begin_occlusion_query(&mut self, QuerySlot);
+    /// Stop counting the samples of an occlusion query
+    fn end_occlusion_query(&mut self, QuerySlot);
+    /// Start measuring the GPU time of the following commands into a timer query
+    fn begin_timer_query(&mut self, QuerySlot);
+    /// Stop measuring the GPU time of a timer query
+    fn end_timer_query(&mut self, QuerySlot);
+    /// Open a named group of commands, for debugging tools
+    fn push_debug_group(&mut self, name: &str);
+    /// Close the last opened group of commands
+    fn pop_debug_group(&mut self);
 }
 
 macro_rules! impl_clear {
@@ -15,9 +15,12 @@
 //! Dummy backend implementation to test the code for compile errors
 //! outside of the graphics development environment.
 
-use {Capabilities, Device, SubmissionResult, Resources, IndexType, VertexCount};
-use {state, target, handle, mapping, pso, shade, texture};
+use {AttributeSlot, Capabilities, Device, SubmissionResult, Resources, IndexType, VertexCount,
+     QuerySlot};
+use {ShaderSet, buffer, factory, format, state, target, handle, mapping, memory, pso, shade,
+     texture};
 use command::{self, AccessInfo};
+use handle::Producer;
 
 /// Dummy device which does minimal work, just to allow testing
 /// gfx-rs apps for compilation.
@@ -58,30 +61,196 @@ impl mapping::Gate<DummyResources> for DummyMapping {
     unsafe fn mut_slice<'a, 'b, T>(&'a self, _len: usize) -> &'b mut [T] { unimplemented!() }
 }
 
+fn dummy_capabilities() -> Capabilities {
+    Capabilities {
+        max_vertex_count: 0,
+        max_index_count: 0,
+        max_texture_size: 0,
+        max_patch_size: 0,
+        max_constant_buffer_size: 0,
+        max_color_targets: 0,
+        constant_buffer_offset_alignment: 0,
+        instance_base_supported: false,
+        instance_call_supported: false,
+        instance_rate_supported: false,
+        vertex_base_supported: false,
+        srgb_color_supported: false,
+        constant_buffer_supported: false,
+        unordered_access_view_supported: false,
+        separate_blending_slots_supported: false,
+        copy_buffer_supported: false,
+        polygon_mode_supported: false,
+        texture_1d_supported: false,
+        texture_multisample_supported: false,
+        texture_buffer_supported: false,
+        half_float_vertex_supported: false,
+        half_float_color_supported: false,
+        index_u8_supported: false,
+        atomic_counter_supported: false,
+        multiview_supported: false,
+        layered_rendering_supported: false,
+        occlusion_query_supported: false,
+        timer_query_supported: false,
+    }
+}
+
+/// Reflection of a vertex attribute of the programs the dummy factory
+/// would create, as a 4-component float vector. Used to test the linking
+/// of pipelines, with the fields that matter changed by the caller.
+pub fn dummy_attribute(name: &str, slot: AttributeSlot) -> shade::AttributeVar {
+    shade::AttributeVar {
+        name: name.to_string(),
+        slot: slot,
+        base_type: shade::BaseType::F32,
+        container: shade::ContainerType::Vector(4),
+    }
+}
+
+/// Reflection of a constant of `count` 4-component float vectors, global
+/// or inside a constant buffer.
+pub fn dummy_constant(name: &str, location: shade::Location, count: usize) -> shade::ConstVar {
+    shade::ConstVar {
+        name: name.to_string(),
+        location: location,
+        count: count,
+        base_type: shade::BaseType::F32,
+        container: shade::ContainerType::Vector(4),
+    }
+}
+
 impl DummyDevice {
     /// Create a new dummy device
     pub fn new() -> DummyDevice {
-        let caps = Capabilities {
-            max_vertex_count: 0,
-            max_index_count: 0,
-            max_texture_size: 0,
-            max_patch_size: 0,
-            instance_base_supported: false,
-            instance_call_supported: false,
-            instance_rate_supported: false,
-            vertex_base_supported: false,
-            srgb_color_supported: false,
-            constant_buffer_supported: false,
-            unordered_access_view_supported: false,
-            separate_blending_slots_supported: false,
-            copy_buffer_supported: false,
-        };
         DummyDevice {
-            capabilities: caps,
+            capabilities: dummy_capabilities(),
         }
     }
 }
 
+/// Dummy factory, creating handles to nothing, just to allow testing
+/// the code creating resources. The buffers can't be mapped.
+pub struct DummyFactory {
+    capabilities: Capabilities,
+    handles: handle::Manager<DummyResources>,
+}
+
+impl DummyFactory {
+    /// Create a new dummy factory
+    pub fn new() -> DummyFactory {
+        DummyFactory {
+            capabilities: dummy_capabilities(),
+            handles: handle::Manager::new(),
+        }
+    }
+}
+
+impl factory::Factory<DummyResources> for DummyFactory {
+    fn get_capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    fn create_buffer_raw(&mut self, info: buffer::Info)
+                         -> Result<handle::RawBuffer<DummyResources>, buffer::CreationError> {
+        Ok(self.handles.make_buffer((), info, None))
+    }
+
+    fn create_buffer_immutable_raw(&mut self, data: &[u8], stride: usize, role: buffer::Role,
+                                   bind: memory::Bind)
+                                   -> Result<handle::RawBuffer<DummyResources>,
+                                             buffer::CreationError> {
+        let info = buffer::Info {
+            role: role,
+            usage: memory::Usage::Data,
+            bind: bind,
+            size: data.len(),
+            stride: stride,
+        };
+        self.create_buffer_raw(info)
+    }
+
+    fn create_pipeline_state_raw(&mut self, program: &handle::Program<DummyResources>,
+                                 _: &pso::Descriptor)
+                                 -> Result<handle::RawPipelineState<DummyResources>,
+                                           pso::CreationError> {
+        Ok(self.handles.make_pso((), program))
+    }
+
+    fn create_program(&mut self, _: &ShaderSet<DummyResources>)
+                      -> Result<handle::Program<DummyResources>, shade::CreateProgramError> {
+        Ok(self.handles.make_program((), shade::ProgramInfo::default()))
+    }
+
+    fn create_shader(&mut self, _: shade::Stage, _: &[u8])
+                     -> Result<handle::Shader<DummyResources>, shade::CreateShaderError> {
+        Ok(self.handles.make_shader(()))
+    }
+
+    fn create_sampler(&mut self, info: texture::SamplerInfo) -> handle::Sampler<DummyResources> {
+        self.handles.make_sampler((), info)
+    }
+
+    fn read_mapping<'a, 'b, T>(&'a mut self, buf: &'b handle::Buffer<DummyResources, T>)
+                               -> Result<mapping::Reader<'b, DummyResources, T>, mapping::Error>
+        where T: Copy
+    {
+        Err(mapping::Error::InvalidAccess(memory::READ, buf.get_info().usage))
+    }
+
+    fn write_mapping<'a, 'b, T>(&'a mut self, buf: &'b handle::Buffer<DummyResources, T>)
+                                -> Result<mapping::Writer<'b, DummyResources, T>, mapping::Error>
+        where T: Copy
+    {
+        Err(mapping::Error::InvalidAccess(memory::WRITE, buf.get_info().usage))
+    }
+
+    fn create_texture_raw(&mut self, info: texture::Info, _: Option<format::ChannelType>,
+                          _: Option<&[&[u8]]>)
+                          -> Result<handle::RawTexture<DummyResources>, texture::CreationError> {
+        Ok(self.handles.make_texture((), info))
+    }
+
+    fn view_buffer_as_shader_resource_raw(&mut self, buf: &handle::RawBuffer<DummyResources>)
+        -> Result<handle::RawShaderResourceView<DummyResources>, factory::ResourceViewError>
+    {
+        Ok(self.handles.make_buffer_srv((), buf))
+    }
+
+    fn view_buffer_as_unordered_access_raw(&mut self, buf: &handle::RawBuffer<DummyResources>)
+        -> Result<handle::RawUnorderedAccessView<DummyResources>, factory::ResourceViewError>
+    {
+        Ok(self.handles.make_buffer_uav((), buf))
+    }
+
+    fn view_texture_as_shader_resource_raw(&mut self, tex: &handle::RawTexture<DummyResources>,
+                                           desc: texture::ResourceDesc)
+        -> Result<handle::RawShaderResourceView<DummyResources>, factory::ResourceViewError>
+    {
+        Ok(self.handles.make_texture_srv((), tex, desc.channel))
+    }
+
+    fn view_texture_as_unordered_access_raw(&mut self, tex: &handle::RawTexture<DummyResources>)
+        -> Result<handle::RawUnorderedAccessView<DummyResources>, factory::ResourceViewError>
+    {
+        Ok(self.handles.make_texture_uav((), tex))
+    }
+
+    fn view_texture_as_render_target_raw(&mut self, tex: &handle::RawTexture<DummyResources>,
+                                         desc: texture::RenderDesc)
+        -> Result<handle::RawRenderTargetView<DummyResources>, factory::TargetViewError>
+    {
+        let dim = tex.get_info().kind.get_level_dimensions(desc.level);
+        Ok(self.handles.make_rtv((), tex, dim))
+    }
+
+    fn view_texture_as_depth_stencil_raw(&mut self, tex: &handle::RawTexture<DummyResources>,
+                                         desc: texture::DepthStencilDesc)
+        -> Result<handle::RawDepthStencilView<DummyResources>, factory::TargetViewError>
+    {
+        let dim = tex.get_info().kind.get_level_dimensions(desc.level);
+        Ok(self.handles.make_dsv((), tex, dim))
+    }
+}
+
 /// Dummy command buffer, which ignores all the calls.
 pub struct DummyCommandBuffer;
 impl command::Buffer<DummyResources> for DummyCommandBuffer {
@@ -93,6 +262,7 @@ impl command::Buffer<DummyResources> for DummyCommandBuffer {
     fn bind_resource_views(&mut self, _: &[pso::ResourceViewParam<DummyResources>]) {}
     fn bind_unordered_views(&mut self, _: &[pso::UnorderedViewParam<DummyResources>]) {}
     fn bind_samplers(&mut self, _: &[pso::SamplerParam<DummyResources>]) {}
+    fn bind_atomic_counter_buffers(&mut self, _: &[pso::AtomicCounterBufferParam<DummyResources>]) {}
     fn bind_pixel_targets(&mut self, _: pso::PixelTargetSet<DummyResources>) {}
     fn bind_index(&mut self, _: (), _: IndexType) {}
     fn set_scissor(&mut self, _: target::Rect) {}
@@ -118,6 +288,13 @@ impl command::Buffer<DummyResources> for DummyCommandBuffer {
     fn call_draw(&mut self, _: VertexCount, _: VertexCount, _: Option<command::InstanceParams>) {}
     fn call_draw_indexed(&mut self, _: VertexCount, _: VertexCount,
                          _: VertexCount, _: Option<command::InstanceParams>) {}
+    fn push_debug_group(&mut self, _: &str) {}
+    fn memory_barrier(&mut self, _: memory::Barrier) {}
+    fn begin_occlusion_query(&mut self, _: QuerySlot) {}
+    fn end_occlusion_query(&mut self, _: QuerySlot) {}
+    fn begin_timer_query(&mut self, _: QuerySlot) {}
+    fn end_timer_query(&mut self, _: QuerySlot) {}
+    fn pop_debug_group(&mut self) {}
 }
 
 impl Device for DummyDevice {
@@ -132,7 +309,7 @@ impl Device for DummyDevice {
               _: &mut DummyCommandBuffer,
               _: &AccessInfo<Self::Resources>)
               -> SubmissionResult<()> {
-        unimplemented!()
+        Ok(())
     }
 
     fn fenced_submit(&mut self,
@@ -147,5 +324,13 @@ impl Device for DummyDevice {
         unimplemented!()
     }
 
+    fn get_occlusion_result(&mut self, _: QuerySlot) -> Option<bool> {
+        None
+    }
+
+    fn get_timer_result(&mut self, _: QuerySlot) -> Option<u64> {
+        None
+    }
+
     fn cleanup(&mut self) {}
 }
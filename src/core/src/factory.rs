@@ -465,7 +465,20 @@ pub trait Factory<R: Resources> {
                                      (&mut self, width: texture::Size, height: texture::Size)
                                       -> Result<handle::DepthStencilView<R, T>, CombinedError>
     {
-        let kind = texture::Kind::D2(width, height, texture::AaMode::Single);
+        self.create_depth_stencil_surface(width, height, texture::AaMode::Single)
+    }
+
+    /// Create a depth/stencil target that is never sampled, possibly
+    /// multisampled. Without the `SHADER_RESOURCE` binding, the storage
+    /// doesn't have to be a texture, so OpenGL backs it by a renderbuffer,
+    /// which is cheaper and doesn't need texture support for the format
+    /// or the sample count.
+    fn create_depth_stencil_surface<T: format::DepthFormat>
+                                   (&mut self, width: texture::Size, height: texture::Size,
+                                    aa: texture::AaMode)
+                                    -> Result<handle::DepthStencilView<R, T>, CombinedError>
+    {
+        let kind = texture::Kind::D2(width, height, aa);
         let cty = <T::Channel as format::ChannelTyped>::get_channel_type();
         let tex = try!(self.create_texture(kind, 1, DEPTH_STENCIL, Usage::Data, Some(cty)));
         let target = try!(self.view_texture_as_depth_stencil_trivial(&tex));
@@ -332,6 +332,117 @@ alias! {
     F16 = u16, // half-float
 }
 
+impl F16 {
+    /// Convert a 32-bit float, rounding to the nearest representable value.
+    /// Values out of range become infinities.
+    pub fn from_f32(value: f32) -> F16 {
+        use std::mem::transmute;
+        let x: u32 = unsafe { transmute(value) };
+        let sign = (x >> 16) & 0x8000;
+        let exp = ((x >> 23) & 0xFF) as i32;
+        let mant = x & 0x7F_FFFF;
+        if exp == 0xFF {
+            // infinity or NaN, keeping NaNs quiet
+            let nan = if mant != 0 {0x200} else {0};
+            return F16((sign | 0x7C00 | nan) as u16)
+        }
+        let e = exp - 127 + 15;
+        let bits = if e >= 0x1F {
+            sign | 0x7C00
+        } else if e <= 0 {
+            if e < -10 {
+                sign
+            } else {
+                // denormalized result
+                let m = mant | 0x80_0000;
+                let shift = (14 - e) as u32;
+                sign | ((m >> shift) + ((m >> (shift - 1)) & 1))
+            }
+        } else {
+            // rounding may carry into the exponent, which is still correct
+            (sign | (e as u32) << 10 | mant >> 13) + ((mant >> 12) & 1)
+        };
+        F16(bits as u16)
+    }
+
+    /// Convert to a 32-bit float, which is always exact.
+    pub fn to_f32(&self) -> f32 {
+        use std::mem::transmute;
+        let h = self.0 as u32;
+        let sign = (h & 0x8000) << 16;
+        let exp = (h >> 10) & 0x1F;
+        let mant = h & 0x3FF;
+        let bits = match exp {
+            0 => {
+                let value = mant as f32 / (1 << 24) as f32;
+                return if sign != 0 { -value } else { value }
+            },
+            0x1F => sign | 0x7F80_0000 | mant << 13,
+            _ => sign | (exp + 127 - 15) << 23 | mant << 13,
+        };
+        unsafe { transmute(bits) }
+    }
+}
+
+/// The `R10_G10_B10_A2` surface of the packed vertex formats, only usable
+/// in vertex buffers, as the backends have no signed normalized textures or
+/// render targets with it.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature="serialize", derive(Serialize, Deserialize))]
+pub enum PackedSurface {}
+
+impl SurfaceTyped for PackedSurface {
+    type DataType = u32;
+    fn get_surface_type() -> SurfaceType {
+        SurfaceType::R10_G10_B10_A2
+    }
+}
+
+impl BufferSurface for PackedSurface {}
+
+macro_rules! packed {
+    { $( $(#[$attr:meta])* $name:ident = $channel:ident [$low:expr, $scale:expr, $w_scale:expr], )* } => {
+        $(
+            $(#[$attr])*
+            #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+            #[cfg_attr(feature="serialize", derive(Serialize, Deserialize))]
+            pub struct $name(pub u32);
+
+            unsafe impl Pod for $name {}
+
+            impl Formatted for $name {
+                type Surface = PackedSurface;
+                type Channel = $channel;
+                type View = [f32; 4];
+            }
+
+            impl $name {
+                /// Pack 4 components, clamped to the representable range,
+                /// with X in the lowest bits.
+                pub fn pack(v: [f32; 4]) -> $name {
+                    let quantize = |x: f32, scale: f32, mask: u32| {
+                        ((x.max($low).min(1.0) * scale).round() as i32 as u32) & mask
+                    };
+                    $name(quantize(v[0], $scale, 0x3FF) |
+                          quantize(v[1], $scale, 0x3FF) << 10 |
+                          quantize(v[2], $scale, 0x3FF) << 20 |
+                          quantize(v[3], $w_scale, 0x3) << 30)
+                }
+            }
+        )*
+    }
+}
+
+packed! {
+    /// Unsigned normalized 10-bit XYZ and 2-bit W components, packed
+    /// into 32 bits. Known as `UNSIGNED_INT_2_10_10_10_REV` in GL.
+    U10x3A2Norm = Unorm [0.0, 1023.0, 3.0],
+    /// Signed normalized 10-bit XYZ and 2-bit W components, packed into
+    /// 32 bits, for compact normals and tangents. Known as
+    /// `INT_2_10_10_10_REV` in GL.
+    I10x3A2Norm = Inorm [-1.0, 511.0, 1.0],
+}
+
 /// Abstracted 1-element container for macro internal use
 pub type Vec1<T> = T;
 /// Abstracted 2-element container for macro internal use
@@ -19,7 +19,7 @@
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::Arc;
-use {buffer, shade, texture, Resources};
+use {buffer, format, shade, texture, Resources};
 use memory::Typed;
 
 /// Untyped buffer handle
@@ -78,6 +78,11 @@ impl<R: Resources> Deref for Program<R> {
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct RawPipelineState<R: Resources>(Arc<R::PipelineStateObject>, Program<R>);
 
+impl<R: Resources> RawPipelineState<R> {
+    /// Get the program the pipeline state was created with
+    pub fn get_program(&self) -> &Program<R> { &self.1 }
+}
+
 /// Raw texture handle
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct RawTexture<R: Resources>(Arc<texture::Raw<R>>);
@@ -118,7 +123,26 @@ enum ViewSource<R: Resources> {
 
 /// Raw Shader Resource View Handle
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct RawShaderResourceView<R: Resources>(Arc<R::ShaderResourceView>, ViewSource<R>);
+pub struct RawShaderResourceView<R: Resources>(
+    Arc<R::ShaderResourceView>,
+    ViewSource<R>,
+    Option<format::ChannelType>
+);
+
+impl<R: Resources> RawShaderResourceView<R> {
+    /// Get the texture viewed, or `None` for a buffer view
+    pub fn get_texture(&self) -> Option<&RawTexture<R>> {
+        match self.1 {
+            ViewSource::Texture(ref tex) => Some(tex),
+            ViewSource::Buffer(_) => None,
+        }
+    }
+
+    /// Get the channel type the texture is viewed as, or `None` for a buffer view
+    pub fn get_channel_type(&self) -> Option<format::ChannelType> {
+        self.2
+    }
+}
 
 /// Type-safe Shader Resource View Handle
 #[derive(Derivative)]
@@ -240,6 +264,9 @@ impl<R: Resources, T> Typed for DepthStencilView<R, T> {
 pub struct Sampler<R: Resources>(Arc<R::Sampler>, texture::SamplerInfo);
 
 impl<R: Resources> Sampler<R> {
+    #[doc(hidden)]
+    pub fn resource(&self) -> &R::Sampler { &self.0 }
+
     /// Get sampler info
     pub fn get_info(&self) -> &texture::SamplerInfo { &self.1 }
 }
@@ -280,7 +307,8 @@ pub trait Producer<R: Resources> {
     fn make_pso(&mut self, R::PipelineStateObject, &Program<R>) -> RawPipelineState<R>;
     fn make_texture(&mut self, R::Texture, texture::Info) -> RawTexture<R>;
     fn make_buffer_srv(&mut self, R::ShaderResourceView, &RawBuffer<R>) -> RawShaderResourceView<R>;
-    fn make_texture_srv(&mut self, R::ShaderResourceView, &RawTexture<R>) -> RawShaderResourceView<R>;
+    fn make_texture_srv(&mut self, R::ShaderResourceView, &RawTexture<R>, format::ChannelType)
+                        -> RawShaderResourceView<R>;
     fn make_buffer_uav(&mut self, R::UnorderedAccessView, &RawBuffer<R>) -> RawUnorderedAccessView<R>;
     fn make_texture_uav(&mut self, R::UnorderedAccessView, &RawTexture<R>) -> RawUnorderedAccessView<R>;
     fn make_rtv(&mut self, R::RenderTargetView, &RawTexture<R>, texture::Dimensions) -> RawRenderTargetView<R>;
@@ -342,13 +370,14 @@ impl<R: Resources> Producer<R> for Manager<R> {
     fn make_buffer_srv(&mut self, res: R::ShaderResourceView, buf: &RawBuffer<R>) -> RawShaderResourceView<R> {
         let r = Arc::new(res);
         self.srvs.push(r.clone());
-        RawShaderResourceView(r, ViewSource::Buffer(buf.clone()))
+        RawShaderResourceView(r, ViewSource::Buffer(buf.clone()), None)
     }
 
-    fn make_texture_srv(&mut self, res: R::ShaderResourceView, tex: &RawTexture<R>) -> RawShaderResourceView<R> {
+    fn make_texture_srv(&mut self, res: R::ShaderResourceView, tex: &RawTexture<R>,
+                        channel: format::ChannelType) -> RawShaderResourceView<R> {
         let r = Arc::new(res);
         self.srvs.push(r.clone());
-        RawShaderResourceView(r, ViewSource::Texture(tex.clone()))
+        RawShaderResourceView(r, ViewSource::Texture(tex.clone()), Some(channel))
     }
 
     fn make_buffer_uav(&mut self, res: R::UnorderedAccessView, buf: &RawBuffer<R>) -> RawUnorderedAccessView<R> {
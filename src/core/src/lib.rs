@@ -32,6 +32,8 @@ extern crate serde;
 #[cfg(feature = "serialize")]
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "serialize")]
+extern crate bincode;
 
 use std::fmt::{self, Debug};
 use std::error::Error;
@@ -42,6 +44,7 @@ pub use draw_state::{state, target};
 pub use self::factory::Factory;
 
 pub mod buffer;
+pub mod capture;
 pub mod command;
 pub mod dummy;
 pub mod factory;
@@ -52,6 +55,7 @@ pub mod memory;
 pub mod pso;
 pub mod shade;
 pub mod texture;
+pub mod trace;
 
 /// Compile-time maximum number of vertex attributes.
 pub const MAX_VERTEX_ATTRIBUTES: usize = 16;
@@ -65,6 +69,8 @@ pub const MAX_RESOURCE_VIEWS: usize = 32;
 pub const MAX_UNORDERED_VIEWS: usize = 4;
 /// Compile-time maximum number of samplers.
 pub const MAX_SAMPLERS: usize = 16;
+/// Compile-time maximum number of atomic counter buffers.
+pub const MAX_ATOMIC_COUNTER_BUFFERS: usize = 8;
 
 /// Draw vertex count.
 pub type VertexCount = u32;
@@ -85,6 +91,10 @@ pub type UnorderedViewSlot = u8;
 pub type ColorSlot = u8;
 /// Slot for a sampler.
 pub type SamplerSlot = u8;
+/// Slot for an atomic counter buffer.
+pub type AtomicCounterSlot = u8;
+/// Slot for an occlusion or timer query, each kind having its own slots.
+pub type QuerySlot = u16;
 
 macro_rules! define_shaders {
     ( $($name:ident),+ ) => {
@@ -142,6 +152,13 @@ pub struct Capabilities {
     pub max_index_count: usize,
     pub max_texture_size: usize,
     pub max_patch_size: usize,
+    /// Maximum size of a bound constant buffer, in bytes.
+    pub max_constant_buffer_size: usize,
+    /// Maximum number of color targets bound at once.
+    pub max_color_targets: usize,
+    /// Alignment of the offsets a constant buffer can be bound at, in
+    /// bytes. Zero if only whole buffers can be bound.
+    pub constant_buffer_offset_alignment: usize,
 
     pub instance_base_supported: bool,
     pub instance_call_supported: bool,
@@ -152,6 +169,98 @@ pub struct Capabilities {
     pub unordered_access_view_supported: bool,
     pub separate_blending_slots_supported: bool,
     pub copy_buffer_supported: bool,
+    pub polygon_mode_supported: bool,
+    pub texture_1d_supported: bool,
+    pub texture_multisample_supported: bool,
+    pub texture_buffer_supported: bool,
+    pub half_float_vertex_supported: bool,
+    pub half_float_color_supported: bool,
+    pub index_u8_supported: bool,
+    pub atomic_counter_supported: bool,
+    pub multiview_supported: bool,
+    pub layered_rendering_supported: bool,
+    pub occlusion_query_supported: bool,
+    pub timer_query_supported: bool,
+}
+
+/// An optional feature of the device, as reported by `Capabilities`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Feature {
+    /// Instanced draws with a base instance
+    InstanceBase,
+    /// Instanced draws
+    InstanceCall,
+    /// Per-instance vertex attributes
+    InstanceRate,
+    /// Indexed draws with a base vertex
+    VertexBase,
+    /// sRGB color targets
+    SrgbColor,
+    /// Constant (uniform) buffers
+    ConstantBuffer,
+    /// Unordered access views
+    UnorderedAccessView,
+    /// Blending state specified per color target
+    SeparateBlendingSlots,
+    /// Buffer to buffer copies
+    CopyBuffer,
+    /// Line and point rasterization of polygons
+    PolygonMode,
+    /// 1D textures
+    Texture1D,
+    /// Multisampled textures
+    TextureMultisample,
+    /// Buffer textures
+    TextureBuffer,
+    /// Tessellation shaders
+    Tessellation,
+    /// Vertex attributes made of 16-bit floats
+    HalfFloatVertex,
+    /// Rendering to 16-bit floating-point color targets
+    HalfFloatColor,
+    /// Index buffers with 8-bit indices
+    IndexU8,
+    /// Atomic counter buffers
+    AtomicCounter,
+    /// Rendering to several layers at once with multiview shaders
+    Multiview,
+    /// Rendering to several layers at once from a geometry shader
+    LayeredRendering,
+    /// Occlusion queries
+    OcclusionQuery,
+    /// Timer queries, measuring the GPU time of commands
+    TimerQuery,
+}
+
+impl Capabilities {
+    /// Check if the device supports a given feature.
+    pub fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::InstanceBase => self.instance_base_supported,
+            Feature::InstanceCall => self.instance_call_supported,
+            Feature::InstanceRate => self.instance_rate_supported,
+            Feature::VertexBase => self.vertex_base_supported,
+            Feature::SrgbColor => self.srgb_color_supported,
+            Feature::ConstantBuffer => self.constant_buffer_supported,
+            Feature::UnorderedAccessView => self.unordered_access_view_supported,
+            Feature::SeparateBlendingSlots => self.separate_blending_slots_supported,
+            Feature::CopyBuffer => self.copy_buffer_supported,
+            Feature::PolygonMode => self.polygon_mode_supported,
+            Feature::Texture1D => self.texture_1d_supported,
+            Feature::TextureMultisample => self.texture_multisample_supported,
+            Feature::TextureBuffer => self.texture_buffer_supported,
+            Feature::Tessellation => self.max_patch_size != 0,
+            Feature::HalfFloatVertex => self.half_float_vertex_supported,
+            Feature::HalfFloatColor => self.half_float_color_supported,
+            Feature::IndexU8 => self.index_u8_supported,
+            Feature::AtomicCounter => self.atomic_counter_supported,
+            Feature::Multiview => self.multiview_supported,
+            Feature::LayeredRendering => self.layered_rendering_supported,
+            Feature::OcclusionQuery => self.occlusion_query_supported,
+            Feature::TimerQuery => self.timer_query_supported,
+        }
+    }
 }
 
 /// Describes what geometric primitives are created from vertex data.
@@ -207,6 +316,7 @@ pub enum Primitive {
 pub enum IndexType {
     U16,
     U32,
+    U8,
 }
 
 /// Different types of a specific API.
@@ -254,6 +364,23 @@ impl Error for SubmissionError {
 #[allow(missing_docs)]
 pub type SubmissionResult<T> = Result<T, SubmissionError>;
 
+/// Counters of the work done by the GPU over a frame.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct PipelineStatistics {
+    /// Number of vertices submitted by the draw calls.
+    pub vertices_submitted: u64,
+    /// Number of primitives submitted by the draw calls.
+    pub primitives_submitted: u64,
+    /// Number of primitives reaching the clipping stage.
+    pub clipping_input_primitives: u64,
+    /// Number of primitives left after clipping, including the ones
+    /// split by it and excluding the ones culled.
+    pub clipping_output_primitives: u64,
+    /// Number of fragment shader invocations.
+    pub fragment_shader_invocations: u64,
+}
+
 /// A `Device` is responsible for submitting `CommandBuffer`s to the GPU.
 pub trait Device: Sized {
     /// Associated `Resources` type.
@@ -285,6 +412,36 @@ pub trait Device: Sized {
     /// Stalls the current thread until the fence is satisfied
     fn wait_fence(&mut self, &handle::Fence<Self::Resources>);
 
+    /// Check whether any sample passed the depth test during the last run
+    /// of an occlusion query, without blocking. Returns `None` until the
+    /// result is available, if the query never ran, or if the device has
+    /// no occlusion queries, which is the default.
+    fn get_occlusion_result(&mut self, _slot: QuerySlot) -> Option<bool> {
+        None
+    }
+
+    /// Get the GPU time elapsed during the last run of a timer query, in
+    /// nanoseconds, without blocking. Returns `None` until the result is
+    /// available, if the query never ran, or if the device has no timer
+    /// queries, which is the default.
+    fn get_timer_result(&mut self, _slot: QuerySlot) -> Option<u64> {
+        None
+    }
+
+    /// Start or stop counting the work done by the GPU in each frame, as
+    /// delimited by calls to `cleanup`. Returns `false` if the device
+    /// can't count it, which is the default.
+    fn set_pipeline_statistics(&mut self, _enabled: bool) -> bool {
+        false
+    }
+
+    /// Get the statistics of the latest frame whose results are
+    /// available, usually a couple frames behind, without blocking.
+    /// Returns `None` if they are not counted.
+    fn get_pipeline_statistics(&self) -> Option<PipelineStatistics> {
+        None
+    }
+
     /// Cleanup unused resources. This should be called between frames.
     fn cleanup(&mut self);
 }
@@ -373,12 +530,74 @@ impl Frame {
     }
 }
 
+/// Presentation settings of a `SwapChain`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct SwapChainConfig {
+    /// Number of vertical blanks to wait for before presenting a frame,
+    /// zero meaning that vsync is disabled.
+    pub swap_interval: u8,
+    /// Number of buffers in the chain, including the one being displayed.
+    pub buffer_count: u8,
+}
+
+impl SwapChainConfig {
+    /// Create the default configuration: vsync with double buffering.
+    pub fn new() -> SwapChainConfig {
+        SwapChainConfig {
+            swap_interval: 1,
+            buffer_count: 2,
+        }
+    }
+
+    /// Enable or disable vsync.
+    pub fn with_vsync(self, vsync: bool) -> SwapChainConfig {
+        SwapChainConfig {
+            swap_interval: if vsync { 1 } else { 0 },
+            .. self
+        }
+    }
+
+    /// Set the number of vertical blanks to wait for between presents.
+    pub fn with_swap_interval(self, swap_interval: u8) -> SwapChainConfig {
+        SwapChainConfig {
+            swap_interval: swap_interval,
+            .. self
+        }
+    }
+
+    /// Set the number of buffers in the chain.
+    pub fn with_buffer_count(self, buffer_count: u8) -> SwapChainConfig {
+        SwapChainConfig {
+            buffer_count: buffer_count,
+            .. self
+        }
+    }
+
+    /// Check if the presentation is synchronized with the vertical blank.
+    pub fn is_vsync(&self) -> bool {
+        self.swap_interval != 0
+    }
+}
+
 /// The `SwapChain` is the backend representation of the surface.
 /// It consists of multiple buffers, which will be presented on the surface.
 pub trait SwapChain {
+    /// Get the configuration the swapchain was created with. Defaults to
+    /// `SwapChainConfig::new()` for the swapchains not tracking it.
+    fn get_config(&self) -> SwapChainConfig {
+        SwapChainConfig::new()
+    }
+
     /// Acquire a new frame for rendering. This needs to be called before presenting.
     fn acquire_frame(&mut self) -> Frame;
 
     /// Present one acquired frame in FIFO order.
     fn present(&mut self);
+
+    /// Get the number of frames presented so far, or zero for the
+    /// swapchains not counting them.
+    fn get_present_count(&self) -> usize {
+        0
+    }
 }
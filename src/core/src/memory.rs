@@ -67,6 +67,34 @@ bitflags!(
     }
 );
 
+bitflags!(
+    /// Kinds of memory accesses that have to see the writes done by the shaders
+    /// before a barrier, like the ones of a compute pass to an unordered view.
+    #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+    pub flags Barrier: u16 {
+        /// Vertex attributes fetched from vertex buffers.
+        const VERTEX_BUFFER_BARRIER    = 0x1,
+        /// Indices fetched from index buffers.
+        const INDEX_BUFFER_BARRIER     = 0x2,
+        /// Reads from constant buffers.
+        const CONSTANT_BUFFER_BARRIER  = 0x4,
+        /// Texture fetches through shader resource views.
+        const SHADER_RESOURCE_BARRIER  = 0x8,
+        /// Loads and stores through unordered access views.
+        const UNORDERED_ACCESS_BARRIER = 0x10,
+        /// Reads of the arguments of indirect draws and dispatches.
+        const INDIRECT_BARRIER         = 0x20,
+        /// Copies and updates of buffers and textures.
+        const TRANSFER_BARRIER         = 0x40,
+        /// Rendering into color and depth/stencil targets.
+        const RENDER_TARGET_BARRIER    = 0x80,
+        /// Operations on atomic counters.
+        const ATOMIC_COUNTER_BARRIER   = 0x100,
+        /// All of the above.
+        const ALL_BARRIERS             = 0x1FF,
+    }
+);
+
 impl Bind {
     /// Is this memory bound to be mutated ?
     pub fn is_mutable(&self) -> bool {
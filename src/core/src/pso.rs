@@ -19,9 +19,9 @@
 //! crate.
 
 use {MAX_COLOR_TARGETS, MAX_VERTEX_ATTRIBUTES, MAX_CONSTANT_BUFFERS,
-     MAX_RESOURCE_VIEWS, MAX_UNORDERED_VIEWS, MAX_SAMPLERS};
+     MAX_RESOURCE_VIEWS, MAX_UNORDERED_VIEWS, MAX_SAMPLERS, MAX_ATOMIC_COUNTER_BUFFERS};
 use {ConstantBufferSlot, ColorSlot, ResourceViewSlot,
-     UnorderedViewSlot, SamplerSlot,
+     UnorderedViewSlot, SamplerSlot, AtomicCounterSlot,
      Primitive, Resources};
 use {format, state as s, texture};
 use shade::Usage;
@@ -50,6 +50,45 @@ impl Error for CreationError {
     }
 }
 
+/// Origin of the coordinates across a point sprite, `gl_PointCoord`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum PointOrigin {
+    /// The coordinates go down from the upper left corner.
+    UpperLeft,
+    /// The coordinates go up from the lower left corner.
+    LowerLeft,
+}
+
+/// Point sprite configuration of the PSO, used when drawing a
+/// `Primitive::PointList`. Only applied by the GL backend, the others
+/// always take the point size from the shader.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct PointInfo {
+    /// Let the vertex shader write the size of each point to
+    /// `gl_PointSize`, instead of using `size`.
+    pub program_size: bool,
+    /// Size of the points in pixels, when not written by the shader.
+    pub size: u16,
+    /// Size in pixels below which multisampled points fade out instead
+    /// of getting smaller.
+    pub fade_threshold: u16,
+    /// Origin of the sprite coordinates.
+    pub origin: PointOrigin,
+}
+
+impl Default for PointInfo {
+    fn default() -> PointInfo {
+        PointInfo {
+            program_size: true,
+            size: 1,
+            fade_threshold: 1,
+            origin: PointOrigin::UpperLeft,
+        }
+    }
+}
+
 /// Color output configuration of the PSO.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
@@ -158,6 +197,8 @@ pub type ResourceViewDesc = Usage;
 pub type UnorderedViewDesc = Usage;
 /// PSO sampler descriptor
 pub type SamplerDesc = Usage;
+/// PSO atomic counter buffer descriptor
+pub type AtomicCounterBufferDesc = Usage;
 /// PSO color target descriptor
 pub type ColorTargetDesc = (format::Format, ColorInfo);
 /// PSO depth-stencil target descriptor
@@ -174,6 +215,8 @@ pub struct Descriptor {
     pub rasterizer: s::Rasterizer,
     /// Enable scissor test
     pub scissor: bool,
+    /// Point sprite setup
+    pub point: PointInfo,
     /// Vertex buffers
     pub vertex_buffers: [Option<VertexBufferDesc>; MAX_VERTEX_BUFFERS],
     /// Vertex attributes
@@ -186,6 +229,8 @@ pub struct Descriptor {
     pub unordered_views: [Option<UnorderedViewDesc>; MAX_UNORDERED_VIEWS],
     /// Samplers
     pub samplers: [Option<SamplerDesc>; MAX_SAMPLERS],
+    /// Atomic counter buffers
+    pub atomic_counter_buffers: [Option<AtomicCounterBufferDesc>; MAX_ATOMIC_COUNTER_BUFFERS],
     /// Render target views (RTV)
     pub color_targets: [Option<ColorTargetDesc>; MAX_COLOR_TARGETS],
     /// Depth stencil view (DSV)
@@ -199,12 +244,14 @@ impl Descriptor {
             primitive: primitive,
             rasterizer: rast,
             scissor: false,
+            point: PointInfo::default(),
             vertex_buffers: [None; MAX_VERTEX_BUFFERS],
             attributes: [None; MAX_VERTEX_ATTRIBUTES],
             constant_buffers: [None; MAX_CONSTANT_BUFFERS],
             resource_views: [None; MAX_RESOURCE_VIEWS],
             unordered_views: [None; MAX_UNORDERED_VIEWS],
             samplers: [None; MAX_SAMPLERS],
+            atomic_counter_buffers: [None; MAX_ATOMIC_COUNTER_BUFFERS],
             color_targets: [None; MAX_COLOR_TARGETS],
             depth_stencil: None,
         }
@@ -225,9 +272,11 @@ impl<R: Resources> VertexBufferSet<R> {
     }
 }
 
-/// A constant buffer run-time parameter for PSO.
+/// A constant buffer run-time parameter for PSO, with the offset and
+/// size in bytes of the bound range, or `None` to bind the whole buffer.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub struct ConstantBufferParam<R: Resources>(pub R::Buffer, pub Usage, pub ConstantBufferSlot);
+pub struct ConstantBufferParam<R: Resources>(pub R::Buffer, pub Usage, pub ConstantBufferSlot,
+                                             pub Option<(BufferOffset, usize)>);
 
 /// A shader resource view (SRV) run-time parameter for PSO.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -237,6 +286,10 @@ pub struct ResourceViewParam<R: Resources>(pub R::ShaderResourceView, pub Usage,
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct UnorderedViewParam<R: Resources>(pub R::UnorderedAccessView, pub Usage, pub UnorderedViewSlot);
 
+/// An atomic counter buffer run-time parameter for PSO.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AtomicCounterBufferParam<R: Resources>(pub R::Buffer, pub Usage, pub AtomicCounterSlot);
+
 /// A sampler run-time parameter for PSO.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct SamplerParam<R: Resources>(pub R::Sampler, pub Usage, pub SamplerSlot);
@@ -19,7 +19,8 @@
 use std::{fmt, cmp, hash};
 use std::error::Error;
 use {Resources};
-use {AttributeSlot, ColorSlot, ConstantBufferSlot, ResourceViewSlot, SamplerSlot, UnorderedViewSlot};
+use {AtomicCounterSlot, AttributeSlot, ColorSlot, ConstantBufferSlot, ResourceViewSlot, SamplerSlot,
+     UnorderedViewSlot};
 
 #[cfg(feature = "mint")]
 use mint;
@@ -145,6 +146,7 @@ pub type Location = usize;
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum UniformValue {
     I32(i32),
+    U32(u32),
     F32(f32),
 
     I32Vector2([i32; 2]),
@@ -164,6 +166,7 @@ impl fmt::Debug for UniformValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             UniformValue::I32(x)            => write!(f, "ValueI32({:?})", x),
+            UniformValue::U32(x)            => write!(f, "ValueU32({:?})", x),
             UniformValue::F32(x)            => write!(f, "ValueF32({:?})", x),
 
             UniformValue::I32Vector2(ref v) => write!(f, "ValueI32Vector2({:?})", &v[..]),
@@ -434,6 +437,21 @@ pub struct SamplerVar {
     pub usage: Usage,
 }
 
+/// Atomic counter buffer shader parameter. Its binding point is fixed by
+/// the shader, and it is identified by the counters it contains.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct AtomicCounterBufferVar {
+    /// Slot of the atomic counter buffer.
+    pub slot: AtomicCounterSlot,
+    /// Size (in bytes) of this buffer's data.
+    pub size: usize,
+    /// What program stage this buffer is used in.
+    pub usage: Usage,
+    /// List of the counters in this buffer, located by their offset in bytes.
+    pub counters: Vec<ConstVar>,
+}
+
 /// Target output variable.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
@@ -449,7 +467,7 @@ pub struct OutputVar {
 }
 
 /// Metadata about a program.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct ProgramInfo {
     /// Attributes in the program
@@ -464,6 +482,8 @@ pub struct ProgramInfo {
     pub unordereds: Vec<UnorderedVar>,
     /// Samplers in the program
     pub samplers: Vec<SamplerVar>,
+    /// Atomic counter buffers in the program
+    pub atomic_counter_buffers: Vec<AtomicCounterBufferVar>,
     /// Output targets in the program
     pub outputs: Vec<OutputVar>,
     /// A flag indicating that the pixel shader manually assigns the depth.
@@ -473,6 +493,82 @@ pub struct ProgramInfo {
     pub knows_outputs: bool,
 }
 
+/// Check if a variable is called `name`. Arrays are reported by some
+/// backends with a `[0]` suffix, which is optional here.
+pub fn is_named(var_name: &str, name: &str) -> bool {
+    var_name == name || (var_name.ends_with("[0]") && &var_name[..var_name.len() - 3] == name)
+}
+
+impl ConstantBufferVar {
+    /// Find an element of this buffer by name.
+    pub fn get_element(&self, name: &str) -> Option<&ConstVar> {
+        self.elements.iter().find(|e| is_named(&e.name, name))
+    }
+}
+
+impl AtomicCounterBufferVar {
+    /// Find a counter of this buffer by name.
+    pub fn get_counter(&self, name: &str) -> Option<&ConstVar> {
+        self.counters.iter().find(|c| is_named(&c.name, name))
+    }
+}
+
+impl ProgramInfo {
+    /// Find a vertex attribute by name.
+    pub fn get_attribute(&self, name: &str) -> Option<&AttributeVar> {
+        self.vertex_attributes.iter().find(|a| is_named(&a.name, name))
+    }
+
+    /// Find a global constant by name.
+    pub fn get_global(&self, name: &str) -> Option<&ConstVar> {
+        self.globals.iter().find(|g| is_named(&g.name, name))
+    }
+
+    /// Find a constant buffer by name.
+    pub fn get_constant_buffer(&self, name: &str) -> Option<&ConstantBufferVar> {
+        self.constant_buffers.iter().find(|cb| cb.name == name)
+    }
+
+    /// Find a constant by name, either global or inside a constant buffer,
+    /// along with the buffer containing it.
+    pub fn get_constant(&self, name: &str) -> Option<(Option<&ConstantBufferVar>, &ConstVar)> {
+        match self.get_global(name) {
+            Some(var) => Some((None, var)),
+            None => self.constant_buffers.iter().filter_map(|cb| {
+                cb.get_element(name).map(|var| (Some(cb), var))
+            }).next(),
+        }
+    }
+
+    /// Find a texture by name.
+    pub fn get_texture(&self, name: &str) -> Option<&TextureVar> {
+        self.textures.iter().find(|t| is_named(&t.name, name))
+    }
+
+    /// Find an unordered access resource by name.
+    pub fn get_unordered(&self, name: &str) -> Option<&UnorderedVar> {
+        self.unordereds.iter().find(|u| is_named(&u.name, name))
+    }
+
+    /// Find a sampler by name.
+    pub fn get_sampler(&self, name: &str) -> Option<&SamplerVar> {
+        self.samplers.iter().find(|s| is_named(&s.name, name))
+    }
+
+    /// Find an atomic counter by name, along with the buffer containing it.
+    pub fn get_atomic_counter(&self, name: &str) -> Option<(&AtomicCounterBufferVar, &ConstVar)> {
+        self.atomic_counter_buffers.iter().filter_map(|acb| {
+            acb.get_counter(name).map(|var| (acb, var))
+        }).next()
+    }
+
+    /// Find an output target by name. Always `None` for backends that
+    /// don't know the outputs, see `knows_outputs`.
+    pub fn get_output(&self, name: &str) -> Option<&OutputVar> {
+        self.outputs.iter().find(|o| is_named(&o.name, name))
+    }
+}
+
 /// A program
 #[derive(Debug)]
 pub struct Program<R: Resources> {
@@ -553,6 +649,7 @@ impl ConstVar {
         }
         match (self.base_type, self.container, *value) {
             (BaseType::I32, ContainerType::Single,         UniformValue::I32(_))        => Ok(()),
+            (BaseType::U32, ContainerType::Single,         UniformValue::U32(_))        => Ok(()),
             (BaseType::F32, ContainerType::Single,         UniformValue::F32(_))        => Ok(()),
 
             (BaseType::F32, ContainerType::Vector(2),      UniformValue::F32Vector2(_)) => Ok(()),
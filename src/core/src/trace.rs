@@ -0,0 +1,408 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Call tracing
+//!
+//! A `TraceDevice` wraps the device of a backend, forwarding all the calls
+//! to it while writing them to a log, along with the calls recorded by its
+//! `TraceCommandBuffer`s. Resources are written with the names given by the
+//! backend, like the object names of OpenGL:
+//!
+//! ```text
+//! frame 0
+//!   pin_submitted_resources(<4 handles>)
+//!   submit
+//!     clear_color(Surface(0), Float([0.1, 0.2, 0.3, 1.0]))
+//!     bind_vertex_buffers([(0, (2, 0))])
+//!     call_draw(0, 3, None)
+//!   cleanup
+//! ```
+//!
+//! ```ignore
+//! let (device, mut factory) = gfx_device_gl::create(get_proc_address);
+//! let mut device = TraceDevice::new(device, try!(File::create("trace.log")));
+//! let mut encoder: Encoder<_, _> = TraceCommandBuffer::new(factory.create_command_buffer()).into();
+//! ```
+//!
+//! Each frame ends with `cleanup`, and the calls of a frame don't mention
+//! its number, so the log of two frames can be compared with `diff`. The
+//! factory is not traced: resources are created by the one of the backend.
+
+use std::io::{self, Write};
+use {Capabilities, Device, IndexType, PipelineStatistics, QuerySlot, Resources,
+     SubmissionResult, VertexCount};
+use {command, handle, memory, pso, shade, state, target, texture};
+use command::{ClearColor, InstanceParams};
+
+/// A command buffer keeping a description of the calls it forwards.
+#[derive(Debug)]
+pub struct TraceCommandBuffer<C> {
+    inner: C,
+    calls: Vec<String>,
+}
+
+impl<C> TraceCommandBuffer<C> {
+    /// Wrap a command buffer of the backend.
+    pub fn new(inner: C) -> TraceCommandBuffer<C> {
+        TraceCommandBuffer {
+            inner: inner,
+            calls: Vec::new(),
+        }
+    }
+
+    /// Get the calls recorded since the last reset.
+    pub fn get_calls(&self) -> &[String] {
+        &self.calls
+    }
+
+    /// Get the command buffer of the backend.
+    pub fn get_inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Unwrap the command buffer of the backend.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<R: Resources, C: command::Buffer<R>> command::Buffer<R> for TraceCommandBuffer<C> {
+    fn reset(&mut self) {
+        self.calls.clear();
+        self.inner.reset()
+    }
+
+    fn bind_pipeline_state(&mut self, pso: R::PipelineStateObject) {
+        self.calls.push(format!("bind_pipeline_state({:?})", pso));
+        self.inner.bind_pipeline_state(pso)
+    }
+
+    fn bind_vertex_buffers(&mut self, vbs: pso::VertexBufferSet<R>) {
+        let bound: Vec<_> = vbs.0.iter().enumerate()
+            .filter_map(|(i, vb)| vb.map(|vb| (i, vb)))
+            .collect();
+        self.calls.push(format!("bind_vertex_buffers({:?})", bound));
+        self.inner.bind_vertex_buffers(vbs)
+    }
+
+    fn bind_constant_buffers(&mut self, cbs: &[pso::ConstantBufferParam<R>]) {
+        self.calls.push(format!("bind_constant_buffers({:?})", cbs));
+        self.inner.bind_constant_buffers(cbs)
+    }
+
+    fn bind_global_constant(&mut self, loc: shade::Location, value: shade::UniformValue) {
+        self.calls.push(format!("bind_global_constant({}, {:?})", loc, value));
+        self.inner.bind_global_constant(loc, value)
+    }
+
+    fn bind_resource_views(&mut self, rvs: &[pso::ResourceViewParam<R>]) {
+        self.calls.push(format!("bind_resource_views({:?})", rvs));
+        self.inner.bind_resource_views(rvs)
+    }
+
+    fn bind_unordered_views(&mut self, uvs: &[pso::UnorderedViewParam<R>]) {
+        self.calls.push(format!("bind_unordered_views({:?})", uvs));
+        self.inner.bind_unordered_views(uvs)
+    }
+
+    fn bind_samplers(&mut self, ss: &[pso::SamplerParam<R>]) {
+        self.calls.push(format!("bind_samplers({:?})", ss));
+        self.inner.bind_samplers(ss)
+    }
+
+    fn bind_atomic_counter_buffers(&mut self, acbs: &[pso::AtomicCounterBufferParam<R>]) {
+        self.calls.push(format!("bind_atomic_counter_buffers({:?})", acbs));
+        self.inner.bind_atomic_counter_buffers(acbs)
+    }
+
+    fn bind_pixel_targets(&mut self, pts: pso::PixelTargetSet<R>) {
+        self.calls.push(format!("bind_pixel_targets({:?})", pts));
+        self.inner.bind_pixel_targets(pts)
+    }
+
+    fn bind_index(&mut self, buf: R::Buffer, ty: IndexType) {
+        self.calls.push(format!("bind_index({:?}, {:?})", buf, ty));
+        self.inner.bind_index(buf, ty)
+    }
+
+    fn set_scissor(&mut self, rect: target::Rect) {
+        self.calls.push(format!("set_scissor({:?})", rect));
+        self.inner.set_scissor(rect)
+    }
+
+    fn set_ref_values(&mut self, rv: state::RefValues) {
+        self.calls.push(format!("set_ref_values({:?})", rv));
+        self.inner.set_ref_values(rv)
+    }
+
+    fn copy_buffer(&mut self, src: R::Buffer, dst: R::Buffer,
+                   src_offset_bytes: usize, dst_offset_bytes: usize,
+                   size_bytes: usize) {
+        self.calls.push(format!("copy_buffer({:?}, {:?}, {}, {}, {})",
+                                src, dst, src_offset_bytes, dst_offset_bytes, size_bytes));
+        self.inner.copy_buffer(src, dst, src_offset_bytes, dst_offset_bytes, size_bytes)
+    }
+
+    fn copy_buffer_to_texture(&mut self,
+                              src: R::Buffer, src_offset_bytes: usize,
+                              dst: R::Texture, kind: texture::Kind,
+                              face: Option<texture::CubeFace>, info: texture::RawImageInfo) {
+        self.calls.push(format!("copy_buffer_to_texture({:?}, {}, {:?}, {:?}, {:?}, {:?})",
+                                src, src_offset_bytes, dst, kind, face, info));
+        self.inner.copy_buffer_to_texture(src, src_offset_bytes, dst, kind, face, info)
+    }
+
+    fn copy_texture_to_buffer(&mut self,
+                              src: R::Texture, kind: texture::Kind,
+                              face: Option<texture::CubeFace>, info: texture::RawImageInfo,
+                              dst: R::Buffer, dst_offset_bytes: usize) {
+        self.calls.push(format!("copy_texture_to_buffer({:?}, {:?}, {:?}, {:?}, {:?}, {})",
+                                src, kind, face, info, dst, dst_offset_bytes));
+        self.inner.copy_texture_to_buffer(src, kind, face, info, dst, dst_offset_bytes)
+    }
+
+    fn update_buffer(&mut self, buf: R::Buffer, data: &[u8], offset: usize) {
+        // the data is summed up by its size, to keep the log readable
+        self.calls.push(format!("update_buffer({:?}, <{} bytes>, {})", buf, data.len(), offset));
+        self.inner.update_buffer(buf, data, offset)
+    }
+
+    fn update_texture(&mut self, tex: R::Texture, kind: texture::Kind,
+                      face: Option<texture::CubeFace>, data: &[u8],
+                      info: texture::RawImageInfo) {
+        self.calls.push(format!("update_texture({:?}, {:?}, {:?}, <{} bytes>, {:?})",
+                                tex, kind, face, data.len(), info));
+        self.inner.update_texture(tex, kind, face, data, info)
+    }
+
+    fn generate_mipmap(&mut self, srv: R::ShaderResourceView) {
+        self.calls.push(format!("generate_mipmap({:?})", srv));
+        self.inner.generate_mipmap(srv)
+    }
+
+    fn clear_color(&mut self, target: R::RenderTargetView, value: ClearColor) {
+        self.calls.push(format!("clear_color({:?}, {:?})", target, value));
+        self.inner.clear_color(target, value)
+    }
+
+    fn clear_depth_stencil(&mut self, target: R::DepthStencilView,
+                           depth: Option<target::Depth>, stencil: Option<target::Stencil>) {
+        self.calls.push(format!("clear_depth_stencil({:?}, {:?}, {:?})", target, depth, stencil));
+        self.inner.clear_depth_stencil(target, depth, stencil)
+    }
+
+    fn call_draw(&mut self, start: VertexCount, count: VertexCount,
+                 instances: Option<InstanceParams>) {
+        self.calls.push(format!("call_draw({}, {}, {:?})", start, count, instances));
+        self.inner.call_draw(start, count, instances)
+    }
+
+    fn call_draw_indexed(&mut self, start: VertexCount,
+                         count: VertexCount, base: VertexCount,
+                         instances: Option<InstanceParams>) {
+        self.calls.push(format!("call_draw_indexed({}, {}, {}, {:?})",
+                                start, count, base, instances));
+        self.inner.call_draw_indexed(start, count, base, instances)
+    }
+
+    fn memory_barrier(&mut self, barrier: memory::Barrier) {
+        self.calls.push(format!("memory_barrier({:?})", barrier));
+        self.inner.memory_barrier(barrier)
+    }
+
+    fn begin_occlusion_query(&mut self, slot: QuerySlot) {
+        self.calls.push(format!("begin_occlusion_query({})", slot));
+        self.inner.begin_occlusion_query(slot)
+    }
+
+    fn end_occlusion_query(&mut self, slot: QuerySlot) {
+        self.calls.push(format!("end_occlusion_query({})", slot));
+        self.inner.end_occlusion_query(slot)
+    }
+
+    fn begin_timer_query(&mut self, slot: QuerySlot) {
+        self.calls.push(format!("begin_timer_query({})", slot));
+        self.inner.begin_timer_query(slot)
+    }
+
+    fn end_timer_query(&mut self, slot: QuerySlot) {
+        self.calls.push(format!("end_timer_query({})", slot));
+        self.inner.end_timer_query(slot)
+    }
+
+    fn push_debug_group(&mut self, name: &str) {
+        self.calls.push(format!("push_debug_group({:?})", name));
+        self.inner.push_debug_group(name)
+    }
+
+    fn pop_debug_group(&mut self) {
+        self.calls.push("pop_debug_group()".to_string());
+        self.inner.pop_debug_group()
+    }
+}
+
+/// A device writing a log of the calls it forwards to the device of a
+/// backend.
+pub struct TraceDevice<D, W> {
+    inner: D,
+    output: W,
+    frame: usize,
+    frame_started: bool,
+    error: Option<io::Error>,
+}
+
+impl<D: Device, W: Write> TraceDevice<D, W> {
+    /// Wrap a device, writing the log to `output`.
+    pub fn new(inner: D, output: W) -> TraceDevice<D, W> {
+        TraceDevice {
+            inner: inner,
+            output: output,
+            frame: 0,
+            frame_started: false,
+            error: None,
+        }
+    }
+
+    /// Get the device of the backend.
+    pub fn get_inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Get the device of the backend, to access its specific features.
+    /// The calls made to it directly are not traced.
+    pub fn get_inner_mut(&mut self) -> &mut D {
+        &mut self.inner
+    }
+
+    /// Get the number of the frame being traced.
+    pub fn get_frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Take the error that stopped the writing of the log, if any.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Unwrap the device and the output of the log.
+    pub fn into_inner(self) -> (D, W) {
+        (self.inner, self.output)
+    }
+
+    fn write(&mut self, indent: usize, line: &str) {
+        if self.error.is_some() {
+            return
+        }
+        if !self.frame_started {
+            self.frame_started = true;
+            if let Err(e) = writeln!(self.output, "frame {}", self.frame) {
+                self.error = Some(e);
+                return
+            }
+        }
+        if let Err(e) = writeln!(self.output, "{:2$}{}", "", line, indent * 2) {
+            self.error = Some(e);
+        }
+    }
+
+    fn write_calls(&mut self, name: &str, calls: &[String]) {
+        self.write(1, name);
+        for call in calls {
+            self.write(2, call);
+        }
+    }
+}
+
+impl<D: Device, W: Write> Device for TraceDevice<D, W> {
+    type Resources = D::Resources;
+    type CommandBuffer = TraceCommandBuffer<D::CommandBuffer>;
+
+    fn get_capabilities(&self) -> &Capabilities {
+        self.inner.get_capabilities()
+    }
+
+    fn pin_submitted_resources(&mut self, man: &handle::Manager<D::Resources>) {
+        self.write(1, &format!("pin_submitted_resources(<{} handles>)", man.count()));
+        self.inner.pin_submitted_resources(man)
+    }
+
+    fn submit(&mut self,
+              cb: &mut TraceCommandBuffer<D::CommandBuffer>,
+              access: &command::AccessInfo<D::Resources>)
+              -> SubmissionResult<()> {
+        self.write_calls("submit", &cb.calls);
+        let result = self.inner.submit(&mut cb.inner, access);
+        if let Err(ref e) = result {
+            self.write(1, &format!("-> {:?}", e));
+        }
+        result
+    }
+
+    fn fenced_submit(&mut self,
+                     cb: &mut TraceCommandBuffer<D::CommandBuffer>,
+                     access: &command::AccessInfo<D::Resources>,
+                     after: Option<handle::Fence<D::Resources>>)
+                     -> SubmissionResult<handle::Fence<D::Resources>> {
+        let name = match after {
+            Some(ref fence) => format!("fenced_submit(after {:?})", fence),
+            None => "fenced_submit".to_string(),
+        };
+        self.write_calls(&name, &cb.calls);
+        let result = self.inner.fenced_submit(&mut cb.inner, access, after);
+        match result {
+            Ok(ref fence) => self.write(1, &format!("-> {:?}", fence)),
+            Err(ref e) => self.write(1, &format!("-> {:?}", e)),
+        }
+        result
+    }
+
+    fn wait_fence(&mut self, fence: &handle::Fence<D::Resources>) {
+        self.write(1, &format!("wait_fence({:?})", fence));
+        self.inner.wait_fence(fence)
+    }
+
+    fn get_occlusion_result(&mut self, slot: QuerySlot) -> Option<bool> {
+        let result = self.inner.get_occlusion_result(slot);
+        self.write(1, &format!("get_occlusion_result({}) -> {:?}", slot, result));
+        result
+    }
+
+    fn get_timer_result(&mut self, slot: QuerySlot) -> Option<u64> {
+        let result = self.inner.get_timer_result(slot);
+        self.write(1, &format!("get_timer_result({}) -> {:?}", slot, result));
+        result
+    }
+
+    fn set_pipeline_statistics(&mut self, enabled: bool) -> bool {
+        let result = self.inner.set_pipeline_statistics(enabled);
+        self.write(1, &format!("set_pipeline_statistics({}) -> {}", enabled, result));
+        result
+    }
+
+    fn get_pipeline_statistics(&self) -> Option<PipelineStatistics> {
+        self.inner.get_pipeline_statistics()
+    }
+
+    fn cleanup(&mut self) {
+        self.write(1, "cleanup");
+        if self.error.is_none() {
+            if let Err(e) = self.output.flush() {
+                self.error = Some(e);
+            }
+        }
+        self.frame += 1;
+        self.frame_started = false;
+        self.inner.cleanup()
+    }
+}
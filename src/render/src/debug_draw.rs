@@ -0,0 +1,181 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Immediate-mode debug lines.
+//!
+//! Lines are accumulated during the frame by `DebugDraw` and flushed at
+//! once into a `DynamicBuffer`, to be drawn with a single call:
+//!
+//! ```ignore
+//! debug.wire_box([-1.0; 3], [1.0; 3], [1.0, 1.0, 0.0, 1.0]);
+//! debug.axis([0.0; 3], 2.0);
+//! let slice = try!(debug.flush(&mut lines, &mut factory, &mut encoder));
+//! data.vbuf = lines.get_buffer().clone();
+//! encoder.draw(&slice, &pso, &data);
+//! ```
+//!
+//! The shaders are up to the application: the vertex attributes are named
+//! `a_Pos` and `a_Color`, and the pipeline is expected to use
+//! `Primitive::LineList`.
+
+use std::f32::consts::PI;
+use std::mem;
+use std::ops::Range;
+use core::{format, Resources};
+use core::command::Buffer as CommandBuffer;
+use core::factory::Factory;
+use core::format::Formatted;
+use core::memory::Pod;
+use dynamic::{DynamicBuffer, WriteError};
+use encoder::Encoder;
+use pso::buffer::{Element, ElemOffset, Structure};
+use slice::Slice;
+
+/// Number of segments in each circle of a sphere.
+const SPHERE_SEGMENTS: usize = 24;
+
+/// A line end, with a position and a color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Vertex {
+    /// Position, bound to `a_Pos`
+    pub pos: [f32; 3],
+    /// Color, bound to `a_Color`
+    pub color: [f32; 4],
+}
+
+unsafe impl Pod for Vertex {}
+
+impl Structure<format::Format> for Vertex {
+    fn query(name: &str) -> Option<Element<format::Format>> {
+        let (format, offset) = match name {
+            "a_Pos" => (<[f32; 3] as Formatted>::get_format(), 0),
+            "a_Color" => (<[f32; 4] as Formatted>::get_format(),
+                          3 * mem::size_of::<f32>() as ElemOffset),
+            _ => return None,
+        };
+        Some(Element {
+            format: format,
+            offset: offset,
+        })
+    }
+}
+
+/// Accumulator of the debug lines of a frame.
+#[derive(Clone, Debug, Default)]
+pub struct DebugDraw {
+    vertices: Vec<Vertex>,
+}
+
+impl DebugDraw {
+    /// Create an empty accumulator.
+    pub fn new() -> DebugDraw {
+        DebugDraw::default()
+    }
+
+    /// Get the line ends accumulated so far, two per line.
+    pub fn get_vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    /// Add a line between two points.
+    pub fn line(&mut self, from: [f32; 3], to: [f32; 3], color: [f32; 4]) {
+        self.vertices.push(Vertex { pos: from, color: color });
+        self.vertices.push(Vertex { pos: to, color: color });
+    }
+
+    /// Add the edges of an axis-aligned box.
+    pub fn wire_box(&mut self, min: [f32; 3], max: [f32; 3], color: [f32; 4]) {
+        let corner = |i: usize| [
+            if i & 1 != 0 { max[0] } else { min[0] },
+            if i & 2 != 0 { max[1] } else { min[1] },
+            if i & 4 != 0 { max[2] } else { min[2] },
+        ];
+        self.edges(&corner, color);
+    }
+
+    /// Add three circles, one around each axis, outlining a sphere.
+    pub fn sphere(&mut self, center: [f32; 3], radius: f32, color: [f32; 4]) {
+        for axis in 0 .. 3 {
+            let point = |i: usize| {
+                let (s, c) = (i as f32 / SPHERE_SEGMENTS as f32 * 2.0 * PI).sin_cos();
+                let mut pos = center;
+                pos[(axis + 1) % 3] += radius * c;
+                pos[(axis + 2) % 3] += radius * s;
+                pos
+            };
+            for i in 0 .. SPHERE_SEGMENTS {
+                self.line(point(i), point(i + 1), color);
+            }
+        }
+    }
+
+    /// Add the X, Y and Z axes of `size` length, in red, green and blue.
+    pub fn axis(&mut self, origin: [f32; 3], size: f32) {
+        for axis in 0 .. 3 {
+            let mut end = origin;
+            end[axis] += size;
+            let mut color = [0.0, 0.0, 0.0, 1.0];
+            color[axis] = 1.0;
+            self.line(origin, end, color);
+        }
+    }
+
+    /// Add the edges of the volume seen by a camera, given the inverse of
+    /// its column-major view-projection matrix. The depth range is the one
+    /// of the clip space: `-1.0 .. 1.0` for OpenGL and `0.0 .. 1.0` for the
+    /// other backends.
+    pub fn frustum(&mut self, inv_view_proj: [[f32; 4]; 4], depth: Range<f32>,
+                   color: [f32; 4]) {
+        let m = inv_view_proj;
+        let corner = |i: usize| {
+            let v = [if i & 1 != 0 { 1.0 } else { -1.0 },
+                     if i & 2 != 0 { 1.0 } else { -1.0 },
+                     if i & 4 != 0 { depth.end } else { depth.start },
+                     1.0];
+            let mut out = [0.0f32; 4];
+            for row in 0 .. 4 {
+                out[row] = (0 .. 4).map(|col| m[col][row] * v[col]).sum();
+            }
+            [out[0] / out[3], out[1] / out[3], out[2] / out[3]]
+        };
+        self.edges(&corner, color);
+    }
+
+    /// Add the 12 edges between 8 corners indexed by their XYZ bits.
+    fn edges<F: Fn(usize) -> [f32; 3]>(&mut self, corner: &F, color: [f32; 4]) {
+        for i in 0 .. 8 {
+            for &bit in [1, 2, 4].iter() {
+                if i & bit == 0 {
+                    self.line(corner(i), corner(i | bit), color);
+                }
+            }
+        }
+    }
+
+    /// Write the accumulated lines into `buffer` and clear them,
+    /// returning the slice drawing them. On an error the lines are kept,
+    /// to be flushed again.
+    pub fn flush<R, F, C>(&mut self, buffer: &mut DynamicBuffer<R, Vertex>,
+                          factory: &mut F, encoder: &mut Encoder<R, C>)
+                          -> Result<Slice<R>, WriteError> where
+        R: Resources,
+        F: Factory<R> + ?Sized,
+        C: CommandBuffer<R>,
+    {
+        let slice = try!(buffer.write(factory, encoder, &self.vertices));
+        self.vertices.clear();
+        Ok(slice)
+    }
+}
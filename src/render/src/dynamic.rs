@@ -0,0 +1,217 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Streaming of per-frame vertex and constant data.
+
+use std::{fmt, mem, slice};
+use std::error::Error;
+use core::{buffer, handle, Resources, VertexCount};
+use core::command::Buffer as CommandBuffer;
+use core::factory::Factory;
+use core::memory::{Bind, Pod, Typed, Usage};
+use encoder::{Encoder, UpdateError};
+use slice::{IndexBuffer, Slice};
+
+/// An error writing a `DynamicBuffer`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WriteError {
+    /// The data is larger than the buffer.
+    Update(UpdateError<usize>),
+    /// The buffer replacing the full one could not be created.
+    Creation(buffer::CreationError),
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WriteError::Update(ref e) => write!(f, "{}", e),
+            WriteError::Creation(ref e) => write!(f, "{}: {}", self.description(), e),
+        }
+    }
+}
+
+impl Error for WriteError {
+    fn description(&self) -> &str {
+        match *self {
+            WriteError::Update(ref e) => e.description(),
+            WriteError::Creation(_) => "Could not orphan the dynamic buffer",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            WriteError::Update(ref e) => Some(e),
+            WriteError::Creation(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<UpdateError<usize>> for WriteError {
+    fn from(e: UpdateError<usize>) -> WriteError {
+        WriteError::Update(e)
+    }
+}
+
+impl From<buffer::CreationError> for WriteError {
+    fn from(e: buffer::CreationError) -> WriteError {
+        WriteError::Creation(e)
+    }
+}
+
+/// A vertex buffer written anew every frame, like UI quads, particles or
+/// debug lines.
+///
+/// Successive writes go one after the other into a ring, so that the
+/// data of previous draws is never overwritten while they may still be
+/// in flight. When the ring is full, the buffer gets orphaned: a fresh
+/// one replaces it, while the old one is kept alive by the device until
+/// the commands using it are complete.
+///
+/// Since the buffer may change on any write, it has to be fetched with
+/// `get_buffer` after the write and before binding it.
+#[derive(Clone, Debug)]
+pub struct DynamicBuffer<R: Resources, T> {
+    buffer: handle::Buffer<R, T>,
+    capacity: usize,
+    cursor: usize,
+}
+
+impl<R: Resources, T: Pod> DynamicBuffer<R, T> {
+    /// Create a buffer holding up to `capacity` elements.
+    pub fn new<F: Factory<R> + ?Sized>(factory: &mut F, capacity: usize)
+               -> Result<DynamicBuffer<R, T>, buffer::CreationError> {
+        let buffer = try!(factory.create_buffer(capacity, buffer::Role::Vertex,
+                                                Usage::Dynamic, Bind::empty()));
+        Ok(DynamicBuffer {
+            buffer: buffer,
+            capacity: capacity,
+            cursor: 0,
+        })
+    }
+
+    /// Get the current buffer, as written by the last `write`.
+    pub fn get_buffer(&self) -> &handle::Buffer<R, T> {
+        &self.buffer
+    }
+
+    /// Get the maximum number of elements written at once.
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Copy `data` into the buffer and return the slice drawing it.
+    pub fn write<F, C>(&mut self, factory: &mut F, encoder: &mut Encoder<R, C>, data: &[T])
+                       -> Result<Slice<R>, WriteError> where
+        F: Factory<R> + ?Sized,
+        C: CommandBuffer<R>,
+    {
+        if data.len() > self.capacity {
+            return Err(WriteError::Update(UpdateError::OutOfBounds {
+                target: self.capacity,
+                source: data.len(),
+            }))
+        }
+        if self.cursor + data.len() > self.capacity {
+            self.buffer = try!(factory.create_buffer(self.capacity, buffer::Role::Vertex,
+                                                     Usage::Dynamic, Bind::empty()));
+            self.cursor = 0;
+        }
+        let start = self.cursor;
+        try!(encoder.update_buffer(&self.buffer, data, start));
+        self.cursor += data.len();
+        Ok(Slice {
+            start: start as VertexCount,
+            end: self.cursor as VertexCount,
+            base_vertex: 0,
+            instances: None,
+            buffer: IndexBuffer::Auto,
+        })
+    }
+}
+
+/// A constant buffer holding the constant blocks of many draws, written
+/// anew every frame, like per-object transforms.
+///
+/// Each block is placed after the previous one, at the next multiple of
+/// `constant_buffer_offset_alignment`, and bound with a
+/// `ConstantBufferOffset` component. Instead of one buffer per object or
+/// one update and rebind of a shared buffer per draw, a frame ends up using
+/// a single buffer bound at different offsets. `reset` should be called at
+/// the start of each frame to reuse the space.
+///
+/// On backends which can't bind a buffer at an offset, a single block fits
+/// until the next `reset`.
+#[derive(Clone, Debug)]
+pub struct ConstantAllocator<R: Resources> {
+    buffer: handle::Buffer<R, u8>,
+    alignment: usize,
+    cursor: usize,
+}
+
+impl<R: Resources> ConstantAllocator<R> {
+    /// Create an allocator out of a buffer of `size` bytes.
+    pub fn new<F: Factory<R> + ?Sized>(factory: &mut F, size: usize)
+               -> Result<ConstantAllocator<R>, buffer::CreationError> {
+        let alignment = factory.get_capabilities().constant_buffer_offset_alignment;
+        let buffer = try!(factory.create_buffer(size, buffer::Role::Constant,
+                                                Usage::Dynamic, Bind::empty()));
+        Ok(ConstantAllocator {
+            buffer: buffer,
+            alignment: alignment,
+            cursor: 0,
+        })
+    }
+
+    /// Get the size of the buffer, in bytes.
+    pub fn get_size(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Get the number of bytes allocated since the last `reset`.
+    pub fn get_used(&self) -> usize {
+        self.cursor
+    }
+
+    /// Free all the blocks, to be allocated again.
+    pub fn reset(&mut self) {
+        self.cursor = 0;
+    }
+
+    /// Copy a constant block into the buffer and return the data of the
+    /// `ConstantBufferOffset` component binding it.
+    pub fn allocate<T, C>(&mut self, encoder: &mut Encoder<R, C>, value: &T)
+                          -> Result<(handle::Buffer<R, T>, usize), UpdateError<usize>> where
+        T: Pod,
+        C: CommandBuffer<R>,
+    {
+        let start = match self.alignment {
+            0 if self.cursor == 0 => 0,
+            0 => self.get_size(),
+            a => (self.cursor + a - 1) / a * a,
+        };
+        let end = start + mem::size_of::<T>();
+        if end > self.get_size() {
+            return Err(UpdateError::OutOfBounds {
+                target: self.get_size(),
+                source: end,
+            })
+        }
+        let data = unsafe {
+            slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>())
+        };
+        try!(encoder.update_buffer(&self.buffer, data, start));
+        self.cursor = end;
+        Ok((Typed::new(self.buffer.raw().clone()), start))
+    }
+}
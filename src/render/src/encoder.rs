@@ -19,13 +19,15 @@
 use draw_state::target::{Depth, Stencil};
 use std::error::Error;
 use std::any::Any;
-use std::{fmt, mem};
+use std::{cmp, fmt, mem};
 
-use core::{Device, SubmissionResult, IndexType, Resources, VertexCount};
-use core::{command, format, handle, texture};
+use core::{Device, SubmissionResult, IndexType, QuerySlot, Resources, VertexCount};
+use core::{capture, command, format, handle, texture};
 use core::memory::{self, cast_slice, Typed, Pod, Usage};
 use slice;
 use pso;
+use shade;
+use validate;
 
 /// An error occuring in memory copies.
 #[allow(missing_docs)]
@@ -186,6 +188,30 @@ impl<R: Resources, C: command::Buffer<R>> Encoder<R, C> {
         self.reset();
     }
 
+    /// Submits the commands of several encoders to the GPU, one after another,
+    /// and resets them.
+    ///
+    /// Encoders are `Send`, so they can be recorded concurrently on worker
+    /// threads and handed back to the thread owning the `Device`. The
+    /// submission order is the order of the iterator, which lets the
+    /// frame be split into independent chunks without reordering it.
+    pub fn flush_all<'a, D, I>(encoders: I, device: &mut D)
+        where D: Device<Resources=R, CommandBuffer=C>,
+              I: IntoIterator<Item=&'a mut Encoder<R, C>>,
+              R: 'a, C: 'a,
+    {
+        for encoder in encoders {
+            encoder.flush(device);
+        }
+    }
+
+    /// Replays a captured command sequence into this encoder, with the
+    /// resource ids mapped through the given table. The resources of the
+    /// table need to be kept alive until the encoder is flushed.
+    pub fn replay(&mut self, capture: &capture::Capture, table: &capture::ResourceTable<R>) {
+        capture.replay(table, &mut self.command_buffer);
+    }
+
     /// Like `flush` but keeps the encoded commands.
     pub fn flush_no_reset<D>(&mut self, device: &mut D) -> SubmissionResult<()>
         where D: Device<Resources=R, CommandBuffer=C>
@@ -368,6 +394,37 @@ impl<R: Resources, C: command::Buffer<R>> Encoder<R, C> {
         }
     }
 
+    /// Update a single member of a constant buffer, found by
+    /// `shade::find_member`, instead of the whole structure. The buffer has
+    /// to be dynamic, and the value can't overflow the member.
+    pub fn update_member<T, V: Pod>(&mut self, buf: &handle::Buffer<R, T>,
+                                    member: &shade::MemberLocation, value: &V)
+                                    -> Result<(), UpdateError<usize>>
+    {
+        try!(check_update_usage(buf.raw().get_info().usage));
+
+        let bound = member.offset + mem::size_of::<V>();
+        let member_bound = member.offset + member.size;
+        if bound > member_bound {
+            return Err(UpdateError::OutOfBounds {
+                target: bound,
+                source: member_bound,
+            })
+        }
+        if bound > buf.get_info().size {
+            return Err(UpdateError::OutOfBounds {
+                target: bound,
+                source: buf.get_info().size,
+            })
+        }
+        let data = unsafe {
+            ::std::slice::from_raw_parts(value as *const V as *const u8, mem::size_of::<V>())
+        };
+        self.command_buffer.update_buffer(
+            self.handles.ref_buffer(buf.raw()).clone(), data, member.offset);
+        Ok(())
+    }
+
     /// Update a buffer with a single structure.
     pub fn update_constant_buffer<T: Copy>(&mut self, buf: &handle::Buffer<R, T>, data: &T) {
         use std::slice;
@@ -381,6 +438,20 @@ impl<R: Resources, C: command::Buffer<R>> Encoder<R, C> {
             self.handles.ref_buffer(buf.raw()).clone(), slice, 0);
     }
 
+    /// Reset all the atomic counters of a buffer to zero.
+    pub fn reset_counters(&mut self, buf: &handle::Buffer<R, u32>)
+                          -> Result<(), UpdateError<usize>>
+    {
+        const ZEROS: [u32; 64] = [0; 64];
+        let mut offset = 0;
+        while offset < buf.len() {
+            let count = cmp::min(buf.len() - offset, ZEROS.len());
+            try!(self.update_buffer(buf, &ZEROS[..count], offset));
+            offset += count;
+        }
+        Ok(())
+    }
+
     /// Update the contents of a texture.
     pub fn update_texture<S, T>(&mut self, tex: &handle::Texture<R, T::Surface>,
                           face: Option<texture::CubeFace>,
@@ -425,6 +496,8 @@ impl<R: Resources, C: command::Buffer<R>> Encoder<R, C> {
     fn draw_indexed<T>(&mut self, buf: &handle::Buffer<R, T>, ty: IndexType,
                     slice: &slice::Slice<R>, base: VertexCount,
                     instances: Option<command::InstanceParams>) {
+        debug_assert!(slice.end as usize <= buf.len(),
+                      "Slice end {} is out of the index buffer of {} elements", slice.end, buf.len());
         self.access_info.buffer_read(buf.raw());
         self.command_buffer.bind_index(self.handles.ref_buffer(buf.raw()).clone(), ty);
         self.command_buffer.call_draw_indexed(slice.start, slice.end - slice.start, base, instances);
@@ -434,6 +507,8 @@ impl<R: Resources, C: command::Buffer<R>> Encoder<R, C> {
         match slice.buffer {
             slice::IndexBuffer::Auto => self.command_buffer.call_draw(
                 slice.start + slice.base_vertex, slice.end - slice.start, instances),
+            slice::IndexBuffer::Index8(ref buf) =>
+                self.draw_indexed(buf, IndexType::U8, slice, slice.base_vertex, instances),
             slice::IndexBuffer::Index16(ref buf) =>
                 self.draw_indexed(buf, IndexType::U16, slice, slice.base_vertex, instances),
             slice::IndexBuffer::Index32(ref buf) =>
@@ -441,6 +516,48 @@ impl<R: Resources, C: command::Buffer<R>> Encoder<R, C> {
         }
     }
 
+    /// Make the writes done by the shaders so far, like the ones of a
+    /// compute pass into an unordered view, visible to the following
+    /// accesses of the given kinds.
+    pub fn memory_barrier(&mut self, barrier: memory::Barrier) {
+        self.command_buffer.memory_barrier(barrier);
+    }
+
+    /// Start counting the samples passing the depth test into the
+    /// occlusion query of `slot`, to be read back with
+    /// `Device::get_occlusion_result`.
+    pub fn begin_occlusion_query(&mut self, slot: QuerySlot) {
+        self.command_buffer.begin_occlusion_query(slot);
+    }
+
+    /// Stop counting the samples of the occlusion query of `slot`.
+    pub fn end_occlusion_query(&mut self, slot: QuerySlot) {
+        self.command_buffer.end_occlusion_query(slot);
+    }
+
+    /// Start measuring the GPU time of the following commands into the
+    /// timer query of `slot`, to be read back with
+    /// `Device::get_timer_result`. Timer queries can't be nested.
+    pub fn begin_timer_query(&mut self, slot: QuerySlot) {
+        self.command_buffer.begin_timer_query(slot);
+    }
+
+    /// Stop measuring the GPU time of the timer query of `slot`.
+    pub fn end_timer_query(&mut self, slot: QuerySlot) {
+        self.command_buffer.end_timer_query(slot);
+    }
+
+    /// Open a named group of commands. Debugging tools show the commands
+    /// recorded until the matching `pop_debug_group` under this name.
+    pub fn push_debug_group(&mut self, name: &str) {
+        self.command_buffer.push_debug_group(name);
+    }
+
+    /// Close the last group opened by `push_debug_group`.
+    pub fn pop_debug_group(&mut self) {
+        self.command_buffer.pop_debug_group();
+    }
+
     /// Clears the supplied `RenderTargetView` to the supplied `ClearColor`.
     pub fn clear<T: format::RenderFormat>(&mut self,
                  view: &handle::RenderTargetView<R, T>, value: T::View)
@@ -448,6 +565,17 @@ impl<R: Resources, C: command::Buffer<R>> Encoder<R, C> {
         let target = self.handles.ref_rtv(view.raw()).clone();
         self.command_buffer.clear_color(target, value.into())
     }
+    /// Clears a raw `RenderTargetView` to the supplied `ClearColor`.
+    pub fn clear_raw(&mut self, view: &handle::RawRenderTargetView<R>, value: command::ClearColor) {
+        let target = self.handles.ref_rtv(view).clone();
+        self.command_buffer.clear_color(target, value)
+    }
+    /// Clear the depth and stencil of a raw view, if specified.
+    pub fn clear_depth_stencil_raw(&mut self, view: &handle::RawDepthStencilView<R>,
+                                   depth: Option<Depth>, stencil: Option<Stencil>) {
+        let target = self.handles.ref_dsv(view).clone();
+        self.command_buffer.clear_depth_stencil(target, depth, stencil)
+    }
     /// Clear a depth view with a specified value.
     pub fn clear_depth<T: format::DepthFormat>(&mut self,
                        view: &handle::DepthStencilView<R, T>, depth: Depth) {
@@ -462,14 +590,57 @@ impl<R: Resources, C: command::Buffer<R>> Encoder<R, C> {
         self.command_buffer.clear_depth_stencil(target, None, Some(stencil))
     }
 
+    /// Enable or disable the validation of the draw calls, which reports
+    /// the errors of `try_draw`, and makes `draw` panic on them. See the
+    /// `validate` module for the checks done.
+    pub fn set_validation(&mut self, enabled: bool) {
+        self.raw_pso_data.bindings = if enabled { Some(validate::Bindings::new()) } else { None };
+    }
+
+    /// Check whether the draw calls are validated.
+    pub fn is_validating(&self) -> bool {
+        self.raw_pso_data.bindings.is_some()
+    }
+
     /// Draws a `slice::Slice` using a pipeline state object, and its matching `Data` structure.
     pub fn draw<D: pso::PipelineData<R>>(&mut self, slice: &slice::Slice<R>,
                 pipeline: &pso::PipelineState<R, D::Meta>, user_data: &D)
     {
-        let (pso, _) = self.handles.ref_pso(pipeline.get_handle());
-        //TODO: make `raw_data` a member to this struct, to re-use the heap allocation
+        if let Err(e) = self.try_draw(slice, pipeline, user_data) {
+            panic!("Invalid draw call: {}", e)
+        }
+    }
+
+    /// Like `draw`, but returns the error found by the validation, if it's
+    /// enabled, instead of panicking. Nothing is drawn in that case.
+    pub fn try_draw<D: pso::PipelineData<R>>(&mut self, slice: &slice::Slice<R>,
+                    pipeline: &pso::PipelineState<R, D::Meta>, user_data: &D)
+                    -> Result<(), validate::DrawError>
+    {
         self.raw_pso_data.clear();
-        user_data.bake_to(&mut self.raw_pso_data, pipeline.get_meta(), &mut self.handles, &mut self.access_info);
+        if self.raw_pso_data.bindings.is_some() {
+            // bake into scratch copies first, so that an invalid draw
+            // doesn't keep its resources referenced by the encoder
+            let mut handles = handle::Manager::new();
+            let mut access_info = command::AccessInfo::new();
+            user_data.bake_to(&mut self.raw_pso_data, pipeline.get_meta(),
+                              &mut handles, &mut access_info);
+            if let Some(ref bindings) = self.raw_pso_data.bindings {
+                let program = pipeline.get_handle().get_program();
+                try!(validate::check_draw(slice, program.get_info(), bindings));
+            }
+            self.handles.extend(&handles);
+            for buffer in access_info.mapped_reads() {
+                self.access_info.buffer_read(buffer);
+            }
+            for buffer in access_info.mapped_writes() {
+                self.access_info.buffer_write(buffer);
+            }
+        } else {
+            user_data.bake_to(&mut self.raw_pso_data, pipeline.get_meta(),
+                              &mut self.handles, &mut self.access_info);
+        }
+        let (pso, _) = self.handles.ref_pso(pipeline.get_handle());
         self.command_buffer.bind_pixel_targets(self.raw_pso_data.pixel_targets.clone());
         self.command_buffer.bind_pipeline_state(pso.clone());
         self.command_buffer.bind_vertex_buffers(self.raw_pso_data.vertex_buffers.clone());
@@ -483,6 +654,8 @@ impl<R: Resources, C: command::Buffer<R>> Encoder<R, C> {
         //Note: it's important to bind RTV, DSV, and UAV before SRV
         self.command_buffer.bind_resource_views(&self.raw_pso_data.resource_views);
         self.command_buffer.bind_samplers(&self.raw_pso_data.samplers);
+        self.command_buffer.bind_atomic_counter_buffers(&self.raw_pso_data.atomic_counter_buffers);
         self.draw_slice(slice, slice.instances);
+        Ok(())
     }
 }
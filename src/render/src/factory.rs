@@ -25,7 +25,7 @@ use core::{Primitive, Resources, ShaderSet};
 use core::factory::Factory;
 use core::pso::{CreationError, Descriptor};
 use core::memory::{self, Bind, Pod};
-use slice::{Slice, IndexBuffer, IntoIndexBuffer};
+use slice::{Slice, IndexBuffer, IndexBufferError, IntoIndexBuffer};
 use pso;
 use shade::ProgramError;
 
@@ -114,35 +114,56 @@ pub trait FactoryExt<R: Resources>: Factory<R> {
     /// Creates an immutable index buffer from the supplied vertices.
     ///
     /// The paramater `indices` is typically a &[u16] or &[u32] slice.
+    /// Panics on the errors `try_create_index_buffer` returns.
     fn create_index_buffer<T>(&mut self, indices: T)
                               -> IndexBuffer<R>
         where T: IntoIndexBuffer<R>
+    {
+        self.try_create_index_buffer(indices).unwrap()
+    }
+
+    /// Creates an immutable index buffer from the supplied vertices, or
+    /// returns the errors `IntoIndexBuffer::into_index_buffer` reports,
+    /// like a buffer of 8-bit indices the device doesn't support.
+    fn try_create_index_buffer<T>(&mut self, indices: T)
+                                  -> Result<IndexBuffer<R>, IndexBufferError>
+        where T: IntoIndexBuffer<R>
     {
         indices.into_index_buffer(self)
     }
 
     /// Creates an immutable vertex buffer from the supplied vertices,
     /// together with a `Slice` from the supplied indices.
+    /// Panics on the errors `try_create_vertex_buffer_with_slice` returns.
     fn create_vertex_buffer_with_slice<B, V>(&mut self, vertices: &[V], indices: B)
                                              -> (handle::Buffer<R, V>, Slice<R>)
         where V: Pod + pso::buffer::Structure<format::Format>,
               B: IntoIndexBuffer<R>
     {
-        let vertex_buffer = self.create_vertex_buffer(vertices);
-        let index_buffer = self.create_index_buffer(indices);
-        let buffer_length = match index_buffer {
-            IndexBuffer::Auto => vertex_buffer.len(),
-            IndexBuffer::Index16(ref ib) => ib.len(),
-            IndexBuffer::Index32(ref ib) => ib.len(),
-        };
-        
-        (vertex_buffer, Slice {
+        self.try_create_vertex_buffer_with_slice(vertices, indices).unwrap()
+    }
+
+    /// Creates an immutable vertex buffer from the supplied vertices,
+    /// together with a `Slice` from the supplied indices, or returns the
+    /// error creating either of them.
+    fn try_create_vertex_buffer_with_slice<B, V>(&mut self, vertices: &[V], indices: B)
+                                                 -> Result<(handle::Buffer<R, V>, Slice<R>),
+                                                           IndexBufferError>
+        where V: Pod + pso::buffer::Structure<format::Format>,
+              B: IntoIndexBuffer<R>
+    {
+        let vertex_buffer = try!(self.create_buffer_immutable(vertices, buffer::Role::Vertex,
+                                                              Bind::empty()));
+        let index_buffer = try!(self.try_create_index_buffer(indices));
+        let buffer_length = index_buffer.get_index_count().unwrap_or(vertex_buffer.len());
+
+        Ok((vertex_buffer, Slice {
             start: 0,
             end: buffer_length as u32,
             base_vertex: 0,
             instances: None,
             buffer: index_buffer
-        })
+        }))
     }
 
     /// Creates a constant buffer for `num` identical elements of type `T`.
@@ -155,6 +176,17 @@ pub trait FactoryExt<R: Resources>: Factory<R> {
                            Bind::empty()).unwrap()
     }
 
+    /// Creates a buffer for `num` atomic counters, to be bound with an
+    /// `AtomicCounterBuffer` component.
+    fn create_atomic_counter_buffer(&mut self, num: usize)
+                                    -> Result<handle::Buffer<R, u32>, buffer::CreationError>
+    {
+        self.create_buffer(num,
+                           buffer::Role::Constant,
+                           memory::Usage::Dynamic,
+                           Bind::empty())
+    }
+
     /// Creates an upload buffer for `num` elements of type `T`.
     fn create_upload_buffer<T>(&mut self, num: usize)
                                -> Result<handle::Buffer<R, T>, buffer::CreationError>
@@ -0,0 +1,699 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Frame graphs.
+//!
+//! A frame graph is a list of passes, each declaring the targets it reads
+//! and writes. Compiling the graph orders the passes so that every target
+//! is written before being read, drops the passes whose results are never
+//! used, and creates the transient targets. Transients of the same size
+//! and format share their texture when their lifetimes don't overlap, and
+//! are cleared by the graph before their first write in a frame.
+//!
+//! The targets written by a pass have to be bound together, so they are
+//! checked to have the same size, with at most one depth-stencil target.
+//!
+//! Targets are typed by their format, so that their views can only be
+//! used where the format is expected, like with the other handles:
+//!
+//! ```ignore
+//! let mut graph = FrameGraph::new();
+//! let normals = graph.create_color::<Rgba16F>(w, h);
+//! let depth = graph.create_depth::<DepthStencil>(w, h);
+//! let screen = graph.import_color(&main_color);
+//! graph.add_pass("light", &[normals.id(), depth.id()], &[screen.id()], move |encoder, targets| {
+//!     light_data.normals.0 = targets.get_resource_view(normals).unwrap();
+//!     ...
+//! });
+//! graph.add_pass("geometry", &[], &[normals.id(), depth.id()], move |encoder, targets| { ... });
+//! try!(graph.compile(&mut factory));
+//! // every frame
+//! graph.execute(&mut encoder);
+//! ```
+//!
+//! Targets following the size of the window, like a G-buffer or the
+//! stages of a post-processing chain, are declared with a divisor of it.
+//! On resize, the imported main targets are updated, then `resize`
+//! recreates the window-sized targets and notifies the listeners:
+//!
+//! ```ignore
+//! let bloom = graph.create_window_color::<Rgba16F>(2); // half size
+//! graph.on_resize(move |w, h| camera.borrow_mut().set_aspect(w as f32 / h as f32));
+//! // on a window event
+//! gfx_window_glutin::update_views(&window, &mut main_color, &mut main_depth);
+//! graph.update_color(screen, &main_color);
+//! try!(graph.resize(&mut factory, width, height));
+//! ```
+//!
+//! Multisampled targets are not supported yet, so the graph never has to
+//! resolve them.
+
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use core::{handle, texture, Resources, MAX_COLOR_TARGETS};
+use core::command::{Buffer as CommandBuffer, ClearColor};
+use core::factory::{CombinedError, Factory};
+use core::format::{DepthFormat, Format, RenderFormat, SurfaceType, Swizzle, TextureFormat};
+use core::memory::{Typed, Usage, DEPTH_STENCIL, RENDER_TARGET, SHADER_RESOURCE};
+use encoder::Encoder;
+
+/// Identifier of a target in a graph.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TargetId(usize);
+
+/// Identifier of a target of a known format `T`.
+#[derive(Debug, Eq, Hash, PartialEq)]
+pub struct TypedTarget<T> {
+    id: TargetId,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Clone for TypedTarget<T> {
+    fn clone(&self) -> TypedTarget<T> {
+        *self
+    }
+}
+
+impl<T> Copy for TypedTarget<T> {}
+
+impl<T> TypedTarget<T> {
+    fn new(id: TargetId) -> TypedTarget<T> {
+        TypedTarget {
+            id: id,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Get the untyped identifier, to declare the accesses of a pass.
+    pub fn id(&self) -> TargetId {
+        self.id
+    }
+}
+
+/// Description of a transient target.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TargetDesc {
+    /// Width in pixels
+    pub width: texture::Size,
+    /// Height in pixels
+    pub height: texture::Size,
+    /// Color or depth-stencil format
+    pub format: Format,
+}
+
+impl TargetDesc {
+    fn from_view(dim: texture::Dimensions, format: Format) -> TargetDesc {
+        TargetDesc {
+            width: dim.0,
+            height: dim.1,
+            format: format,
+        }
+    }
+
+    fn is_depth(&self) -> bool {
+        match self.format.0 {
+            SurfaceType::D16 | SurfaceType::D24 |
+            SurfaceType::D24_S8 | SurfaceType::D32 => true,
+            _ => false,
+        }
+    }
+}
+
+/// An error in compiling a graph.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphError {
+    /// The passes, by name, depend on each other.
+    Cycle(Vec<String>),
+    /// A pass reads a target that no pass writes.
+    Unwritten {
+        /// Name of the reading pass
+        pass: String,
+        /// Target read
+        target: TargetId,
+    },
+    /// A pass writes targets of different sizes.
+    SizeMismatch {
+        /// Name of the writing pass
+        pass: String,
+        /// Target of a different size than the first one written
+        target: TargetId,
+        /// Width and height of the first target
+        expected: (texture::Size, texture::Size),
+        /// Width and height of `target`
+        found: (texture::Size, texture::Size),
+    },
+    /// A pass writes more than one depth-stencil target.
+    MultipleDepth(String),
+    /// A pass writes more color targets than can be bound at once.
+    TooManyColors(String, usize),
+    /// A transient target could not be created.
+    Target(CombinedError),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GraphError::Cycle(ref passes) => write!(f, "{}: {:?}", self.description(), passes),
+            GraphError::Unwritten { ref pass, target } =>
+                write!(f, "{}: {:?} read by {}", self.description(), target, pass),
+            GraphError::SizeMismatch { ref pass, target, expected, found } =>
+                write!(f, "{}: {:?} of {:?} written by {}, expected {:?}",
+                       self.description(), target, found, pass, expected),
+            GraphError::MultipleDepth(ref pass) => write!(f, "{}: {}", self.description(), pass),
+            GraphError::TooManyColors(ref pass, count) =>
+                write!(f, "{}: {} written by {}", self.description(), count, pass),
+            GraphError::Target(ref e) => write!(f, "{}: {}", self.description(), e),
+        }
+    }
+}
+
+impl Error for GraphError {
+    fn description(&self) -> &str {
+        match *self {
+            GraphError::Cycle(_) => "The passes depend on each other",
+            GraphError::Unwritten { .. } => "A target is read but never written",
+            GraphError::SizeMismatch { .. } => "The targets of a pass differ in size",
+            GraphError::MultipleDepth(_) => "A pass writes several depth-stencil targets",
+            GraphError::TooManyColors(..) => "A pass writes too many color targets",
+            GraphError::Target(_) => "Unable to create a transient target",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            GraphError::Target(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<CombinedError> for GraphError {
+    fn from(e: CombinedError) -> GraphError {
+        GraphError::Target(e)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Target<R: Resources> {
+    Transient(TargetDesc),
+    Color(handle::RawRenderTargetView<R>, TargetDesc),
+    Depth(handle::RawDepthStencilView<R>, TargetDesc),
+}
+
+impl<R: Resources> Target<R> {
+    fn get_desc(&self) -> TargetDesc {
+        match *self {
+            Target::Transient(desc) |
+            Target::Color(_, desc) |
+            Target::Depth(_, desc) => desc,
+        }
+    }
+}
+
+/// Views of a physical target.
+#[derive(Clone, Debug)]
+struct Views<R: Resources> {
+    color: Option<handle::RawRenderTargetView<R>>,
+    depth: Option<handle::RawDepthStencilView<R>>,
+    resource: Option<handle::RawShaderResourceView<R>>,
+}
+
+/// The targets of a compiled graph, as seen by the passes.
+#[derive(Clone, Debug)]
+pub struct Targets<R: Resources> {
+    /// Physical views of each target.
+    views: Vec<Views<R>>,
+}
+
+impl<R: Resources> Targets<R> {
+    /// Get the color view of a target.
+    pub fn get_render_target(&self, id: TargetId) -> Option<&handle::RawRenderTargetView<R>> {
+        self.views.get(id.0).and_then(|v| v.color.as_ref())
+    }
+
+    /// Get the depth-stencil view of a target.
+    pub fn get_depth_stencil(&self, id: TargetId) -> Option<&handle::RawDepthStencilView<R>> {
+        self.views.get(id.0).and_then(|v| v.depth.as_ref())
+    }
+
+    /// Get the shader resource view of a transient target.
+    pub fn get_shader_resource(&self, id: TargetId) -> Option<&handle::RawShaderResourceView<R>> {
+        self.views.get(id.0).and_then(|v| v.resource.as_ref())
+    }
+
+    /// Get the typed color view of a target.
+    pub fn get_render_target_view<T: RenderFormat>(&self, target: TypedTarget<T>)
+                                                   -> Option<handle::RenderTargetView<R, T>> {
+        self.get_render_target(target.id).map(|view| Typed::new(view.clone()))
+    }
+
+    /// Get the typed depth-stencil view of a target.
+    pub fn get_depth_stencil_view<T: DepthFormat>(&self, target: TypedTarget<T>)
+                                                  -> Option<handle::DepthStencilView<R, T>> {
+        self.get_depth_stencil(target.id).map(|view| Typed::new(view.clone()))
+    }
+
+    /// Get the typed shader resource view of a transient target.
+    pub fn get_resource_view<T: TextureFormat>(&self, target: TypedTarget<T>)
+                                               -> Option<handle::ShaderResourceView<R, T::View>> {
+        self.get_shader_resource(target.id).map(|view| Typed::new(view.clone()))
+    }
+}
+
+struct Pass<R: Resources, C: CommandBuffer<R>> {
+    name: String,
+    reads: Vec<TargetId>,
+    writes: Vec<TargetId>,
+    execute: Box<FnMut(&mut Encoder<R, C>, &Targets<R>)>,
+}
+
+/// A list of passes with their targets.
+pub struct FrameGraph<R: Resources, C: CommandBuffer<R>> {
+    targets: Vec<Target<R>>,
+    passes: Vec<Pass<R, C>>,
+    /// Indices of the passes to execute, with the transients to clear first.
+    order: Vec<(usize, Vec<TargetId>)>,
+    compiled: Targets<R>,
+    window_size: (texture::Size, texture::Size),
+    /// Transients following the window size, with their divisor.
+    window_targets: Vec<(TargetId, texture::Size)>,
+    resize_listeners: Vec<Box<FnMut(texture::Size, texture::Size)>>,
+}
+
+impl<R: Resources, C: CommandBuffer<R>> FrameGraph<R, C> {
+    /// Create an empty graph.
+    pub fn new() -> FrameGraph<R, C> {
+        FrameGraph {
+            targets: Vec::new(),
+            passes: Vec::new(),
+            order: Vec::new(),
+            compiled: Targets { views: Vec::new() },
+            window_size: (1, 1),
+            window_targets: Vec::new(),
+            resize_listeners: Vec::new(),
+        }
+    }
+
+    /// Declare a transient target, created by the graph.
+    pub fn create_target(&mut self, desc: TargetDesc) -> TargetId {
+        self.targets.push(Target::Transient(desc));
+        TargetId(self.targets.len() - 1)
+    }
+
+    /// Declare a transient color target of format `T`.
+    pub fn create_color<T>(&mut self, width: texture::Size, height: texture::Size)
+                           -> TypedTarget<T> where T: RenderFormat + TextureFormat {
+        TypedTarget::new(self.create_target(TargetDesc {
+            width: width,
+            height: height,
+            format: T::get_format(),
+        }))
+    }
+
+    /// Declare a transient depth-stencil target of format `T`.
+    pub fn create_depth<T>(&mut self, width: texture::Size, height: texture::Size)
+                           -> TypedTarget<T> where T: DepthFormat + TextureFormat {
+        TypedTarget::new(self.create_target(TargetDesc {
+            width: width,
+            height: height,
+            format: T::get_format(),
+        }))
+    }
+
+    /// Declare a transient color target of format `T`, sized after the
+    /// window divided by `divisor`.
+    pub fn create_window_color<T>(&mut self, divisor: texture::Size) -> TypedTarget<T> where
+        T: RenderFormat + TextureFormat
+    {
+        let target = self.create_window_target(divisor, T::get_format());
+        TypedTarget::new(target)
+    }
+
+    /// Declare a transient depth-stencil target of format `T`, sized after
+    /// the window divided by `divisor`.
+    pub fn create_window_depth<T>(&mut self, divisor: texture::Size) -> TypedTarget<T> where
+        T: DepthFormat + TextureFormat
+    {
+        let target = self.create_window_target(divisor, T::get_format());
+        TypedTarget::new(target)
+    }
+
+    fn create_window_target(&mut self, divisor: texture::Size, format: Format) -> TargetId {
+        let (width, height) = get_divided_size(self.window_size, divisor);
+        let id = self.create_target(TargetDesc {
+            width: width,
+            height: height,
+            format: format,
+        });
+        self.window_targets.push((id, divisor));
+        id
+    }
+
+    /// Declare an external color target, like the main window. The passes
+    /// writing to it are never dropped, and it's never cleared by the graph.
+    pub fn import_color<T: RenderFormat>(&mut self, view: &handle::RenderTargetView<R, T>)
+                                         -> TypedTarget<T> {
+        let desc = TargetDesc::from_view(view.get_dimensions(), T::get_format());
+        self.targets.push(Target::Color(view.raw().clone(), desc));
+        TypedTarget::new(TargetId(self.targets.len() - 1))
+    }
+
+    /// Declare an external depth-stencil target.
+    pub fn import_depth<T: DepthFormat>(&mut self, view: &handle::DepthStencilView<R, T>)
+                                        -> TypedTarget<T> {
+        let desc = TargetDesc::from_view(view.get_dimensions(), T::get_format());
+        self.targets.push(Target::Depth(view.raw().clone(), desc));
+        TypedTarget::new(TargetId(self.targets.len() - 1))
+    }
+
+    /// Replace the view of an imported color target, like the main one
+    /// after a resize. Does nothing for the other targets.
+    pub fn update_color<T: RenderFormat>(&mut self, target: TypedTarget<T>,
+                                         view: &handle::RenderTargetView<R, T>) {
+        let target = self.targets.get_mut(target.id.0);
+        if let Some(&mut Target::Color(ref mut raw, ref mut desc)) = target {
+            *raw = view.raw().clone();
+            *desc = TargetDesc::from_view(view.get_dimensions(), T::get_format());
+        }
+    }
+
+    /// Replace the view of an imported depth-stencil target. Does nothing
+    /// for the other targets.
+    pub fn update_depth<T: DepthFormat>(&mut self, target: TypedTarget<T>,
+                                        view: &handle::DepthStencilView<R, T>) {
+        let target = self.targets.get_mut(target.id.0);
+        if let Some(&mut Target::Depth(ref mut raw, ref mut desc)) = target {
+            *raw = view.raw().clone();
+            *desc = TargetDesc::from_view(view.get_dimensions(), T::get_format());
+        }
+    }
+
+    /// Get the window size the window-sized targets follow.
+    pub fn get_window_size(&self) -> (texture::Size, texture::Size) {
+        self.window_size
+    }
+
+    /// Set the window size, updating the descriptions of the window-sized
+    /// targets. They are only recreated by the next compilation, `resize`
+    /// does both.
+    pub fn set_window_size(&mut self, width: texture::Size, height: texture::Size) {
+        self.window_size = (width, height);
+        for &(id, divisor) in self.window_targets.iter() {
+            if let Target::Transient(ref mut desc) = self.targets[id.0] {
+                let (w, h) = get_divided_size((width, height), divisor);
+                desc.width = w;
+                desc.height = h;
+            }
+        }
+    }
+
+    /// Add a function called with the new window size after each `resize`,
+    /// for the state depending on it, like the aspect ratio of a camera.
+    pub fn on_resize<F>(&mut self, listener: F) where
+        F: FnMut(texture::Size, texture::Size) + 'static
+    {
+        self.resize_listeners.push(Box::new(listener));
+    }
+
+    /// Follow a resize of the window: recreate the window-sized targets,
+    /// and notify the listeners. The imported targets have to be updated
+    /// first. The previous targets are released once the passes stop
+    /// using them.
+    pub fn resize<F>(&mut self, factory: &mut F, width: texture::Size, height: texture::Size)
+                     -> Result<(), GraphError> where
+        F: Factory<R> + ?Sized
+    {
+        self.set_window_size(width, height);
+        try!(self.compile(factory));
+        for listener in self.resize_listeners.iter_mut() {
+            listener(width, height);
+        }
+        Ok(())
+    }
+
+    /// Add a pass reading and writing the given targets, recorded by
+    /// `execute` every frame.
+    pub fn add_pass<F>(&mut self, name: &str, reads: &[TargetId], writes: &[TargetId], execute: F)
+                       where F: FnMut(&mut Encoder<R, C>, &Targets<R>) + 'static {
+        self.passes.push(Pass {
+            name: name.to_string(),
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+            execute: Box::new(execute),
+        });
+    }
+
+    /// Get the names of the passes to execute, in order.
+    pub fn get_pass_order(&self) -> Result<Vec<&str>, GraphError> {
+        self.schedule().map(|order| {
+            order.into_iter().map(|i| self.passes[i].name.as_str()).collect()
+        })
+    }
+
+    /// Get the targets, as created by the last compilation.
+    pub fn get_targets(&self) -> &Targets<R> {
+        &self.compiled
+    }
+
+    /// Get the description of a target, including the imported ones.
+    pub fn get_target_desc(&self, id: TargetId) -> Option<TargetDesc> {
+        self.targets.get(id.0).map(|t| t.get_desc())
+    }
+
+    /// Check that the targets written by a pass can be bound together.
+    fn validate(&self, pass: &Pass<R, C>) -> Result<(), GraphError> {
+        let mut size = None;
+        let (mut colors, mut depths) = (0, 0);
+        for &id in pass.writes.iter() {
+            let desc = self.targets[id.0].get_desc();
+            if desc.is_depth() { depths += 1 } else { colors += 1 }
+            let found = (desc.width, desc.height);
+            match size {
+                Some(expected) if expected != found => return Err(GraphError::SizeMismatch {
+                    pass: pass.name.clone(),
+                    target: id,
+                    expected: expected,
+                    found: found,
+                }),
+                _ => size = Some(found),
+            }
+        }
+        if depths > 1 {
+            return Err(GraphError::MultipleDepth(pass.name.clone()))
+        }
+        if colors > MAX_COLOR_TARGETS {
+            return Err(GraphError::TooManyColors(pass.name.clone(), colors))
+        }
+        Ok(())
+    }
+
+    /// Find the indices of the passes to execute, in order.
+    fn schedule(&self) -> Result<Vec<usize>, GraphError> {
+        let num = self.passes.len();
+        let writers = |id: TargetId| (0 .. num).filter(move |&j| self.passes[j].writes.contains(&id));
+        // pass `i` has to be executed after each pass in `deps[i]`
+        let mut deps = vec![Vec::new(); num];
+        for (i, pass) in self.passes.iter().enumerate() {
+            for &id in pass.reads.iter() {
+                let mut any = false;
+                for j in writers(id).filter(|&j| j != i) {
+                    deps[i].push(j);
+                    any = true;
+                }
+                let imported = match self.targets[id.0] {
+                    Target::Transient(_) => false,
+                    _ => true,
+                };
+                if !any && !imported && !pass.writes.contains(&id) {
+                    return Err(GraphError::Unwritten {
+                        pass: pass.name.clone(),
+                        target: id,
+                    })
+                }
+            }
+            for &id in pass.writes.iter() {
+                deps[i].extend(writers(id).filter(|&j| j < i));
+            }
+            try!(self.validate(pass));
+        }
+        // keep the passes writing external targets, and their dependencies
+        let mut used = vec![false; num];
+        let mut stack: Vec<usize> = (0 .. num).filter(|&i| {
+            let pass = &self.passes[i];
+            pass.writes.is_empty() || pass.writes.iter().any(|id| match self.targets[id.0] {
+                Target::Transient(_) => false,
+                _ => true,
+            })
+        }).collect();
+        while let Some(i) = stack.pop() {
+            if !used[i] {
+                used[i] = true;
+                stack.extend_from_slice(&deps[i]);
+            }
+        }
+        // topological sort, preferring the declaration order
+        let num_used = used.iter().filter(|&&u| u).count();
+        let mut order = Vec::with_capacity(num_used);
+        let mut done = vec![false; num];
+        while order.len() < num_used {
+            match (0 .. num).find(|&i| used[i] && !done[i] && deps[i].iter().all(|&j| done[j])) {
+                Some(i) => {
+                    done[i] = true;
+                    order.push(i);
+                },
+                None => return Err(GraphError::Cycle((0 .. num)
+                    .filter(|&i| used[i] && !done[i])
+                    .map(|i| self.passes[i].name.clone())
+                    .collect())),
+            }
+        }
+        Ok(order)
+    }
+
+    /// Order the passes and create the transient targets. Has to be called
+    /// again when the graph or the target sizes change.
+    pub fn compile<F: Factory<R> + ?Sized>(&mut self, factory: &mut F) -> Result<(), GraphError> {
+        let order = try!(self.schedule());
+        // lifetime of each transient, in positions of `order`
+        let mut lifetimes: Vec<Option<(usize, usize)>> = vec![None; self.targets.len()];
+        let mut clears = vec![Vec::new(); order.len()];
+        for (pos, &i) in order.iter().enumerate() {
+            let pass = &self.passes[i];
+            for &id in pass.writes.iter().chain(pass.reads.iter()) {
+                if let Target::Transient(_) = self.targets[id.0] {
+                    lifetimes[id.0] = Some(match lifetimes[id.0] {
+                        Some((first, _)) => (first, pos),
+                        None => {
+                            clears[pos].push(id);
+                            (pos, pos)
+                        },
+                    });
+                }
+            }
+        }
+        // assign the physical targets, reusing the ones free again
+        let mut physical: Vec<(TargetDesc, usize, Views<R>)> = Vec::new();
+        let mut views = Vec::with_capacity(self.targets.len());
+        let mut by_first: Vec<usize> = (0 .. self.targets.len()).collect();
+        by_first.sort_by_key(|&t| lifetimes[t].map(|l| l.0));
+        let mut assigned = vec![None; self.targets.len()];
+        for t in by_first {
+            let (desc, (first, last)) = match (&self.targets[t], lifetimes[t]) {
+                (&Target::Transient(desc), Some(lifetime)) => (desc, lifetime),
+                _ => continue,
+            };
+            let slot = match physical.iter().position(|p| p.0 == desc && p.1 < first) {
+                Some(slot) => slot,
+                None => {
+                    physical.push((desc, 0, try!(create_views(factory, desc))));
+                    physical.len() - 1
+                },
+            };
+            physical[slot].1 = last;
+            assigned[t] = Some(slot);
+        }
+        for (t, target) in self.targets.iter().enumerate() {
+            views.push(match (target, assigned[t]) {
+                (_, Some(slot)) => physical[slot].2.clone(),
+                (&Target::Color(ref view, _), _) => Views {
+                    color: Some(view.clone()),
+                    depth: None,
+                    resource: None,
+                },
+                (&Target::Depth(ref view, _), _) => Views {
+                    color: None,
+                    depth: Some(view.clone()),
+                    resource: None,
+                },
+                (&Target::Transient(_), None) => Views {
+                    color: None,
+                    depth: None,
+                    resource: None,
+                },
+            });
+        }
+        self.order = order.into_iter().zip(clears.into_iter()).collect();
+        self.compiled = Targets { views: views };
+        Ok(())
+    }
+
+    /// Record the passes of the last compilation, in order.
+    pub fn execute(&mut self, encoder: &mut Encoder<R, C>) {
+        for &(i, ref clears) in self.order.iter() {
+            let pass = &mut self.passes[i];
+            encoder.push_debug_group(&pass.name);
+            for &id in clears.iter() {
+                let views = &self.compiled.views[id.0];
+                if let Some(ref view) = views.color {
+                    encoder.clear_raw(view, ClearColor::Float([0.0; 4]));
+                }
+                if let Some(ref view) = views.depth {
+                    encoder.clear_depth_stencil_raw(view, Some(1.0), Some(0));
+                }
+            }
+            (pass.execute)(encoder, &self.compiled);
+            encoder.pop_debug_group();
+        }
+    }
+}
+
+/// Get a window size divided by `divisor`, rounding up.
+fn get_divided_size(size: (texture::Size, texture::Size), divisor: texture::Size)
+                    -> (texture::Size, texture::Size) {
+    let divisor = cmp::max(divisor, 1);
+    (cmp::max((size.0 + divisor - 1) / divisor, 1),
+     cmp::max((size.1 + divisor - 1) / divisor, 1))
+}
+
+fn create_views<R, F>(factory: &mut F, desc: TargetDesc) -> Result<Views<R>, CombinedError> where
+    R: Resources,
+    F: Factory<R> + ?Sized,
+{
+    let depth = desc.is_depth();
+    let info = texture::Info {
+        kind: texture::Kind::D2(desc.width, desc.height, texture::AaMode::Single),
+        levels: 1,
+        format: desc.format.0,
+        bind: SHADER_RESOURCE | if depth { DEPTH_STENCIL } else { RENDER_TARGET },
+        usage: Usage::Data,
+    };
+    let tex = try!(factory.create_texture_raw(info, Some(desc.format.1), None));
+    let resource = try!(factory.view_texture_as_shader_resource_raw(&tex, texture::ResourceDesc {
+        channel: desc.format.1,
+        layer: None,
+        min: 0,
+        max: 0,
+        swizzle: Swizzle::new(),
+    }));
+    let render = texture::RenderDesc {
+        channel: desc.format.1,
+        level: 0,
+        layer: None,
+    };
+    Ok(if depth {
+        Views {
+            color: None,
+            depth: Some(try!(factory.view_texture_as_depth_stencil_raw(&tex, render.into()))),
+            resource: Some(resource),
+        }
+    } else {
+        Views {
+            color: Some(try!(factory.view_texture_as_render_target_raw(&tex, render))),
+            depth: None,
+            resource: Some(resource),
+        }
+    })
+}
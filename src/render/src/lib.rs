@@ -38,38 +38,82 @@ pub use draw_state::{preset, state};
 pub use draw_state::target::*;
 
 // public re-exports
-pub use core::{Device, Primitive, Resources, SubmissionError, SubmissionResult};
-pub use core::{VertexCount, InstanceCount};
+pub use core::{Device, Feature, Primitive, Resources, SubmissionError, SubmissionResult};
+pub use core::{VertexCount, InstanceCount, QuerySlot};
 pub use core::{ShaderSet, VertexShader, HullShader, DomainShader, GeometryShader, PixelShader};
-pub use core::{buffer, format, handle, texture, mapping};
+pub use core::{buffer, capture, format, handle, texture, mapping};
 pub use core::factory::{Factory, ResourceViewError, TargetViewError, CombinedError};
 pub use core::memory::{self, Bind, TRANSFER_SRC, TRANSFER_DST, RENDER_TARGET,
                        DEPTH_STENCIL, SHADER_RESOURCE, UNORDERED_ACCESS};
 pub use core::command::{Buffer as CommandBuffer, InstanceParams};
 pub use core::shade::{ProgramInfo, UniformValue};
 
+pub use dynamic::{ConstantAllocator, DynamicBuffer, WriteError};
 pub use encoder::{CopyBufferResult, CopyBufferTextureResult, CopyError,
                   CopyTextureBufferResult, Encoder, UpdateError};
 pub use factory::PipelineStateError;
-pub use slice::{Slice, IntoIndexBuffer, IndexBuffer};
+pub use slice::{Slice, IntoIndexBuffer, IndexBuffer, IndexBufferError};
 pub use pso::{PipelineState};
-pub use pso::buffer::{VertexBuffer, InstanceBuffer, RawVertexBuffer,
-                      ConstantBuffer, RawConstantBuffer, Global, RawGlobal};
+pub use pso::buffer::{VertexBuffer, InstanceBuffer, VertexBufferOffset, RawVertexBuffer,
+                      ConstantBuffer, ConstantBufferOffset, RawConstantBuffer,
+                      Global, RawGlobal, GlobalArray, PerView, AtomicCounterBuffer};
 pub use pso::resource::{ShaderResource, RawShaderResource, UnorderedAccess,
                         Sampler, TextureSampler};
 pub use pso::target::{DepthStencilTarget, DepthTarget, StencilTarget,
-                      RenderTarget, RawRenderTarget, BlendTarget, BlendRef, Scissor};
-pub use pso::bundle::{Bundle};
+                      RenderTarget, RawRenderTarget, BlendTarget, BlendRef, Scissor,
+                      PointSprite};
+pub use pso::bundle::{Bundle, BundleContext, CoreBundle};
+pub use queue::{RenderQueue};
+pub use technique::{MissingFeatures, Technique};
+pub use validate::DrawError;
+pub use variant::{VariantCache, VariantError};
 
+/// Streaming buffers
+mod dynamic;
 /// Render commands encoder
 mod encoder;
 /// Factory extensions
 mod factory;
 /// Slices
 mod slice;
+/// Debug lines
+pub mod debug_draw;
+/// Frame graphs
+pub mod graph;
+/// Geometry helpers
+pub mod mesh;
+/// Occlusion culling
+pub mod occlusion;
+/// Object picking
+#[cfg(feature = "picking")]
+pub mod picking;
 // Pipeline states
 pub mod pso;
+/// Post-processing chains
+pub mod post;
+/// Shader source preprocessing
+pub mod preprocess;
+/// Draw call sorting
+pub mod queue;
+/// Resource re-creation
+pub mod recreate;
 /// Shaders
 pub mod shade;
+/// Shadow maps
+pub mod shadow;
+/// Stereo rendering
+pub mod stereo;
+/// Feature fallbacks
+pub mod technique;
+/// Bitmap text
+pub mod text;
+/// Frame timing statistics
+pub mod timing;
+/// GLSL version translation
+pub mod translate;
+/// Draw call validation
+pub mod validate;
+/// Shader permutations
+pub mod variant;
 /// Convenience macros
 pub mod macros;
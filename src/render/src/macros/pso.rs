@@ -150,6 +150,21 @@ macro_rules! gfx_pipeline_inner {
                     )*
                     return Err(InitError::Sampler(&sm.name, None));
                 }
+                // atomic counters
+                for acb in &info.atomic_counter_buffers {
+                    $(
+                        match meta.$field.link_atomic_counter_buffer(acb, &self.$field) {
+                            Some(d) => {
+                                assert!(meta.$field.is_active());
+                                desc.atomic_counter_buffers[acb.slot as usize] = Some(d);
+                                continue;
+                            },
+                            None => (),
+                        }
+                    )*
+                    let name = acb.counters.first().map_or("", |c| c.name.as_str());
+                    return Err(InitError::AtomicCounterBuffer(name, None));
+                }
                 // color targets
                 for out in &info.outputs {
                     $(
@@ -189,7 +204,7 @@ macro_rules! gfx_pipeline_inner {
                         }
                     )*
                 }
-                // depth-stencil, scissor
+                // depth-stencil, scissor, point sprites
                 for _ in 0 .. 1 {
                     $(
                         if let Some(d) = meta.$field.link_depth_stencil(&self.$field) {
@@ -200,6 +215,10 @@ macro_rules! gfx_pipeline_inner {
                             assert!(meta.$field.is_active());
                             desc.scissor = true;
                         }
+                        if let Some(p) = meta.$field.link_point(&self.$field) {
+                            assert!(meta.$field.is_active());
+                            desc.point = p;
+                        }
                     )*
                 }
                 // done
@@ -0,0 +1,177 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Procedural shapes.
+//!
+//! All the shapes are centered at the origin and fit into the `[-1, 1]`
+//! cube, except `fullscreen_triangle`, which reaches 3 to cover the whole
+//! clip space square. Triangles are wound counter-clockwise when seen from
+//! the outside.
+
+use std::f32::consts::PI;
+use super::{Geometry, Vertex};
+
+fn vertex(pos: [f32; 3], normal: [f32; 3], tex_coord: [f32; 2]) -> Vertex {
+    Vertex {
+        pos: pos,
+        normal: normal,
+        tex_coord: tex_coord,
+    }
+}
+
+/// Add the triangles of a grid of `columns` by `rows` quads, whose
+/// vertices start at `base` and go row by row.
+fn push_grid(indices: &mut Vec<u16>, base: usize, columns: usize, rows: usize) {
+    let stride = columns + 1;
+    for row in 0 .. rows {
+        for col in 0 .. columns {
+            let i = (base + row * stride + col) as u16;
+            let s = stride as u16;
+            indices.extend_from_slice(&[i, i + 1, i + s + 1, i + s + 1, i + s, i]);
+        }
+    }
+}
+
+fn check_size(num_vertices: usize) {
+    assert!(num_vertices <= 0x10000, "Too many vertices for 16-bit indices: {}", num_vertices);
+}
+
+/// A cube with flat faces, each textured with the full `[0, 1]` range.
+pub fn cube() -> Geometry {
+    // normal, then the directions of increasing U and V
+    let faces = [
+        ([ 1.0, 0.0, 0.0], [ 0.0, 0.0,-1.0], [0.0, 1.0, 0.0]),
+        ([-1.0, 0.0, 0.0], [ 0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+        ([ 0.0, 1.0, 0.0], [ 1.0, 0.0, 0.0], [0.0, 0.0,-1.0]),
+        ([ 0.0,-1.0, 0.0], [ 1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ([ 0.0, 0.0, 1.0], [ 1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([ 0.0, 0.0,-1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ];
+    let mut geometry = Geometry {
+        vertices: Vec::with_capacity(24),
+        indices: Vec::with_capacity(36),
+    };
+    for &(n, u, v) in faces.iter() {
+        let base = geometry.vertices.len();
+        for &(su, sv) in [(-1.0f32, -1.0f32), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)].iter() {
+            let pos = [n[0] + su * u[0] + sv * v[0],
+                       n[1] + su * u[1] + sv * v[1],
+                       n[2] + su * u[2] + sv * v[2]];
+            geometry.vertices.push(vertex(pos, n, [(su + 1.0) * 0.5, (sv + 1.0) * 0.5]));
+        }
+        push_grid(&mut geometry.indices, base, 1, 1);
+    }
+    geometry
+}
+
+/// A sphere of radius 1, made of `sectors` slices around the Y axis and
+/// `stacks` rings from the bottom pole to the top one.
+pub fn uv_sphere(sectors: usize, stacks: usize) -> Geometry {
+    assert!(sectors >= 3 && stacks >= 2);
+    check_size((sectors + 1) * (stacks + 1));
+    let mut geometry = Geometry {
+        vertices: Vec::with_capacity((sectors + 1) * (stacks + 1)),
+        indices: Vec::with_capacity(sectors * stacks * 6),
+    };
+    for stack in 0 .. stacks + 1 {
+        let v = stack as f32 / stacks as f32;
+        let phi = (v - 0.5) * PI;
+        for sector in 0 .. sectors + 1 {
+            let u = sector as f32 / sectors as f32;
+            let theta = u * 2.0 * PI;
+            let n = [phi.cos() * theta.sin(), phi.sin(), phi.cos() * theta.cos()];
+            geometry.vertices.push(vertex(n, n, [u, v]));
+        }
+    }
+    push_grid(&mut geometry.indices, 0, sectors, stacks);
+    geometry
+}
+
+/// A square in the XY plane facing +Z, split into `segments` by
+/// `segments` quads.
+pub fn plane(segments: usize) -> Geometry {
+    assert!(segments >= 1);
+    check_size((segments + 1) * (segments + 1));
+    let mut geometry = Geometry {
+        vertices: Vec::with_capacity((segments + 1) * (segments + 1)),
+        indices: Vec::with_capacity(segments * segments * 6),
+    };
+    for row in 0 .. segments + 1 {
+        let v = row as f32 / segments as f32;
+        for col in 0 .. segments + 1 {
+            let u = col as f32 / segments as f32;
+            let pos = [u * 2.0 - 1.0, v * 2.0 - 1.0, 0.0];
+            geometry.vertices.push(vertex(pos, [0.0, 0.0, 1.0], [u, v]));
+        }
+    }
+    push_grid(&mut geometry.indices, 0, segments, segments);
+    geometry
+}
+
+/// A closed cylinder of radius 1 around the Y axis, made of `sectors`
+/// slices. The side is textured with U going around and V going up, and
+/// the caps get planar coordinates.
+pub fn cylinder(sectors: usize) -> Geometry {
+    assert!(sectors >= 3);
+    check_size((sectors + 1) * 2 + (sectors + 1) * 2);
+    let mut geometry = Geometry {
+        vertices: Vec::new(),
+        indices: Vec::new(),
+    };
+    let angle = |sector: usize| sector as f32 / sectors as f32 * 2.0 * PI;
+    // side
+    for &y in [-1.0f32, 1.0].iter() {
+        for sector in 0 .. sectors + 1 {
+            let (s, c) = angle(sector).sin_cos();
+            geometry.vertices.push(vertex([s, y, c], [s, 0.0, c],
+                                          [sector as f32 / sectors as f32, (y + 1.0) * 0.5]));
+        }
+    }
+    push_grid(&mut geometry.indices, 0, sectors, 1);
+    // caps, as fans around a center vertex
+    for &y in [-1.0f32, 1.0].iter() {
+        let center = geometry.vertices.len() as u16;
+        geometry.vertices.push(vertex([0.0, y, 0.0], [0.0, y, 0.0], [0.5, 0.5]));
+        for sector in 0 .. sectors {
+            let (s, c) = angle(sector).sin_cos();
+            geometry.vertices.push(vertex([s, y, c], [0.0, y, 0.0],
+                                          [(s + 1.0) * 0.5, (c + 1.0) * 0.5]));
+        }
+        for sector in 0 .. sectors as u16 {
+            let a = center + 1 + sector;
+            let b = center + 1 + (sector + 1) % sectors as u16;
+            if y > 0.0 {
+                geometry.indices.extend_from_slice(&[center, a, b]);
+            } else {
+                geometry.indices.extend_from_slice(&[center, b, a]);
+            }
+        }
+    }
+    geometry
+}
+
+/// A single triangle covering the whole `[-1, 1]` clip space square,
+/// with texture coordinates going from 0 to 1 over the visible part.
+/// Cheaper than a quad for full-screen passes.
+pub fn fullscreen_triangle() -> Geometry {
+    let n = [0.0, 0.0, 1.0];
+    Geometry {
+        vertices: vec![
+            vertex([-1.0, -1.0, 0.0], n, [0.0, 0.0]),
+            vertex([ 3.0, -1.0, 0.0], n, [2.0, 0.0]),
+            vertex([-1.0,  3.0, 0.0], n, [0.0, 2.0]),
+        ],
+        indices: vec![0, 1, 2],
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ready-made geometry.
+//!
+//! A `Geometry` is an indexed triangle list stored on the CPU, made of
+//! `Vertex` values with positions, normals and texture coordinates. Its
+//! vertex attributes are named `a_Pos`, `a_Normal` and `a_TexCoord`, so a
+//! `VertexBuffer<Vertex>` component can be linked to shaders that use these.
+
+use std::mem;
+use core::{format, handle, Resources};
+use core::memory::Pod;
+use core::format::Formatted;
+use pso::buffer::{Element, ElemOffset, Structure};
+use factory::FactoryExt;
+use slice::Slice;
+
+pub mod generate;
+#[cfg(feature = "obj")]
+pub mod obj;
+
+/// A vertex with a position, a normal and texture coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Vertex {
+    /// Position, bound to `a_Pos`
+    pub pos: [f32; 3],
+    /// Normal, bound to `a_Normal`
+    pub normal: [f32; 3],
+    /// Texture coordinates, bound to `a_TexCoord`
+    pub tex_coord: [f32; 2],
+}
+
+unsafe impl Pod for Vertex {}
+
+impl Structure<format::Format> for Vertex {
+    fn query(name: &str) -> Option<Element<format::Format>> {
+        let float = mem::size_of::<f32>() as ElemOffset;
+        let (format, offset) = match name {
+            "a_Pos" => (<[f32; 3] as Formatted>::get_format(), 0),
+            "a_Normal" => (<[f32; 3] as Formatted>::get_format(), 3 * float),
+            "a_TexCoord" => (<[f32; 2] as Formatted>::get_format(), 6 * float),
+            _ => return None,
+        };
+        Some(Element {
+            format: format,
+            offset: offset,
+        })
+    }
+}
+
+/// An indexed triangle list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Geometry {
+    /// Vertex data
+    pub vertices: Vec<Vertex>,
+    /// Indices into `vertices`, three per triangle
+    pub indices: Vec<u16>,
+}
+
+impl Geometry {
+    /// Create the vertex buffer and the slice to draw the geometry with.
+    pub fn upload<R, F>(&self, factory: &mut F) -> (handle::Buffer<R, Vertex>, Slice<R>) where
+        R: Resources,
+        F: FactoryExt<R>,
+    {
+        factory.create_vertex_buffer_with_slice(&self.vertices, &self.indices[..])
+    }
+}
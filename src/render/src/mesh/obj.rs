@@ -0,0 +1,375 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wavefront OBJ and MTL loading.
+//!
+//! Only the polygonal subset of the format is supported: positions,
+//! texture coordinates, normals, faces (triangulated as fans) and material
+//! switches. Faces without normals get the normal of their plane.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::{fmt, io};
+use std::io::BufRead;
+use std::str::SplitWhitespace;
+use core::{handle, Resources};
+use core::shade::UniformValue;
+use factory::FactoryExt;
+use slice::Slice;
+use super::Vertex;
+
+/// An error while loading an OBJ or MTL file.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The reader failed.
+    Io(io::Error),
+    /// A line couldn't be parsed.
+    Parse {
+        /// Line number, starting at 1
+        line: usize,
+        /// What went wrong
+        message: String,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Io(ref e) => write!(f, "{}: {}", self.description(), e),
+            LoadError::Parse { line, ref message } =>
+                write!(f, "{} at line {}: {}", self.description(), line, message),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn description(&self) -> &str {
+        match *self {
+            LoadError::Io(_) => "Failed to read the file",
+            LoadError::Parse { .. } => "Failed to parse the file",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            LoadError::Io(ref e) => Some(e),
+            LoadError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
+
+/// A range of indices drawn with the same material.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Group {
+    /// Name of the material, as given to `usemtl`
+    pub material: Option<String>,
+    /// First index of the group
+    pub start: u32,
+    /// One past the last index of the group
+    pub end: u32,
+}
+
+/// The contents of an OBJ file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Model {
+    /// Interleaved vertex data
+    pub vertices: Vec<Vertex>,
+    /// Triangle list indices into `vertices`
+    pub indices: Vec<u32>,
+    /// Material groups, covering all the indices
+    pub groups: Vec<Group>,
+    /// Material libraries referenced by `mtllib`, to be loaded with
+    /// `load_mtl`
+    pub material_libraries: Vec<String>,
+}
+
+impl Model {
+    /// Create the vertex and index buffers, and a slice for each group.
+    pub fn upload<R, F>(&self, factory: &mut F)
+                  -> (handle::Buffer<R, Vertex>, Vec<(Slice<R>, Option<String>)>) where
+        R: Resources,
+        F: FactoryExt<R>,
+    {
+        let vertex_buffer = factory.create_vertex_buffer(&self.vertices);
+        let index_buffer = factory.create_index_buffer(&self.indices[..]);
+        let slices = self.groups.iter().map(|group| {
+            let slice = Slice {
+                start: group.start,
+                end: group.end,
+                base_vertex: 0,
+                instances: None,
+                buffer: index_buffer.clone(),
+            };
+            (slice, group.material.clone())
+        }).collect();
+        (vertex_buffer, slices)
+    }
+}
+
+/// A material of an MTL file. Colors and textures that are not specified
+/// keep their default value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    /// Ambient color, `Ka`
+    pub ambient: [f32; 3],
+    /// Diffuse color, `Kd`
+    pub diffuse: [f32; 3],
+    /// Specular color, `Ks`
+    pub specular: [f32; 3],
+    /// Specular exponent, `Ns`
+    pub shininess: f32,
+    /// Opacity, `d`
+    pub alpha: f32,
+    /// Path of the diffuse texture, `map_Kd`
+    pub diffuse_map: Option<String>,
+    /// Path of the normal map, `map_Bump` or `bump`
+    pub normal_map: Option<String>,
+}
+
+impl Material {
+    fn new() -> Material {
+        Material {
+            ambient: [0.0; 3],
+            diffuse: [1.0; 3],
+            specular: [0.0; 3],
+            shininess: 0.0,
+            alpha: 1.0,
+            diffuse_map: None,
+            normal_map: None,
+        }
+    }
+
+    /// Get the material parameters as named shader globals, to be set
+    /// with `gfx::Global` components of the same names.
+    pub fn get_uniforms(&self) -> Vec<(&'static str, UniformValue)> {
+        vec![
+            ("u_Ambient", UniformValue::F32Vector3(self.ambient)),
+            ("u_Diffuse", UniformValue::F32Vector3(self.diffuse)),
+            ("u_Specular", UniformValue::F32Vector3(self.specular)),
+            ("u_Shininess", UniformValue::F32(self.shininess)),
+            ("u_Alpha", UniformValue::F32(self.alpha)),
+        ]
+    }
+}
+
+fn parse_error<T>(line: usize, message: String) -> Result<T, LoadError> {
+    Err(LoadError::Parse {
+        line: line,
+        message: message,
+    })
+}
+
+fn parse_floats(words: SplitWhitespace, line: usize, out: &mut [f32]) -> Result<(), LoadError> {
+    let mut count = 0;
+    for (word, value) in words.zip(out.iter_mut()) {
+        *value = match word.parse() {
+            Ok(v) => v,
+            Err(_) => return parse_error(line, format!("invalid number {:?}", word)),
+        };
+        count += 1;
+    }
+    if count == 0 {
+        return parse_error(line, "missing values".to_string());
+    }
+    Ok(())
+}
+
+/// Resolve a 1-based, possibly negative OBJ index into `count` elements.
+fn resolve(word: &str, count: usize, line: usize) -> Result<usize, LoadError> {
+    let index: isize = match word.parse() {
+        Ok(i) => i,
+        Err(_) => return parse_error(line, format!("invalid index {:?}", word)),
+    };
+    let resolved = if index < 0 { count as isize + index } else { index - 1 };
+    if resolved < 0 || resolved as usize >= count {
+        return parse_error(line, format!("index {} out of range", index));
+    }
+    Ok(resolved as usize)
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn face_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let (u, v) = (sub(b, a), sub(c, a));
+    let n = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+    let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if length > 0.0 {
+        [n[0] / length, n[1] / length, n[2] / length]
+    } else {
+        [0.0, 0.0, 1.0]
+    }
+}
+
+/// Parse an OBJ file.
+pub fn load_obj<B: BufRead>(reader: B) -> Result<Model, LoadError> {
+    let mut positions = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut normals = Vec::new();
+    let mut model = Model {
+        vertices: Vec::new(),
+        indices: Vec::new(),
+        groups: Vec::new(),
+        material_libraries: Vec::new(),
+    };
+    let mut current = Group { material: None, start: 0, end: 0 };
+    // vertices already emitted, by position, texture coordinate and normal
+    let mut cache: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+    let mut face = Vec::new();
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = try!(line);
+        let number = number + 1;
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("v") => {
+                let mut v = [0.0; 3];
+                try!(parse_floats(words, number, &mut v));
+                positions.push(v);
+            },
+            Some("vt") => {
+                let mut v = [0.0; 2];
+                try!(parse_floats(words, number, &mut v));
+                tex_coords.push(v);
+            },
+            Some("vn") => {
+                let mut v = [0.0; 3];
+                try!(parse_floats(words, number, &mut v));
+                normals.push(v);
+            },
+            Some("f") => {
+                face.clear();
+                for word in words {
+                    let mut parts = word.split('/');
+                    let p = try!(resolve(parts.next().unwrap_or(""), positions.len(), number));
+                    let t = match parts.next() {
+                        Some("") | None => None,
+                        Some(s) => Some(try!(resolve(s, tex_coords.len(), number))),
+                    };
+                    let n = match parts.next() {
+                        Some("") | None => None,
+                        Some(s) => Some(try!(resolve(s, normals.len(), number))),
+                    };
+                    face.push((p, t, n));
+                }
+                if face.len() < 3 {
+                    return parse_error(number, "face with less than 3 vertices".to_string());
+                }
+                let flat = face_normal(positions[face[0].0], positions[face[1].0], positions[face[2].0]);
+                for i in 1 .. face.len() - 1 {
+                    for &key in [face[0], face[i], face[i + 1]].iter() {
+                        let index = match key.2 {
+                            // vertices with computed normals are not shared between faces
+                            None => None,
+                            Some(_) => cache.get(&key).cloned(),
+                        };
+                        let index = match index {
+                            Some(index) => index,
+                            None => {
+                                let index = model.vertices.len() as u32;
+                                model.vertices.push(Vertex {
+                                    pos: positions[key.0],
+                                    normal: key.2.map_or(flat, |n| normals[n]),
+                                    tex_coord: key.1.map_or([0.0, 0.0], |t| tex_coords[t]),
+                                });
+                                if key.2.is_some() {
+                                    cache.insert(key, index);
+                                }
+                                index
+                            },
+                        };
+                        model.indices.push(index);
+                    }
+                }
+            },
+            Some("usemtl") => {
+                current.end = model.indices.len() as u32;
+                if current.end > current.start {
+                    model.groups.push(current.clone());
+                }
+                current = Group {
+                    material: words.next().map(|s| s.to_string()),
+                    start: model.indices.len() as u32,
+                    end: 0,
+                };
+            },
+            Some("mtllib") => {
+                model.material_libraries.extend(words.map(|s| s.to_string()));
+            },
+            // comments, object and smoothing groups, lines and points
+            _ => (),
+        }
+    }
+    current.end = model.indices.len() as u32;
+    if current.end > current.start {
+        model.groups.push(current);
+    }
+    Ok(model)
+}
+
+/// Parse an MTL file into its materials, by name.
+pub fn load_mtl<B: BufRead>(reader: B) -> Result<HashMap<String, Material>, LoadError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, Material)> = None;
+    for (number, line) in reader.lines().enumerate() {
+        let line = try!(line);
+        let number = number + 1;
+        let mut words = line.split_whitespace();
+        let keyword = words.next();
+        if keyword == Some("newmtl") {
+            if let Some((name, material)) = current.take() {
+                materials.insert(name, material);
+            }
+            let name = words.next().unwrap_or("").to_string();
+            current = Some((name, Material::new()));
+            continue;
+        }
+        let material = match (keyword, current.as_mut()) {
+            (None, _) => continue,
+            (Some(k), _) if k.starts_with('#') => continue,
+            (Some(_), Some(&mut (_, ref mut material))) => material,
+            (Some(_), None) => return parse_error(number, "property outside of a material".to_string()),
+        };
+        match keyword {
+            Some("Ka") => try!(parse_floats(words, number, &mut material.ambient)),
+            Some("Kd") => try!(parse_floats(words, number, &mut material.diffuse)),
+            Some("Ks") => try!(parse_floats(words, number, &mut material.specular)),
+            Some("Ns") => {
+                let mut v = [0.0];
+                try!(parse_floats(words, number, &mut v));
+                material.shininess = v[0];
+            },
+            Some("d") => {
+                let mut v = [0.0];
+                try!(parse_floats(words, number, &mut v));
+                material.alpha = v[0];
+            },
+            // the path comes last, after the options
+            Some("map_Kd") => material.diffuse_map = words.last().map(|s| s.to_string()),
+            Some("map_Bump") | Some("bump") => material.normal_map = words.last().map(|s| s.to_string()),
+            _ => (),
+        }
+    }
+    if let Some((name, material)) = current {
+        materials.insert(name, material);
+    }
+    Ok(materials)
+}
@@ -0,0 +1,172 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Visibility culling with occlusion queries.
+//!
+//! After the occluders are drawn, the bounding box of each object is tested
+//! against the depth buffer within an occlusion query. The results are
+//! read back after the submission, without waiting for them, so the
+//! visibility of an object lags a frame or so behind its test:
+//!
+//! ```ignore
+//! encoder.draw(&terrain_slice, &terrain_pso, &terrain_data);
+//! for (i, object) in objects.iter().enumerate() {
+//!     culler.test(&mut encoder, i, |encoder| {
+//!         let data = box_pipe::Data { transform: object.transform, .. };
+//!         encoder.draw(&box_slice, &box_pso, &data);
+//!     });
+//!     culler.draw(&mut encoder, i, &object.slice, &pso, &object.data);
+//! }
+//! encoder.flush(&mut device);
+//! culler.update(&mut device);
+//! ```
+//!
+//! The boxes are drawn with the vertices of `box_vertices`, by a pipeline
+//! writing neither color nor depth, and culling no faces, so that a box
+//! containing the camera is still found visible. Objects are visible until
+//! their first result is known, and forever on devices without
+//! occlusion queries.
+
+use std::mem;
+use core::{format, Device, QuerySlot, Resources};
+use core::command::Buffer as CommandBuffer;
+use core::format::Formatted;
+use core::memory::Pod;
+use encoder::Encoder;
+use pso::{PipelineData, PipelineState};
+use pso::buffer::{Element, Structure};
+use slice::Slice;
+
+/// A corner of a bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct BoxVertex {
+    /// Position, bound to `a_Pos`
+    pub pos: [f32; 3],
+}
+
+unsafe impl Pod for BoxVertex {}
+
+impl Structure<format::Format> for BoxVertex {
+    fn query(name: &str) -> Option<Element<format::Format>> {
+        match name {
+            "a_Pos" => Some(Element {
+                format: <[f32; 3] as Formatted>::get_format(),
+                offset: 0,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The corners of each face, counter-clockwise from the outside, with the
+/// bits of the index selecting the maximum along X, Y and Z.
+const BOX_FACES: [[usize; 4]; 6] = [
+    [0, 4, 6, 2], [1, 3, 7, 5],
+    [0, 1, 5, 4], [2, 6, 7, 3],
+    [0, 2, 3, 1], [4, 5, 7, 6],
+];
+
+/// Get the triangles of an axis-aligned box, as a `TriangleList`.
+pub fn box_vertices(min: [f32; 3], max: [f32; 3]) -> [BoxVertex; 36] {
+    let corner = |i: usize| BoxVertex {
+        pos: [
+            if i & 1 != 0 { max[0] } else { min[0] },
+            if i & 2 != 0 { max[1] } else { min[1] },
+            if i & 4 != 0 { max[2] } else { min[2] },
+        ],
+    };
+    let mut vertices = [BoxVertex { pos: [0.0; 3] }; 36];
+    for (face, out) in BOX_FACES.iter().zip(vertices.chunks_mut(6)) {
+        for (v, &i) in out.iter_mut().zip([0, 1, 2, 0, 2, 3].iter()) {
+            *v = corner(face[i]);
+        }
+    }
+    vertices
+}
+
+/// The visibility of a set of objects, each tested by its own occlusion
+/// query.
+#[derive(Clone, Debug)]
+pub struct OcclusionCuller {
+    base: QuerySlot,
+    visible: Vec<bool>,
+    pending: Vec<bool>,
+}
+
+impl OcclusionCuller {
+    /// Create a culler of `count` objects, using the query slots from
+    /// `base` on.
+    pub fn new(base: QuerySlot, count: usize) -> OcclusionCuller {
+        OcclusionCuller {
+            base: base,
+            visible: vec![true; count],
+            pending: vec![false; count],
+        }
+    }
+
+    /// Get the number of objects.
+    pub fn len(&self) -> usize {
+        self.visible.len()
+    }
+
+    /// Check whether an object was visible at its last completed test.
+    pub fn is_visible(&self, object: usize) -> bool {
+        self.visible[object]
+    }
+
+    /// Test the visibility of an object, drawing its bounding box with
+    /// `draw_box`. The test is skipped while the previous one of the
+    /// object is not complete.
+    pub fn test<R, C, F>(&mut self, encoder: &mut Encoder<R, C>, object: usize, draw_box: F) where
+        R: Resources,
+        C: CommandBuffer<R>,
+        F: FnOnce(&mut Encoder<R, C>),
+    {
+        if mem::replace(&mut self.pending[object], true) {
+            return
+        }
+        let slot = self.base + object as QuerySlot;
+        encoder.begin_occlusion_query(slot);
+        draw_box(encoder);
+        encoder.end_occlusion_query(slot);
+    }
+
+    /// Draw an object if it is visible.
+    pub fn draw<R, C, D>(&self, encoder: &mut Encoder<R, C>, object: usize, slice: &Slice<R>,
+                         pipeline: &PipelineState<R, D::Meta>, data: &D) where
+        R: Resources,
+        C: CommandBuffer<R>,
+        D: PipelineData<R>,
+    {
+        if self.visible[object] {
+            encoder.draw(slice, pipeline, data);
+        }
+    }
+
+    /// Read back the results available, after submitting the tests.
+    pub fn update<D: Device>(&mut self, device: &mut D) {
+        for (i, (visible, pending)) in self.visible.iter_mut()
+                                                   .zip(self.pending.iter_mut())
+                                                   .enumerate() {
+            if !*pending {
+                continue
+            }
+            if let Some(result) = device.get_occlusion_result(self.base + i as QuerySlot) {
+                *visible = result;
+                *pending = false;
+            }
+        }
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Object picking on the GPU.
+//!
+//! The objects are drawn into the integer target of a `Picker`, each with
+//! its own identifier, passed to the pixel shader by a `Global<u32>` and
+//! written as is. The identifier of an object is its index offset by one,
+//! since zero is left for the background. The identifier under the cursor is then copied to a
+//! download buffer, and read back a few frames later, once the copy is
+//! complete, so that picking never stalls the pipeline:
+//!
+//! ```ignore
+//! gfx_defines! {
+//!     pipeline pick {
+//!         vbuf: gfx::VertexBuffer<Vertex> = (),
+//!         transform: gfx::Global<[[f32; 4]; 4]> = "u_Transform",
+//!         id: gfx::Global<u32> = "u_Id",
+//!         out: gfx::RenderTarget<gfx::picking::IdFormat> = "Target0",
+//!         depth: gfx::DepthTarget<gfx::format::Depth> = gfx::preset::depth::LESS_EQUAL_WRITE,
+//!     }
+//! }
+//!
+//! picker.clear(&mut encoder);
+//! for (entity, batch) in scene.iter() {
+//!     let data = pick::Data { id: gfx::picking::encode_id(entity.index), .. };
+//!     encoder.draw(&batch.slice, &pso, &data);
+//! }
+//! try!(picker.request(&mut factory, &mut encoder, cursor_x, cursor_y));
+//! encoder.flush(&mut device);
+//! if let Some(result) = try!(picker.poll(&mut factory)) {
+//!     selection = result.object;
+//! }
+//! ```
+//!
+//! The coordinates are the ones of the target, whose origin depends on the
+//! backend: it is at the bottom left with OpenGL.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use core::{buffer, format, handle, mapping, texture, Resources};
+use core::command::Buffer as CommandBuffer;
+use core::factory::{CombinedError, Factory};
+use core::memory::{Typed, Usage, RENDER_TARGET, TRANSFER_SRC};
+use encoder::{CopyError, Encoder};
+use factory::FactoryExt;
+
+/// The format of the target holding the object identifiers.
+pub type IdFormat = (format::R32, format::Uint);
+
+/// The identifier written where no object is drawn.
+pub const NO_OBJECT: u32 = 0;
+
+/// Number of frames between a request and the read back of its result.
+///
+/// The copy is assumed to be complete by then, there is no fence waiting
+/// for it: a driver queuing more frames ahead makes the read back stall
+/// until the copy is done, instead of returning a stale result.
+pub const LATENCY: usize = 2;
+
+/// Get the identifier to draw the object of index `index` with.
+pub fn encode_id(index: u32) -> u32 {
+    index + 1
+}
+
+/// Get the index of the object drawn with the identifier `id`, or `None`
+/// for `NO_OBJECT`.
+pub fn decode_id(id: u32) -> Option<u32> {
+    if id == NO_OBJECT { None } else { Some(id - 1) }
+}
+
+/// The object found under a position.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PickResult {
+    /// The position of the request.
+    pub position: (texture::Size, texture::Size),
+    /// The index of the object, or `None` if there is none.
+    pub object: Option<u32>,
+}
+
+/// An error requesting the object at a position.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RequestError {
+    /// The buffer to read the identifier back failed to be created.
+    Creation(buffer::CreationError),
+    /// The identifier failed to be copied, the position being out of the
+    /// target.
+    Copy(CopyError<[texture::Size; 3], usize>),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RequestError::Creation(ref e) => write!(f, "{}: {}", self.description(), e),
+            RequestError::Copy(ref e) => write!(f, "{}: {:?}", self.description(), e),
+        }
+    }
+}
+
+impl Error for RequestError {
+    fn description(&self) -> &str {
+        match *self {
+            RequestError::Creation(_) => "Could not create the picking buffer",
+            RequestError::Copy(_) => "Could not copy the identifier under the position",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            RequestError::Creation(ref e) => Some(e),
+            RequestError::Copy(_) => None,
+        }
+    }
+}
+
+impl From<buffer::CreationError> for RequestError {
+    fn from(e: buffer::CreationError) -> Self {
+        RequestError::Creation(e)
+    }
+}
+
+impl From<CopyError<[texture::Size; 3], usize>> for RequestError {
+    fn from(e: CopyError<[texture::Size; 3], usize>) -> Self {
+        RequestError::Copy(e)
+    }
+}
+
+struct Request<R: Resources> {
+    buffer: handle::Buffer<R, u32>,
+    position: (texture::Size, texture::Size),
+    age: usize,
+}
+
+/// The targets the objects are drawn into, and the pending requests.
+pub struct Picker<R: Resources> {
+    texture: handle::Texture<R, format::R32>,
+    target: handle::RenderTargetView<R, IdFormat>,
+    depth: handle::DepthStencilView<R, format::Depth>,
+    pending: VecDeque<Request<R>>,
+    free: Vec<handle::Buffer<R, u32>>,
+}
+
+impl<R: Resources> Picker<R> {
+    /// Create a picker with targets of `width` by `height`.
+    pub fn new<F: Factory<R>>(factory: &mut F, width: texture::Size, height: texture::Size)
+               -> Result<Picker<R>, CombinedError> {
+        let kind = texture::Kind::D2(width, height, texture::AaMode::Single);
+        let texture = try!(factory.create_texture(kind, 1, RENDER_TARGET | TRANSFER_SRC,
+                                                  Usage::Data, Some(format::ChannelType::Uint)));
+        let target = try!(factory.view_texture_as_render_target(&texture, 0, None));
+        let depth = try!(factory.create_depth_stencil_surface(width, height,
+                                                              texture::AaMode::Single));
+        Ok(Picker {
+            texture: texture,
+            target: target,
+            depth: depth,
+            pending: VecDeque::new(),
+            free: Vec::new(),
+        })
+    }
+
+    /// Get the target the object identifiers are written to.
+    pub fn get_target(&self) -> &handle::RenderTargetView<R, IdFormat> {
+        &self.target
+    }
+
+    /// Get the depth target used while drawing the objects.
+    pub fn get_depth(&self) -> &handle::DepthStencilView<R, format::Depth> {
+        &self.depth
+    }
+
+    /// Get the number of requests waiting for their result.
+    pub fn get_pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Get the number of download buffers kept for the next requests.
+    pub fn get_free_count(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Clear the targets before drawing the objects.
+    pub fn clear<C: CommandBuffer<R>>(&self, encoder: &mut Encoder<R, C>) {
+        encoder.clear(&self.target, NO_OBJECT);
+        encoder.clear_depth(&self.depth, 1.0);
+    }
+
+    /// Request the identifier of the object drawn at a position, to be
+    /// returned by a later `poll`.
+    pub fn request<F, C>(&mut self, factory: &mut F, encoder: &mut Encoder<R, C>,
+                         x: texture::Size, y: texture::Size)
+                         -> Result<(), RequestError> where
+        F: Factory<R>,
+        C: CommandBuffer<R>,
+    {
+        let buffer = match self.free.pop() {
+            Some(buffer) => buffer,
+            None => try!(factory.create_download_buffer(1)),
+        };
+        let info = texture::ImageInfoCommon {
+            xoffset: x,
+            yoffset: y,
+            zoffset: 0,
+            width: 1,
+            height: 1,
+            depth: 0,
+            format: (),
+            mipmap: 0,
+        };
+        let raw_info = info.convert(<IdFormat as format::Formatted>::get_format());
+        if let Err(e) = encoder.copy_texture_to_buffer_raw(self.texture.raw(), None, raw_info,
+                                                           buffer.raw(), 0) {
+            self.free.push(buffer);
+            return Err(e.into())
+        }
+        self.pending.push_back(Request {
+            buffer: buffer,
+            position: (x, y),
+            age: 0,
+        });
+        Ok(())
+    }
+
+    /// Complete the frame, and get the result of the latest request issued
+    /// at least `LATENCY` frames ago, if any. The command buffers using the
+    /// picker have to be submitted before calling it, once per frame.
+    pub fn poll<F: Factory<R>>(&mut self, factory: &mut F)
+                               -> Result<Option<PickResult>, mapping::Error> {
+        let mut result = None;
+        for request in self.pending.iter_mut() {
+            request.age += 1;
+        }
+        while self.pending.front().map_or(false, |r| r.age > LATENCY) {
+            let request = self.pending.pop_front().unwrap();
+            let read = factory.read_mapping(&request.buffer).map(|reader| reader[0]);
+            let id = match read {
+                Ok(id) => id,
+                Err(e) => {
+                    self.free.push(request.buffer);
+                    return Err(e)
+                }
+            };
+            result = Some(PickResult {
+                position: request.position,
+                object: decode_id(id),
+            });
+            self.free.push(request.buffer);
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Chains of full-screen post-processing passes.
+//!
+//! A `PostChain` owns two render targets used in turn: the scene is
+//! rendered into the first one, then each pass samples the output of the
+//! previous one, drawing a `FullScreenTriangle` into the other. The last
+//! pass writes to the final target instead, usually the main one.
+//!
+//! Each pass is a pipeline state with its own data, where the chain binds
+//! the source and the target before drawing:
+//!
+//! ```ignore
+//! gfx_defines! {
+//!     pipeline tonemap {
+//!         vbuf: gfx::VertexBuffer<gfx::post::Vertex> = (),
+//!         source: gfx::TextureSampler<[f32; 4]> = "t_Source",
+//!         exposure: gfx::Global<f32> = "u_Exposure",
+//!         out: gfx::RenderTarget<ColorFormat> = "Target0",
+//!     }
+//! }
+//!
+//! let triangle = FullScreenTriangle::new(&mut factory).unwrap();
+//! let mut chain = try!(PostChain::<_, _, Rgba16F>::new(&mut factory, triangle, width, height));
+//! chain.add_pass(bloom_pso, bloom_data, |data, stage| {
+//!     data.vbuf = stage.vertices.clone();
+//!     data.source = (stage.source.clone(), stage.sampler.clone());
+//!     data.out = stage.target.unwrap().clone();
+//! });
+//! chain.add_pass(tonemap_pso, tonemap::Data { out: main_color, .. }, |data, stage| {
+//!     data.vbuf = stage.vertices.clone();
+//!     data.source = (stage.source.clone(), stage.sampler.clone());
+//! });
+//! draw_scene(&mut encoder, chain.get_target());
+//! chain.execute(&mut encoder);
+//! ```
+
+use std::mem;
+use core::{buffer, format, handle, texture, Resources};
+use core::command::Buffer as CommandBuffer;
+use core::factory::{CombinedError, Factory};
+use core::format::{Formatted, RenderFormat, TextureFormat};
+use core::memory::{Bind, Pod};
+use encoder::Encoder;
+use mesh::generate;
+use pso::{PipelineData, PipelineState};
+use pso::buffer::{Element, ElemOffset, Structure};
+use slice::{IndexBuffer, Slice};
+
+/// GLSL 1.50 vertex shader of the full-screen triangle, passing the
+/// texture coordinates to the pixel shader as `v_TexCoord`.
+pub const VERTEX_SHADER_GLSL: &'static [u8] = b"
+#version 150 core
+in vec2 a_Pos;
+in vec2 a_TexCoord;
+out vec2 v_TexCoord;
+void main() {
+    v_TexCoord = a_TexCoord;
+    gl_Position = vec4(a_Pos, 0.0, 1.0);
+}
+";
+
+/// GLSL 1.50 pixel shader copying `t_Source`, the simplest pass.
+pub const COPY_SHADER_GLSL: &'static [u8] = b"
+#version 150 core
+uniform sampler2D t_Source;
+in vec2 v_TexCoord;
+out vec4 Target0;
+void main() {
+    Target0 = texture(t_Source, v_TexCoord);
+}
+";
+
+/// A corner of the full-screen triangle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Vertex {
+    /// Position in clip space, bound to `a_Pos`
+    pub pos: [f32; 2],
+    /// Texture coordinates, bound to `a_TexCoord`
+    pub tex_coord: [f32; 2],
+}
+
+unsafe impl Pod for Vertex {}
+
+impl Structure<format::Format> for Vertex {
+    fn query(name: &str) -> Option<Element<format::Format>> {
+        let (format, offset) = match name {
+            "a_Pos" => (<[f32; 2] as Formatted>::get_format(), 0),
+            "a_TexCoord" => (<[f32; 2] as Formatted>::get_format(),
+                             2 * mem::size_of::<f32>() as ElemOffset),
+            _ => return None,
+        };
+        Some(Element {
+            format: format,
+            offset: offset,
+        })
+    }
+}
+
+/// A single triangle covering the screen, cheaper than a quad as no pixel
+/// is shaded twice along a diagonal. It can be shared by several chains.
+#[derive(Derivative)]
+#[derivative(Clone, Debug)]
+pub struct FullScreenTriangle<R: Resources> {
+    /// The vertex buffer
+    pub vertices: handle::Buffer<R, Vertex>,
+    /// The slice drawing it
+    pub slice: Slice<R>,
+}
+
+impl<R: Resources> FullScreenTriangle<R> {
+    /// Create the vertex buffer of the triangle, out of the one of
+    /// `mesh::generate::fullscreen_triangle`.
+    pub fn new<F: Factory<R> + ?Sized>(factory: &mut F)
+               -> Result<FullScreenTriangle<R>, buffer::CreationError> {
+        let corners: Vec<Vertex> = generate::fullscreen_triangle().vertices.iter()
+            .map(|v| Vertex {
+                pos: [v.pos[0], v.pos[1]],
+                tex_coord: v.tex_coord,
+            })
+            .collect();
+        let vertices = try!(factory.create_buffer_immutable(&corners, buffer::Role::Vertex,
+                                                            Bind::empty()));
+        Ok(FullScreenTriangle {
+            vertices: vertices,
+            slice: Slice {
+                start: 0,
+                end: corners.len() as u32,
+                base_vertex: 0,
+                instances: None,
+                buffer: IndexBuffer::Auto,
+            },
+        })
+    }
+}
+
+/// What a pass reads and writes, to be bound to its pipeline data.
+pub struct PostStage<'a, R: Resources + 'a, T: TextureFormat + 'a> {
+    /// The index of the pass in the chain
+    pub index: usize,
+    /// The vertex buffer of the full-screen triangle
+    pub vertices: &'a handle::Buffer<R, Vertex>,
+    /// The output of the previous pass, or the scene for the first one
+    pub source: &'a handle::ShaderResourceView<R, T::View>,
+    /// A bilinear sampler clamping to the edges
+    pub sampler: &'a handle::Sampler<R>,
+    /// The target to draw into, or `None` for the last pass, which draws
+    /// into a target of its own data.
+    pub target: Option<&'a handle::RenderTargetView<R, T>>,
+}
+
+struct PingPong<R: Resources, T: TextureFormat> {
+    resource: handle::ShaderResourceView<R, T::View>,
+    target: handle::RenderTargetView<R, T>,
+}
+
+/// A chain of full-screen passes, with the intermediate targets of format `T`.
+pub struct PostChain<R: Resources, C: CommandBuffer<R>, T: TextureFormat> {
+    triangle: FullScreenTriangle<R>,
+    sampler: handle::Sampler<R>,
+    targets: [PingPong<R, T>; 2],
+    passes: Vec<Box<FnMut(&mut Encoder<R, C>, &PostStage<R, T>)>>,
+}
+
+fn create_ping_pong<R, F, T>(factory: &mut F, width: texture::Size, height: texture::Size)
+                             -> Result<[PingPong<R, T>; 2], CombinedError> where
+    R: Resources,
+    F: Factory<R> + ?Sized,
+    T: RenderFormat + TextureFormat,
+{
+    let (_, resource0, target0) = try!(factory.create_render_target::<T>(width, height));
+    let (_, resource1, target1) = try!(factory.create_render_target::<T>(width, height));
+    Ok([PingPong { resource: resource0, target: target0 },
+        PingPong { resource: resource1, target: target1 }])
+}
+
+impl<R, C, T> PostChain<R, C, T> where
+    R: Resources,
+    C: CommandBuffer<R>,
+    T: RenderFormat + TextureFormat,
+{
+    /// Create an empty chain, with intermediate targets of `width` by
+    /// `height`.
+    pub fn new<F: Factory<R> + ?Sized>(factory: &mut F, triangle: FullScreenTriangle<R>,
+                                       width: texture::Size, height: texture::Size)
+                                       -> Result<PostChain<R, C, T>, CombinedError> {
+        let targets = try!(create_ping_pong(factory, width, height));
+        let sampler = factory.create_sampler(texture::SamplerInfo::new(
+            texture::FilterMethod::Bilinear, texture::WrapMode::Clamp));
+        Ok(PostChain {
+            triangle: triangle,
+            sampler: sampler,
+            targets: targets,
+            passes: Vec::new(),
+        })
+    }
+
+    /// Recreate the intermediate targets with a new size, like the one of
+    /// the window.
+    pub fn resize<F: Factory<R> + ?Sized>(&mut self, factory: &mut F,
+                                          width: texture::Size, height: texture::Size)
+                                          -> Result<(), CombinedError> {
+        self.targets = try!(create_ping_pong(factory, width, height));
+        Ok(())
+    }
+
+    /// Get the target to render the scene into, sampled by the first pass.
+    pub fn get_target(&self) -> &handle::RenderTargetView<R, T> {
+        &self.targets[0].target
+    }
+
+    /// Get the number of passes.
+    pub fn len(&self) -> usize {
+        self.passes.len()
+    }
+
+    /// Check if the chain has no pass.
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// Append a pass, drawing the full-screen triangle with `pso` and
+    /// `data`, after `bind` updated the latter for the current stage.
+    pub fn add_pass<D, B>(&mut self, pso: PipelineState<R, D::Meta>, mut data: D, mut bind: B)
+                          -> &mut Self where
+        D: PipelineData<R> + 'static,
+        D::Meta: 'static,
+        B: FnMut(&mut D, &PostStage<R, T>) + 'static,
+    {
+        let slice = self.triangle.slice.clone();
+        self.passes.push(Box::new(move |encoder: &mut Encoder<R, C>, stage: &PostStage<R, T>| {
+            bind(&mut data, stage);
+            encoder.draw(&slice, &pso, &data);
+        }));
+        self
+    }
+
+    /// Draw the passes in order. The scene has to be rendered into the
+    /// target of the chain first.
+    pub fn execute(&mut self, encoder: &mut Encoder<R, C>) {
+        let count = self.passes.len();
+        for (i, pass) in self.passes.iter_mut().enumerate() {
+            let stage = PostStage {
+                index: i,
+                vertices: &self.triangle.vertices,
+                source: &self.targets[i % 2].resource,
+                sampler: &self.sampler,
+                target: if i + 1 < count {
+                    Some(&self.targets[(i + 1) % 2].target)
+                } else {
+                    None
+                },
+            };
+            pass(encoder, &stage);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GLSL source preprocessing.
+//!
+//! GLSL has no `#include`, so shaders sharing code have to be assembled
+//! before being compiled. The `Preprocessor` splices the included files in
+//! place, as given by an `IncludeResolver`, and injects a set of
+//! `#define`s right after the `#version` line, so that a single source can
+//! be compiled into several permutations:
+//!
+//! ```ignore
+//! let mut pp = Preprocessor::new(|name: &str| library.get(name).cloned());
+//! let source = try!(pp.process("shadow.glsl", SHADOW_SRC, &[("PCF_TAPS", "4")]));
+//! let vs = try!(factory.create_shader_vertex(source.as_bytes()));
+//! ```
+//!
+//! Included files are looked up by the exact text between the quotes or
+//! angle brackets, and included at most once if they start with
+//! `#pragma once`.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+/// A source of the files included by the shaders.
+pub trait IncludeResolver {
+    /// Get the source of an included file.
+    fn resolve(&mut self, name: &str) -> Option<String>;
+}
+
+impl<F: FnMut(&str) -> Option<String>> IncludeResolver for F {
+    fn resolve(&mut self, name: &str) -> Option<String> {
+        self(name)
+    }
+}
+
+/// An error while preprocessing a shader, in a given file and line.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PreprocessError {
+    /// The resolver doesn't know the included file.
+    NotFound(String, usize, String),
+    /// The file includes itself, directly or not.
+    Recursive(String, usize, String),
+    /// The directive can't be parsed.
+    Syntax(String, usize),
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PreprocessError::NotFound(ref file, line, ref name) |
+            PreprocessError::Recursive(ref file, line, ref name) =>
+                write!(f, "{} at {}:{}: {}", self.description(), file, line, name),
+            PreprocessError::Syntax(ref file, line) =>
+                write!(f, "{} at {}:{}", self.description(), file, line),
+        }
+    }
+}
+
+impl Error for PreprocessError {
+    fn description(&self) -> &str {
+        match *self {
+            PreprocessError::NotFound(..) => "Included file not found",
+            PreprocessError::Recursive(..) => "Recursive include",
+            PreprocessError::Syntax(..) => "Invalid directive",
+        }
+    }
+}
+
+/// Expands the includes of GLSL shaders and injects defines into them.
+#[derive(Debug)]
+pub struct Preprocessor<I> {
+    resolver: I,
+    stack: Vec<String>,
+    once: HashSet<String>,
+}
+
+impl<I: IncludeResolver> Preprocessor<I> {
+    /// Create a preprocessor getting the included files from `resolver`.
+    pub fn new(resolver: I) -> Preprocessor<I> {
+        Preprocessor {
+            resolver: resolver,
+            stack: Vec::new(),
+            once: HashSet::new(),
+        }
+    }
+
+    /// Get the resolver back.
+    pub fn into_resolver(self) -> I {
+        self.resolver
+    }
+
+    /// Preprocess the source of a shader, known as `name` in the errors,
+    /// with the given `(name, value)` pairs defined.
+    pub fn process(&mut self, name: &str, source: &str, defines: &[(&str, &str)])
+                   -> Result<String, PreprocessError> {
+        let mut body = String::with_capacity(source.len());
+        self.stack.clear();
+        self.once.clear();
+        let result = self.expand(name, source, &mut body);
+        self.stack.clear();
+        try!(result);
+
+        let mut out = String::with_capacity(body.len() + 32 * defines.len());
+        let rest = match body.find("#version") {
+            // only comments and blanks may precede the version
+            Some(pos) if is_blank(&body[..pos]) => {
+                let end = body[pos..].find('\n').map_or(body.len(), |e| pos + e + 1);
+                out.push_str(&body[..end]);
+                if !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                &body[end..]
+            },
+            _ => &body[..],
+        };
+        for &(name, value) in defines.iter() {
+            out.push_str("#define ");
+            out.push_str(name);
+            if !value.is_empty() {
+                out.push(' ');
+                out.push_str(value);
+            }
+            out.push('\n');
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    fn expand(&mut self, name: &str, source: &str, out: &mut String)
+              -> Result<(), PreprocessError> {
+        self.stack.push(name.to_string());
+        for (i, line) in source.lines().enumerate() {
+            let directive = match parse_directive(line) {
+                Some(d) => d,
+                None => {
+                    out.push_str(line);
+                    out.push('\n');
+                    continue
+                },
+            };
+            if directive.starts_with("pragma") && directive[6..].trim() == "once" {
+                self.once.insert(name.to_string());
+                continue
+            }
+            if !directive.starts_with("include") {
+                out.push_str(line);
+                out.push('\n');
+                continue
+            }
+            let included = match parse_include(&directive[7..]) {
+                Some(n) => n,
+                None => return Err(PreprocessError::Syntax(name.to_string(), i + 1)),
+            };
+            if self.once.contains(included) {
+                continue
+            }
+            if self.stack.iter().any(|s| s == included) {
+                return Err(PreprocessError::Recursive(name.to_string(), i + 1,
+                                                      included.to_string()))
+            }
+            let code = match self.resolver.resolve(included) {
+                Some(code) => code,
+                None => return Err(PreprocessError::NotFound(name.to_string(), i + 1,
+                                                             included.to_string())),
+            };
+            try!(self.expand(included, &code, out));
+        }
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+/// Get the directive of a line, without the `#`.
+fn parse_directive(line: &str) -> Option<&str> {
+    let line = line.trim_left();
+    if line.starts_with('#') {
+        Some(line[1..].trim_left())
+    } else {
+        None
+    }
+}
+
+/// Get the file name of an `#include`, between quotes or angle brackets.
+fn parse_include(arg: &str) -> Option<&str> {
+    let arg = arg.trim();
+    let close = match arg.chars().next() {
+        Some('"') => '"',
+        Some('<') => '>',
+        _ => return None,
+    };
+    arg[1..].find(close).and_then(|end| {
+        if arg[end + 2..].trim().is_empty() && end != 0 {
+            Some(&arg[1..end + 1])
+        } else {
+            None
+        }
+    })
+}
+
+/// Check that a piece of source has nothing but whitespace and comments.
+fn is_blank(code: &str) -> bool {
+    let mut rest = code.trim_left();
+    while !rest.is_empty() {
+        if rest.starts_with("//") {
+            rest = rest.find('\n').map_or("", |e| &rest[e..]);
+        } else if rest.starts_with("/*") {
+            match rest.find("*/") {
+                Some(e) => rest = &rest[e + 2..],
+                None => return false,
+            }
+        } else {
+            return false
+        }
+        rest = rest.trim_left();
+    }
+    true
+}
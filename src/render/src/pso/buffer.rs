@@ -14,12 +14,14 @@
 
 //! Buffer components for a PSO.
 
+use std::cmp;
 use std::marker::PhantomData;
-use core::{ConstantBufferSlot, Resources, MAX_VERTEX_ATTRIBUTES};
-use core::{handle, pso, shade};
+use core::{AtomicCounterSlot, ConstantBufferSlot, Resources, MAX_VERTEX_ATTRIBUTES};
+use core::{buffer, handle, pso, shade};
 use core::memory::Typed;
 use core::format::Format;
-use shade::{ToUniform, Usage};
+use shade::{ToUniform, ToUniformArray, Usage};
+use validate::VertexBinding;
 use super::{DataLink, DataBind, ElementError, RawDataSet, AccessInfo};
 
 pub use core::pso::{BufferIndex, Element, ElemOffset, ElemStride, InstanceRate};
@@ -77,13 +79,23 @@ pub type VertexBuffer<T> = VertexBufferCommon<T, NonInstanced>;
 /// Instance buffer component. Same as the vertex buffer but advances per instance.
 pub type InstanceBuffer<T> = VertexBufferCommon<T, Instanced>;
 
+/// Vertex buffer component bound at an offset, counted in elements.
+/// Allows several meshes, or several frames of streamed data, to share a
+/// single buffer.
+///
+/// - init: same as `VertexBuffer` or `InstanceBuffer`
+/// - data: `(Buffer<T>, usize)`
+#[derive(Derivative)]
+#[derivative(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct VertexBufferOffset<T, I=NonInstanced>(VertexBufferCommon<T, I>);
+
 /// Raw vertex/instance buffer component. Can be used when the formats of vertex attributes
 /// are not known at compile time.
 ///
 /// - init: `(&[&str, element], stride, inst_rate)`
 /// - data: `RawBuffer`
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct RawVertexBuffer(Option<BufferIndex>, AttributeSlotSet);
+pub struct RawVertexBuffer(Option<BufferIndex>, AttributeSlotSet, Option<pso::VertexBufferDesc>);
 
 /// Constant buffer component.
 ///
@@ -97,6 +109,16 @@ pub struct ConstantBuffer<T: Structure<shade::ConstFormat>>(
     PhantomData<T>
 );
 
+/// Constant buffer component bound at an offset, counted in bytes, since
+/// it has to be a multiple of `constant_buffer_offset_alignment`. Allows
+/// many small blocks to share a single buffer, see `ConstantAllocator`.
+///
+/// - init: `&str` = name of the buffer
+/// - data: `(Buffer<T>, usize)`
+#[derive(Derivative)]
+#[derivative(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ConstantBufferOffset<T: Structure<shade::ConstFormat>>(ConstantBuffer<T>);
+
 /// Raw constant buffer component.
 ///
 /// - init: `&str` = name of the buffer
@@ -125,6 +147,46 @@ pub struct Global<T: ToUniform>(
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct RawGlobal(Option<shade::Location>);
 
+/// Per-view global constant component, for stereo rendering. Describes
+/// a uniform array holding a value for each view, like
+/// `uniform mat4 u_ViewProj[2];`, see the `stereo` module.
+///
+/// - init: `&str` = name of the constant
+/// - data: `[T; 2]` = values of the left and right views
+#[derive(Derivative)]
+#[derivative(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PerView<T: ToUniform>(
+    Option<shade::Location>,
+    #[derivative(Hash = "ignore", PartialEq = "ignore")]
+    PhantomData<T>
+);
+
+/// Global constant array component, for a dynamically sized list bound to
+/// a fixed size array, like `uniform vec4 u_Lights[8];`. The number of
+/// elements bound is written to the optional count constant, like
+/// `uniform int u_LightCount;`, and the elements past the size of the array
+/// are ignored.
+///
+/// - init: `(&str, &str)` = names of the array and of the count constant
+/// - data: `A` = list of values, like `Vec<[f32; 4]>`
+#[derive(Derivative)]
+#[derivative(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct GlobalArray<A: ToUniformArray>(
+    Option<(shade::Location, usize)>,
+    Option<shade::Location>,
+    #[derivative(Hash = "ignore", PartialEq = "ignore")]
+    PhantomData<A>
+);
+
+/// Atomic counter buffer component. Since the counters of a buffer are
+/// anonymous in the shader, it is found by the name of one of its counters.
+/// The counters can be reset with `Encoder::reset_counters`.
+///
+/// - init: `&str` = name of a counter
+/// - data: `Buffer<u32>`
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct AtomicCounterBuffer(Option<(Usage, AtomicCounterSlot)>);
+
 fn match_attribute(attr: &shade::AttributeVar, fmt: Format) -> bool {
     use core::shade::{BaseType, ContainerType};
     use core::format::ChannelType;
@@ -175,10 +237,12 @@ impl<'a,
         use std::mem;
         (self.0).0 = Some(index);
         let rate = I::get_rate(init);
-        Some(pso::VertexBufferDesc {
+        let desc = pso::VertexBufferDesc {
             stride: mem::size_of::<T>() as ElemStride,
             rate: rate as InstanceRate,
-        })
+        };
+        (self.0).2 = Some(desc);
+        Some(desc)
     }
     fn link_input(&mut self, at: &shade::AttributeVar, _: &Self::Init) ->
                   Option<Result<pso::AttributeDesc, Format>> {
@@ -199,7 +263,69 @@ impl<R: Resources, T, I> DataBind<R> for VertexBufferCommon<T, I> {
     }
 }
 
+impl<'a,
+    T: Structure<Format>,
+    I: ToInstanceRate + 'a,
+> DataLink<'a> for VertexBufferOffset<T, I> {
+    type Init = I::Init;
+    fn new() -> Self {
+        VertexBufferOffset(DataLink::new())
+    }
+    fn is_active(&self) -> bool {
+        self.0.is_active()
+    }
+    fn link_vertex_buffer(&mut self, index: BufferIndex, init: &Self::Init)
+                          -> Option<pso::VertexBufferDesc> {
+        self.0.link_vertex_buffer(index, init)
+    }
+    fn link_input(&mut self, at: &shade::AttributeVar, init: &Self::Init) ->
+                  Option<Result<pso::AttributeDesc, Format>> {
+        self.0.link_input(at, init)
+    }
+}
+
+impl<R: Resources, T, I> DataBind<R> for VertexBufferOffset<T, I> {
+    type Data = (handle::Buffer<R, T>, usize);
+    fn bind_to(&self,
+               out: &mut RawDataSet<R>,
+               data: &Self::Data,
+               man: &mut handle::Manager<R>,
+               access: &mut AccessInfo<R>) {
+        use std::mem;
+        let offset = data.1 * mem::size_of::<T>();
+        ((self.0).0).bind_at(out, data.0.raw(), offset, man, access)
+    }
+}
+
 impl RawVertexBuffer {
+    fn bind_at<R: Resources>(&self,
+               out: &mut RawDataSet<R>,
+               data: &handle::RawBuffer<R>,
+               offset: pso::BufferOffset,
+               man: &mut handle::Manager<R>,
+               access: &mut AccessInfo<R>) {
+        let value = Some((man.ref_buffer(data).clone(), offset));
+        let binding = self.2.map(|desc| VertexBinding {
+            size: data.get_info().size.saturating_sub(offset),
+            stride: desc.stride,
+            rate: desc.rate,
+        });
+        for i in 0 .. MAX_VERTEX_ATTRIBUTES {
+            if (self.1 & (1<<i)) != 0 {
+                out.vertex_buffers.0[i] = value;
+                if let Some(ref mut bindings) = out.bindings {
+                    bindings.vertex_buffers[i] = binding;
+                }
+            }
+        }
+        if self.1 != 0 {
+            if let Some(ref mut bindings) = out.bindings {
+                bindings.add_buffer(buffer::Role::Vertex, data.get_info().role);
+            }
+            access.buffer_read(data);
+        }
+    }
+
     fn link(&mut self, at: &shade::AttributeVar, el: Element<Format>)
             -> Result<pso::AttributeDesc, Format> {
         self.1 |= 1 << (at.slot as AttributeSlotSet);
@@ -214,7 +340,7 @@ impl RawVertexBuffer {
 impl<'a> DataLink<'a> for RawVertexBuffer {
     type Init = (&'a [(&'a str, Element<Format>)], ElemStride, InstanceRate);
     fn new() -> Self {
-        RawVertexBuffer(None, 0)
+        RawVertexBuffer(None, 0, None)
     }
     fn is_active(&self) -> bool {
         self.0.is_some()
@@ -222,10 +348,12 @@ impl<'a> DataLink<'a> for RawVertexBuffer {
     fn link_vertex_buffer(&mut self, index: BufferIndex, init: &Self::Init)
                           -> Option<pso::VertexBufferDesc> {
         self.0 = Some(index);
-        Some(pso::VertexBufferDesc {
+        let desc = pso::VertexBufferDesc {
             stride: init.1,
             rate: init.2,
-        })
+        };
+        self.2 = Some(desc);
+        Some(desc)
     }
     fn link_input(&mut self, at: &shade::AttributeVar, init: &Self::Init) ->
                   Option<Result<pso::AttributeDesc, Format>> {
@@ -241,13 +369,7 @@ impl<R: Resources> DataBind<R> for RawVertexBuffer {
                data: &Self::Data,
                man: &mut handle::Manager<R>,
                access: &mut AccessInfo<R>) {
-        let value = Some((man.ref_buffer(data).clone(), 0));
-        for i in 0 .. MAX_VERTEX_ATTRIBUTES {
-            if (self.1 & (1<<i)) != 0 {
-                out.vertex_buffers.0[i] = value;
-            }
-        }
-        if self.1 != 0 { access.buffer_read(data); }
+        self.bind_at(out, data, 0, man, access)
     }
 }
 
@@ -296,6 +418,35 @@ DataBind<R> for ConstantBuffer<T> {
     }
 }
 
+impl<'a, T: Structure<shade::ConstFormat>>
+DataLink<'a> for ConstantBufferOffset<T> {
+    type Init = &'a str;
+    fn new() -> Self {
+        ConstantBufferOffset(DataLink::new())
+    }
+    fn is_active(&self) -> bool {
+        self.0.is_active()
+    }
+    fn link_constant_buffer<'b>(&mut self, cb: &'b shade::ConstantBufferVar, init: &Self::Init)
+                            -> Option<Result<pso::ConstantBufferDesc, ElementError<&'b str>>> {
+        self.0.link_constant_buffer(cb, init)
+    }
+}
+
+impl<R: Resources, T: Structure<shade::ConstFormat>>
+DataBind<R> for ConstantBufferOffset<T> {
+    type Data = (handle::Buffer<R, T>, usize);
+    fn bind_to(&self,
+               out: &mut RawDataSet<R>,
+               data: &Self::Data,
+               man: &mut handle::Manager<R>,
+               access: &mut AccessInfo<R>) {
+        use std::mem;
+        let range = Some((data.1, mem::size_of::<T>()));
+        ((self.0).0).bind_at(out, data.0.raw(), range, man, access)
+    }
+}
+
 impl<'a> DataLink<'a> for RawConstantBuffer {
     type Init = &'a str;
     fn new() -> Self {
@@ -322,9 +473,23 @@ impl<R: Resources> DataBind<R> for RawConstantBuffer {
                data: &Self::Data,
                man: &mut handle::Manager<R>,
                access: &mut AccessInfo<R>) {
+        self.bind_at(out, data, None, man, access)
+    }
+}
+
+impl RawConstantBuffer {
+    fn bind_at<R: Resources>(&self,
+               out: &mut RawDataSet<R>,
+               data: &handle::RawBuffer<R>,
+               range: Option<(pso::BufferOffset, usize)>,
+               man: &mut handle::Manager<R>,
+               access: &mut AccessInfo<R>) {
         if let Some((usage, slot)) = self.0 {
             let buf = man.ref_buffer(data).clone();
-            out.constant_buffers.push(pso::ConstantBufferParam(buf, usage, slot));
+            out.constant_buffers.push(pso::ConstantBufferParam(buf, usage, slot, range));
+            if let Some(ref mut bindings) = out.bindings {
+                bindings.add_buffer(buffer::Role::Constant, data.get_info().role);
+            }
             access.buffer_read(data)
         }
     }
@@ -388,3 +553,122 @@ impl<R: Resources> DataBind<R> for RawGlobal {
         }
     }
 }
+
+impl<'a, T: ToUniform + Default> DataLink<'a> for PerView<T> {
+    type Init = &'a str;
+    fn new() -> Self {
+        PerView(None, PhantomData)
+    }
+    fn is_active(&self) -> bool {
+        self.0.is_some()
+    }
+    fn link_global_constant(&mut self, var: &shade::ConstVar, init: &Self::Init) ->
+                            Option<Result<(), shade::CompatibilityError>> {
+        if !shade::is_named(&var.name, init) {
+            return None
+        }
+        self.0 = Some(var.location);
+        if var.count < 2 {
+            return Some(Err(shade::CompatibilityError::ErrorArraySize))
+        }
+        let element = shade::ConstVar { count: 1, .. var.clone() };
+        Some(element.is_compatible(&ToUniform::convert(T::default())))
+    }
+}
+
+impl<R: Resources, T: ToUniform> DataBind<R> for PerView<T> {
+    type Data = [T; 2];
+    fn bind_to(&self,
+               out: &mut RawDataSet<R>,
+               data: &Self::Data,
+               _: &mut handle::Manager<R>,
+               _: &mut AccessInfo<R>) {
+        if let Some(loc) = self.0 {
+            // the elements of a uniform array have consecutive locations
+            for (i, value) in data.iter().enumerate() {
+                out.global_constants.push((loc + i, value.convert()));
+            }
+        }
+    }
+}
+
+impl<'a, A> DataLink<'a> for GlobalArray<A> where
+    A: ToUniformArray,
+    A::Element: Default,
+{
+    type Init = (&'a str, &'a str);
+    fn new() -> Self {
+        GlobalArray(None, None, PhantomData)
+    }
+    fn is_active(&self) -> bool {
+        self.0.is_some()
+    }
+    fn link_global_constant(&mut self, var: &shade::ConstVar, init: &Self::Init) ->
+                            Option<Result<(), shade::CompatibilityError>> {
+        if shade::is_named(&var.name, init.0) {
+            self.0 = Some((var.location, var.count));
+            let element = shade::ConstVar { count: 1, .. var.clone() };
+            Some(element.is_compatible(&ToUniform::convert(A::Element::default())))
+        } else if var.name.as_str() == init.1 {
+            self.1 = Some(var.location);
+            Some(var.is_compatible(&ToUniform::convert(0i32)))
+        } else {
+            None
+        }
+    }
+}
+
+impl<R: Resources, A: ToUniformArray> DataBind<R> for GlobalArray<A> {
+    type Data = A;
+    fn bind_to(&self,
+               out: &mut RawDataSet<R>,
+               data: &Self::Data,
+               _: &mut handle::Manager<R>,
+               _: &mut AccessInfo<R>) {
+        if let Some((loc, count)) = self.0 {
+            let elements = data.get_elements();
+            let count = cmp::min(elements.len(), count);
+            for (i, value) in elements[.. count].iter().enumerate() {
+                out.global_constants.push((loc + i, value.convert()));
+            }
+            if let Some(count_loc) = self.1 {
+                out.global_constants.push((count_loc, ToUniform::convert(count as i32)));
+            }
+        }
+    }
+}
+
+impl<'a> DataLink<'a> for AtomicCounterBuffer {
+    type Init = &'a str;
+    fn new() -> Self {
+        AtomicCounterBuffer(None)
+    }
+    fn is_active(&self) -> bool {
+        self.0.is_some()
+    }
+    fn link_atomic_counter_buffer(&mut self, acb: &shade::AtomicCounterBufferVar, init: &Self::Init)
+                                  -> Option<pso::AtomicCounterBufferDesc> {
+        if acb.get_counter(init).is_some() {
+            self.0 = Some((acb.usage, acb.slot));
+            Some(acb.usage)
+        } else {
+            None
+        }
+    }
+}
+
+impl<R: Resources> DataBind<R> for AtomicCounterBuffer {
+    type Data = handle::Buffer<R, u32>;
+    fn bind_to(&self,
+               out: &mut RawDataSet<R>,
+               data: &Self::Data,
+               man: &mut handle::Manager<R>,
+               access: &mut AccessInfo<R>) {
+        if let Some((usage, slot)) = self.0 {
+            let buf = man.ref_buffer(data.raw()).clone();
+            out.atomic_counter_buffers.push(pso::AtomicCounterBufferParam(buf, usage, slot));
+            access.buffer_read(data.raw());
+            access.buffer_write(data.raw());
+        }
+    }
+}
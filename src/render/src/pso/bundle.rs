@@ -1,6 +1,9 @@
 //! Combine slice data with pipeline state.
 //!
 //! Suitable for use when PSO is always used with the same one slice.
+//! When many entities share the same few slices and PSOs, they can instead
+//! be stored once in a `BundleContext`, with each entity only keeping a
+//! `CoreBundle` of indices into it.
 
 use { Resources, Slice, PipelineState, Encoder, CommandBuffer };
 use super::PipelineData;
@@ -32,3 +35,80 @@ impl<R: Resources, Data: PipelineData<R>> Bundle<R, Data> {
         encoder.draw(&self.slice, &self.pso, &self.data);
     }
 }
+
+/// A slim bundle, referring to its slice, PSO and data by their index in
+/// a `BundleContext`. Being `Copy` and ordered by PSO first, then data,
+/// it can be stored per entity and sorted cheaply.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct CoreBundle {
+    /// Index of the pipeline state
+    pub pso: u32,
+    /// Index of the pipeline data
+    pub data: u32,
+    /// Index of the slice
+    pub slice: u32,
+}
+
+/// Shared storage of the slices, PSOs and data used by `CoreBundle`s.
+pub struct BundleContext<R: Resources, Data: PipelineData<R>> {
+    slices: Vec<Slice<R>>,
+    psos: Vec<PipelineState<R, Data::Meta>>,
+    data: Vec<Data>,
+}
+
+impl<R: Resources, Data: PipelineData<R>> BundleContext<R, Data> {
+    /// Create an empty context.
+    pub fn new() -> Self {
+        BundleContext {
+            slices: Vec::new(),
+            psos: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Add a slice, returning its index.
+    pub fn add_slice(&mut self, slice: Slice<R>) -> u32 {
+        self.slices.push(slice);
+        self.slices.len() as u32 - 1
+    }
+
+    /// Add a pipeline state, returning its index.
+    pub fn add_pso(&mut self, pso: PipelineState<R, Data::Meta>) -> u32 {
+        self.psos.push(pso);
+        self.psos.len() as u32 - 1
+    }
+
+    /// Add pipeline data, returning its index.
+    pub fn add_data(&mut self, data: Data) -> u32 {
+        self.data.push(data);
+        self.data.len() as u32 - 1
+    }
+
+    /// Get a slice by index.
+    pub fn get_slice(&self, index: u32) -> &Slice<R> {
+        &self.slices[index as usize]
+    }
+
+    /// Get a pipeline state by index.
+    pub fn get_pso(&self, index: u32) -> &PipelineState<R, Data::Meta> {
+        &self.psos[index as usize]
+    }
+
+    /// Get pipeline data by index.
+    pub fn get_data(&self, index: u32) -> &Data {
+        &self.data[index as usize]
+    }
+
+    /// Get pipeline data by index, to be modified in place, affecting
+    /// all the bundles referring to it.
+    pub fn get_data_mut(&mut self, index: u32) -> &mut Data {
+        &mut self.data[index as usize]
+    }
+
+    /// Draw a bundle of this context using encoder.
+    pub fn encode<C>(&self, bundle: CoreBundle, encoder: &mut Encoder<R, C>) where
+        C: CommandBuffer<R> {
+        encoder.draw(self.get_slice(bundle.slice), self.get_pso(bundle.pso),
+                     self.get_data(bundle.data));
+    }
+}
@@ -48,8 +48,9 @@ use std::default::Default;
 use std::error::Error;
 use std::fmt;
 use core as c;
-pub use core::pso::Descriptor;
+pub use core::pso::{Descriptor, PointInfo, PointOrigin};
 pub use core::command::AccessInfo;
+use validate::Bindings;
 
 /// A complete set of raw data that needs to be specified at run-time
 /// whenever we draw something with a PSO. This is what "data" struct
@@ -65,9 +66,12 @@ pub struct RawDataSet<R: c::Resources>{
     pub resource_views: Vec<c::pso::ResourceViewParam<R>>,
     pub unordered_views: Vec<c::pso::UnorderedViewParam<R>>,
     pub samplers: Vec<c::pso::SamplerParam<R>>,
+    pub atomic_counter_buffers: Vec<c::pso::AtomicCounterBufferParam<R>>,
     pub pixel_targets: c::pso::PixelTargetSet<R>,
     pub ref_values: c::state::RefValues,
     pub scissor: c::target::Rect,
+    /// The resources bound, recorded for validation if it's enabled.
+    pub bindings: Option<Bindings>,
 }
 
 impl<R: c::Resources> RawDataSet<R> {
@@ -80,9 +84,11 @@ impl<R: c::Resources> RawDataSet<R> {
             resource_views: Vec::new(),
             unordered_views: Vec::new(),
             samplers: Vec::new(),
+            atomic_counter_buffers: Vec::new(),
             pixel_targets: c::pso::PixelTargetSet::new(),
             ref_values: Default::default(),
             scissor: c::target::Rect{x:0, y:0, w:1, h:1},
+            bindings: None,
         }
     }
     /// Clear all contained data.
@@ -93,9 +99,13 @@ impl<R: c::Resources> RawDataSet<R> {
         self.resource_views.clear();
         self.unordered_views.clear();
         self.samplers.clear();
+        self.atomic_counter_buffers.clear();
         self.pixel_targets = c::pso::PixelTargetSet::new();
         self.ref_values = Default::default();
         self.scissor = c::target::Rect{x:0, y:0, w:1, h:1};
+        if let Some(ref mut bindings) = self.bindings {
+            bindings.clear();
+        }
     }
 }
 
@@ -180,6 +190,8 @@ pub enum InitError<S> {
     UnorderedView(S, Option<()>),
     /// Sampler mismatch.
     Sampler(S, Option<()>),
+    /// Atomic counter buffer mismatch, named after its first counter.
+    AtomicCounterBuffer(S, Option<()>),
     /// Pixel target mismatch.
     PixelExport(S, Option<c::format::Format>),
 }
@@ -194,6 +206,7 @@ impl<'a> From<InitError<&'a str>> for InitError<String> {
             ResourceView(s, v) => ResourceView(s.to_owned(), v),
             UnorderedView(s, v) => UnorderedView(s.to_owned(), v),
             Sampler(s, v) => Sampler(s.to_owned(), v),
+            AtomicCounterBuffer(s, v) => AtomicCounterBuffer(s.to_owned(), v),
             PixelExport(s, v) => PixelExport(s.to_owned(), v),
         }
     }
@@ -210,6 +223,7 @@ impl<S: fmt::Debug + fmt::Display> fmt::Display for InitError<S> {
             ResourceView(ref name, opt) => write!(f, "{}: ({}, {:?})", desc, name, opt),
             UnorderedView(ref name, opt) => write!(f, "{}: ({}, {:?})", desc, name, opt),
             Sampler(ref name, opt) => write!(f, "{}: ({}, {:?})", desc, name, opt),
+            AtomicCounterBuffer(ref name, opt) => write!(f, "{}: ({}, {:?})", desc, name, opt),
             PixelExport(ref name, format) => write!(f, "{}: ({}, {:?})", desc, name, format),
         }
     }
@@ -231,6 +245,8 @@ impl<S: fmt::Debug + fmt::Display> Error for InitError<S> {
             UnorderedView(..) => "Unordered access view mismatch",
             Sampler(_, None) => "Sampler not found",
             Sampler(..) => "Sampler mismatch",
+            AtomicCounterBuffer(_, None) => "Atomic counter buffer not found",
+            AtomicCounterBuffer(..) => "Atomic counter buffer mismatch",
             PixelExport(_, None) => "Pixel target not found",
             PixelExport(..) => "Pixel target mismatch",
         }
@@ -285,6 +301,10 @@ impl<R: c::Resources, M> PipelineState<R, M> {
     pub fn get_handle(&self) -> &c::handle::RawPipelineState<R> {
         &self.0
     }
+    /// Get the primitive topology the PSO draws.
+    pub fn get_primitive(&self) -> c::Primitive {
+        self.1
+    }
     /// Get a "meta" struct reference. Can be used by the user to check
     /// what resources are actually used and what not.
     pub fn get_meta(&self) -> &M {
@@ -328,8 +348,13 @@ pub trait DataLink<'a>: Sized {
     /// Attempt to link with a sampler.
     fn link_sampler(&mut self, _: &c::shade::SamplerVar, _: &Self::Init)
                     -> Option<c::pso::SamplerDesc> { None }
+    /// Attempt to link with an atomic counter buffer.
+    fn link_atomic_counter_buffer(&mut self, _: &c::shade::AtomicCounterBufferVar, _: &Self::Init)
+                                  -> Option<c::pso::AtomicCounterBufferDesc> { None }
     /// Attempt to enable scissor test.
     fn link_scissor(&mut self) -> bool { false }
+    /// Attempt to set up point sprites.
+    fn link_point(&mut self, _: &Self::Init) -> Option<c::pso::PointInfo> { None }
 }
 
 /// The "bind" logic portion of the PSO component.
@@ -20,6 +20,7 @@ use core::{handle, pso, shade};
 use core::memory::Typed;
 use core::format::Format;
 use super::{DataLink, DataBind, RawDataSet, AccessInfo};
+use validate::TextureBinding;
 
 /// Shader resource component (SRV). Typically is a view into some texture,
 /// but can also be a buffer.
@@ -127,6 +128,17 @@ impl<R: Resources> DataBind<R> for RawShaderResource {
         if let Some((slot, usage)) = self.0 {
             let view = man.ref_srv(data).clone();
             out.resource_views.push(pso::ResourceViewParam(view, usage, slot));
+            if let Some(ref mut bindings) = out.bindings {
+                let texture = match (data.get_texture(), data.get_channel_type()) {
+                    (Some(t), Some(channel)) => Some(TextureBinding {
+                        kind: t.get_info().kind,
+                        surface: t.get_info().format,
+                        channel: channel,
+                    }),
+                    _ => None,
+                };
+                bindings.resource_views.push((slot, texture));
+            }
         }
     }
 }
@@ -92,6 +92,15 @@ pub struct DepthStencilTarget<T>(
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Scissor(bool);
 
+/// Point sprite component. Sets up the size and the sprite coordinates of
+/// the points, so that particles can be drawn as `Primitive::PointList`
+/// instead of expanded quads.
+///
+/// - init: `PointInfo`
+/// - data: `()`
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PointSprite(bool);
+
 /// Blend reference component. Sets up the reference color for blending.
 ///
 /// - init: `()`
@@ -127,7 +136,10 @@ impl<R: Resources, T> DataBind<R> for RenderTarget<T> {
                man: &mut handle::Manager<R>,
                _: &mut AccessInfo<R>) {
         if let Some(slot) = self.0 {
-            out.pixel_targets.add_color(slot, man.ref_rtv(data.raw()), data.raw().get_dimensions());
+            let dim = data.raw().get_dimensions();
+            if out.bindings.as_mut().map_or(true, |b| b.add_target(dim)) {
+                out.pixel_targets.add_color(slot, man.ref_rtv(data.raw()), dim);
+            }
         }
     }
 }
@@ -190,7 +202,10 @@ impl<R: Resources> DataBind<R> for RawRenderTarget {
                man: &mut handle::Manager<R>,
                _: &mut AccessInfo<R>) {
         if let Some(slot) = self.0 {
-            out.pixel_targets.add_color(slot, man.ref_rtv(data), data.get_dimensions());
+            let dim = data.get_dimensions();
+            if out.bindings.as_mut().map_or(true, |b| b.add_target(dim)) {
+                out.pixel_targets.add_color(slot, man.ref_rtv(data), dim);
+            }
         }
     }
 }
@@ -213,7 +228,10 @@ impl<R: Resources, T> DataBind<R> for DepthTarget<T> {
                man: &mut handle::Manager<R>,
                _: &mut AccessInfo<R>) {
         let dsv = data.raw();
-        out.pixel_targets.add_depth_stencil(man.ref_dsv(dsv), true, false, dsv.get_dimensions());
+        let dim = dsv.get_dimensions();
+        if out.bindings.as_mut().map_or(true, |b| b.add_target(dim)) {
+            out.pixel_targets.add_depth_stencil(man.ref_dsv(dsv), true, false, dim);
+        }
     }
 }
 
@@ -234,7 +252,10 @@ impl<R: Resources, T> DataBind<R> for StencilTarget<T> {
                man: &mut handle::Manager<R>,
                _: &mut AccessInfo<R>) {
         let dsv = data.0.raw();
-        out.pixel_targets.add_depth_stencil(man.ref_dsv(dsv), false, true, dsv.get_dimensions());
+        let dim = dsv.get_dimensions();
+        if out.bindings.as_mut().map_or(true, |b| b.add_target(dim)) {
+            out.pixel_targets.add_depth_stencil(man.ref_dsv(dsv), false, true, dim);
+        }
         out.ref_values.stencil = data.1;
     }
 }
@@ -256,7 +277,10 @@ impl<R: Resources, T> DataBind<R> for DepthStencilTarget<T> {
                man: &mut handle::Manager<R>,
                _: &mut AccessInfo<R>) {
         let dsv = data.0.raw();
-        out.pixel_targets.add_depth_stencil(man.ref_dsv(dsv), true, true, dsv.get_dimensions());
+        let dim = dsv.get_dimensions();
+        if out.bindings.as_mut().map_or(true, |b| b.add_target(dim)) {
+            out.pixel_targets.add_depth_stencil(man.ref_dsv(dsv), true, true, dim);
+        }
         out.ref_values.stencil = data.1;
     }
 }
@@ -277,9 +301,28 @@ impl<R: Resources> DataBind<R> for Scissor {
                _: &mut handle::Manager<R>,
                _: &mut AccessInfo<R>) {
         out.scissor = *data;
+        if let Some(ref mut bindings) = out.bindings {
+            bindings.scissor = Some(*data);
+        }
     }
 }
 
+impl<'a> DataLink<'a> for PointSprite {
+    type Init = pso::PointInfo;
+    fn new() -> Self { PointSprite(false) }
+    fn is_active(&self) -> bool { self.0 }
+    fn link_point(&mut self, init: &Self::Init) -> Option<pso::PointInfo> {
+        self.0 = true;
+        Some(*init)
+    }
+}
+
+impl<R: Resources> DataBind<R> for PointSprite {
+    type Data = ();
+    fn bind_to(&self, _: &mut RawDataSet<R>, _: &Self::Data,
+               _: &mut handle::Manager<R>, _: &mut AccessInfo<R>) {}
+}
+
 impl<'a> DataLink<'a> for BlendRef {
     type Init = ();
     fn new() -> Self { BlendRef }
@@ -0,0 +1,156 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Render queue with an optional sorting stage.
+//!
+//! Opaque draws are sorted by program, then bound textures, then the rest
+//! of the pipeline state, so that the backend sees as few state switches as
+//! possible. Transparent draws keep their own list and are sorted back to
+//! front by a depth key provided by the user.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use core::handle;
+use core::command::AccessInfo;
+use {Resources, Encoder, CommandBuffer, Bundle};
+use pso::{PipelineData, RawDataSet};
+
+/// Something that can be drawn with an encoder.
+pub trait Drawable<R: Resources, C> {
+    /// Encode the draw call.
+    fn draw(&self, encoder: &mut Encoder<R, C>);
+}
+
+impl<R: Resources, C: CommandBuffer<R>, D: PipelineData<R>> Drawable<R, C> for Bundle<R, D> {
+    fn draw(&self, encoder: &mut Encoder<R, C>) {
+        self.encode(encoder);
+    }
+}
+
+/// Sort key of an opaque draw call. Draws with equal fields share the
+/// corresponding state, and the fields are compared in order of
+/// decreasing switching cost.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SortKey {
+    /// Shader program
+    pub program: u64,
+    /// Bound shader resource views
+    pub textures: u64,
+    /// Remaining pipeline state
+    pub state: u64,
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A queue of draw calls, encoded all at once.
+pub struct RenderQueue<'a, R: Resources, C: 'a> {
+    opaque: Vec<(SortKey, &'a Drawable<R, C>)>,
+    transparent: Vec<(f32, &'a Drawable<R, C>)>,
+    raw_data: RawDataSet<R>,
+    handles: handle::Manager<R>,
+    access_info: AccessInfo<R>,
+}
+
+impl<'a, R: Resources, C: CommandBuffer<R>> RenderQueue<'a, R, C> {
+    /// Create an empty queue.
+    pub fn new() -> RenderQueue<'a, R, C> {
+        RenderQueue {
+            opaque: Vec::new(),
+            transparent: Vec::new(),
+            raw_data: RawDataSet::new(),
+            handles: handle::Manager::new(),
+            access_info: AccessInfo::new(),
+        }
+    }
+
+    /// Compute the sort key of a bundle, from its program and the
+    /// resources referenced by its data.
+    pub fn get_sort_key<D: PipelineData<R>>(&mut self, bundle: &Bundle<R, D>) -> SortKey {
+        self.raw_data.clear();
+        bundle.data.bake_to(&mut self.raw_data, bundle.pso.get_meta(),
+                            &mut self.handles, &mut self.access_info);
+        let key = {
+            let (pso, program) = self.handles.ref_pso(bundle.pso.get_handle());
+            SortKey {
+                program: hash_of(program),
+                textures: hash_of(&self.raw_data.resource_views),
+                state: hash_of(pso),
+            }
+        };
+        self.handles.clear();
+        self.access_info.clear();
+        key
+    }
+
+    /// Queue an opaque bundle, to be sorted by state.
+    pub fn push_opaque<D>(&mut self, bundle: &'a Bundle<R, D>) where
+        D: PipelineData<R> + 'a,
+    {
+        let key = self.get_sort_key(bundle);
+        let drawable: &'a Drawable<R, C> = bundle;
+        self.opaque.push((key, drawable));
+    }
+
+    /// Queue an opaque draw with a custom sort key.
+    pub fn push_opaque_with_key(&mut self, key: SortKey, drawable: &'a Drawable<R, C>) {
+        self.opaque.push((key, drawable));
+    }
+
+    /// Queue a transparent draw. Those are drawn after all opaque ones,
+    /// and the sorting draws the higher depth values first.
+    pub fn push_transparent(&mut self, depth: f32, drawable: &'a Drawable<R, C>) {
+        self.transparent.push((depth, drawable));
+    }
+
+    /// Sort the queued draws: opaque ones by their key, transparent ones
+    /// back to front. The sort is stable, so equal keys keep the order
+    /// of submission.
+    pub fn sort(&mut self) {
+        self.opaque.sort_by_key(|&(key, _)| key);
+        self.transparent.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+    }
+
+    /// Get the number of queued draws.
+    pub fn len(&self) -> usize {
+        self.opaque.len() + self.transparent.len()
+    }
+
+    /// Check if there are no queued draws.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all the queued draws.
+    pub fn clear(&mut self) {
+        self.opaque.clear();
+        self.transparent.clear();
+    }
+
+    /// Encode the queued draws in their current order, opaque ones first,
+    /// and empty the queue. Call `sort` beforehand to reduce state changes.
+    pub fn encode(&mut self, encoder: &mut Encoder<R, C>) {
+        for &(_, drawable) in &self.opaque {
+            drawable.draw(encoder);
+        }
+        for &(_, drawable) in &self.transparent {
+            drawable.draw(encoder);
+        }
+        self.clear();
+    }
+}
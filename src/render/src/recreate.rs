@@ -0,0 +1,124 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resources surviving the loss of the device.
+//!
+//! When the device is lost, like a GL context reset on mobile, all of its
+//! resources are gone with it. A `ResourceRegistry` keeps the functions
+//! creating each resource, to run them again with the factory of the new
+//! device:
+//!
+//! ```ignore
+//! let mut registry = ResourceRegistry::new();
+//! let vbuf = try!(registry.register(&mut factory, |f: &mut Factory| {
+//!     Ok::<_, CreationError>(try!(f.create_vertex_buffer(&VERTICES)))
+//! }));
+//! // ...
+//! if device.check_context_loss().is_some() {
+//!     let (new_device, mut new_factory) = create_device();
+//!     try!(registry.recreate(&mut new_factory));
+//! }
+//! data.vbuf = vbuf.get();
+//! ```
+//!
+//! The resources are recreated in the order of registration, so that the
+//! later ones may use the earlier ones. A resource stops being recreated
+//! once all the `Recreated` handles to it are dropped.
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::{Rc, Weak};
+
+/// A resource created by a `ResourceRegistry`, replaced by each `recreate`.
+#[derive(Debug)]
+pub struct Recreated<T>(Rc<RefCell<T>>);
+
+impl<T> Clone for Recreated<T> {
+    fn clone(&self) -> Recreated<T> {
+        Recreated(self.0.clone())
+    }
+}
+
+impl<T: Clone> Recreated<T> {
+    /// Get the current resource.
+    pub fn get(&self) -> T {
+        self.0.borrow().clone()
+    }
+}
+
+type Recreator<F> = Box<FnMut(&mut F) -> Option<Result<(), Box<Error>>>>;
+
+/// The functions creating the resources of a factory of type `F`.
+pub struct ResourceRegistry<F> {
+    recreators: Vec<Recreator<F>>,
+}
+
+impl<F> ResourceRegistry<F> {
+    /// Create an empty registry.
+    pub fn new() -> ResourceRegistry<F> {
+        ResourceRegistry {
+            recreators: Vec::new(),
+        }
+    }
+
+    /// Get the number of resources registered, including the ones dropped
+    /// since the last `recreate`.
+    pub fn len(&self) -> usize {
+        self.recreators.len()
+    }
+
+    /// Create a resource with `create`, which is kept to create it again.
+    pub fn register<T, E, C>(&mut self, factory: &mut F, mut create: C)
+                             -> Result<Recreated<T>, E> where
+        T: 'static,
+        E: Error + 'static,
+        C: FnMut(&mut F) -> Result<T, E> + 'static,
+    {
+        let cell = Rc::new(RefCell::new(try!(create(factory))));
+        let weak: Weak<RefCell<T>> = Rc::downgrade(&cell);
+        self.recreators.push(Box::new(move |factory: &mut F| {
+            weak.upgrade().map(|cell| match create(factory) {
+                Ok(value) => {
+                    *cell.borrow_mut() = value;
+                    Ok(())
+                },
+                Err(e) => Err(Box::new(e) as Box<Error>),
+            })
+        }));
+        Ok(Recreated(cell))
+    }
+
+    /// Create all the resources again with a new factory, stopping at the
+    /// first failure.
+    pub fn recreate(&mut self, factory: &mut F) -> Result<(), Box<Error>> {
+        let mut result = Ok(());
+        let mut alive = Vec::with_capacity(self.recreators.len());
+        for mut recreator in self.recreators.drain(..) {
+            if result.is_err() {
+                alive.push(recreator);
+                continue
+            }
+            match recreator(factory) {
+                Some(Ok(())) => alive.push(recreator),
+                Some(Err(e)) => {
+                    result = Err(e);
+                    alive.push(recreator);
+                },
+                None => (),
+            }
+        }
+        self.recreators = alive;
+        result
+    }
+}
@@ -20,6 +20,8 @@ use mint;
 use std::error::Error;
 use std::fmt;
 pub use core::shade::{self as core, ConstFormat, Formatted, Usage};
+pub use core::shade::{AttributeVar, BaseType, ConstantBufferVar, ConstVar, ContainerType,
+                      OutputVar, SamplerVar, TextureVar, UnorderedVar};
 
 #[allow(missing_docs)]
 pub trait ToUniform: Copy {
@@ -40,6 +42,7 @@ macro_rules! impl_uniforms {
 
 impl_uniforms! {
     i32 = I32,
+    u32 = U32,
     f32 = F32,
     [i32; 2] = I32Vector2,
     [i32; 3] = I32Vector3,
@@ -52,6 +55,36 @@ impl_uniforms! {
     [[f32; 4]; 4] = F32Matrix4,
 }
 
+/// A list of values bound to a uniform array, carrying its length so the
+/// number of values can be smaller than the size of the array.
+pub trait ToUniformArray {
+    /// Type of the elements
+    type Element: ToUniform;
+    /// Get the elements.
+    fn get_elements(&self) -> &[Self::Element];
+}
+
+impl<T: ToUniform> ToUniformArray for [T] {
+    type Element = T;
+    fn get_elements(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: ToUniform> ToUniformArray for Vec<T> {
+    type Element = T;
+    fn get_elements(&self) -> &[T] {
+        self
+    }
+}
+
+impl<'a, A: ToUniformArray + ?Sized> ToUniformArray for &'a A {
+    type Element = A::Element;
+    fn get_elements(&self) -> &[A::Element] {
+        (**self).get_elements()
+    }
+}
+
 #[cfg(feature = "mint")]
 impl_uniforms! {
     mint::Point2<f32> = F32Vector2,
@@ -117,3 +150,120 @@ impl Error for ProgramError {
         }
     }
 }
+
+/// A member of a constant buffer, found by its path.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MemberLocation {
+    /// Offset in bytes from the start of the buffer
+    pub offset: usize,
+    /// Size in bytes of the member, or of the array element
+    pub size: usize,
+    /// Type of the components
+    pub base_type: BaseType,
+    /// Number of components
+    pub container: ContainerType,
+}
+
+/// Constant buffer member lookup error
+#[derive(Clone, Debug, PartialEq)]
+pub enum MemberError {
+    /// No member has this path
+    NotFound(String),
+    /// The index is past the end of the array
+    IndexOutOfBounds {
+        /// Path of the member
+        path: String,
+        /// Number of elements of the array
+        count: usize,
+    },
+}
+
+impl fmt::Display for MemberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MemberError::NotFound(ref path) => write!(f, "{}: {}", self.description(), path),
+            MemberError::IndexOutOfBounds { ref path, count } =>
+                write!(f, "{}: {} has {} elements", self.description(), path, count),
+        }
+    }
+}
+
+impl Error for MemberError {
+    fn description(&self) -> &str {
+        match *self {
+            MemberError::NotFound(_) => "No constant buffer member has this path",
+            MemberError::IndexOutOfBounds { .. } => "Array index out of bounds",
+        }
+    }
+}
+
+/// Get the size in bytes of a component of `base`.
+fn get_base_size(base: BaseType) -> usize {
+    match base {
+        BaseType::F64 => 8,
+        BaseType::I32 | BaseType::U32 | BaseType::F32 | BaseType::Bool => 4,
+    }
+}
+
+/// Get the size of a member, and the stride of the arrays of it, following
+/// the `std140` layout, where the array elements and the matrix columns
+/// start on 16 bytes boundaries.
+fn get_member_layout(base: BaseType, container: ContainerType) -> (usize, usize) {
+    let base_size = get_base_size(base);
+    let round = |size: usize| (size + 15) & !15;
+    match container {
+        ContainerType::Single => (base_size, round(base_size)),
+        ContainerType::Vector(n) => (base_size * n as usize, round(base_size * n as usize)),
+        ContainerType::Matrix(format, cols, rows) => {
+            let (vectors, length) = match format {
+                core::MatrixFormat::ColumnMajor => (cols as usize, rows as usize),
+                core::MatrixFormat::RowMajor => (rows as usize, cols as usize),
+            };
+            let stride = round(base_size * length);
+            (stride * (vectors - 1) + base_size * length, stride * vectors)
+        },
+    }
+}
+
+/// Split a trailing array index off a path, like `"lights[3]"`.
+fn split_index(path: &str) -> Option<(&str, usize)> {
+    if !path.ends_with(']') {
+        return None
+    }
+    path.rfind('[').and_then(|start| {
+        path[start + 1 .. path.len() - 1].parse().ok().map(|index| (&path[.. start], index))
+    })
+}
+
+/// Find a member of a constant buffer by its path, like
+/// `"Lights.lights[3].color"`, using the offsets of its reflection. The
+/// name of the block is optional in the path, and the index of an array of
+/// basic types defaults to 0.
+pub fn find_member(buffer: &ConstantBufferVar, path: &str) -> Result<MemberLocation, MemberError> {
+    let prefix = format!("{}.", buffer.name);
+    let path = if path.starts_with(&prefix) { &path[prefix.len() ..] } else { path };
+    // the members of named blocks are prefixed by the name of the block
+    let find = |name: &str| buffer.get_element(name).or_else(||
+        buffer.get_element(&format!("{}{}", prefix, name)));
+    let location = |var: &ConstVar, index: usize| {
+        let (size, stride) = get_member_layout(var.base_type, var.container);
+        MemberLocation {
+            offset: var.location + index * stride,
+            size: size,
+            base_type: var.base_type,
+            container: var.container,
+        }
+    };
+    if let Some(var) = find(path) {
+        return Ok(location(var, 0))
+    }
+    // arrays of basic types are reflected as their first element
+    match split_index(path).and_then(|(base, index)| find(base).map(|var| (var, index))) {
+        Some((var, index)) if index < var.count => Ok(location(var, index)),
+        Some((var, _)) => Err(MemberError::IndexOutOfBounds {
+            path: path.to_string(),
+            count: var.count,
+        }),
+        None => Err(MemberError::NotFound(path.to_string())),
+    }
+}
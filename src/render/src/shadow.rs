@@ -0,0 +1,202 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Shadow maps of point lights.
+//!
+//! A `CubeShadowMap` is a depth cube map rendered from the position of the
+//! light, and sampled with depth comparison as a `samplerCubeShadow` by the
+//! `TextureSampler` component of the lit pipeline:
+//!
+//! ```ignore
+//! let shadow = try!(CubeShadowMap::<_, Depth>::new(&mut factory, 512));
+//! shadow.render(|pass| {
+//!     encoder.clear_depth(pass.target, 1.0);
+//!     match pass.face {
+//!         Some(face) => draw_scene(&mut encoder, pass.target,
+//!                                  mul(face_projection(0.1, 50.0), face_view(face, light_pos))),
+//!         // a geometry shader emits each primitive to the six faces,
+//!         // writing the index of the face to `gl_Layer`
+//!         None => draw_scene_layered(&mut encoder, pass.target),
+//!     }
+//! });
+//! let data = pipe::Data { shadow: shadow.get_param(), .. };
+//! ```
+//!
+//! The scene is rendered in a single pass over the layers of the cube when
+//! the device supports layered rendering, and in six passes otherwise.
+//! The matrices follow the OpenGL conventions of the cube map faces.
+
+use core::{format, handle, state, texture, Capabilities, Resources};
+use core::factory::{CombinedError, Factory};
+use core::memory::{Usage, DEPTH_STENCIL, SHADER_RESOURCE};
+use core::texture::{CubeFace, CUBE_FACES};
+
+/// How the faces of a cube shadow map are rendered.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CubeShadowMode {
+    /// All the faces at once, as the layers of the target.
+    Layered,
+    /// Each face separately.
+    PerFace,
+}
+
+impl CubeShadowMode {
+    /// Pick the most efficient mode supported by a device.
+    pub fn new(caps: &Capabilities) -> CubeShadowMode {
+        if caps.layered_rendering_supported {
+            CubeShadowMode::Layered
+        } else {
+            CubeShadowMode::PerFace
+        }
+    }
+}
+
+/// A pass rendering some faces of a cube shadow map.
+#[derive(Debug)]
+pub struct ShadowPass<'a, R: Resources, D: 'a> {
+    /// The face rendered, or `None` for all of them, as layers.
+    pub face: Option<CubeFace>,
+    /// The target to render into.
+    pub target: &'a handle::DepthStencilView<R, D>,
+}
+
+/// A depth cube map, rendered by faces or all at once.
+#[derive(Derivative)]
+#[derivative(Clone, Debug)]
+pub struct CubeShadowMap<R: Resources, D: format::Formatted> {
+    texture: handle::Texture<R, D::Surface>,
+    resource: handle::ShaderResourceView<R, D::View>,
+    sampler: handle::Sampler<R>,
+    mode: CubeShadowMode,
+    targets: Vec<handle::DepthStencilView<R, D>>,
+}
+
+impl<R: Resources, D> CubeShadowMap<R, D> where
+    D: format::DepthFormat + format::TextureFormat,
+{
+    /// Create a shadow map with faces of `size` by `size`.
+    pub fn new<F: Factory<R> + ?Sized>(factory: &mut F, size: texture::Size)
+               -> Result<CubeShadowMap<R, D>, CombinedError> {
+        let mode = CubeShadowMode::new(factory.get_capabilities());
+        let channel = <D::Channel as format::ChannelTyped>::get_channel_type();
+        let texture = try!(factory.create_texture(texture::Kind::Cube(size), 1,
+                                                  SHADER_RESOURCE | DEPTH_STENCIL,
+                                                  Usage::Data, Some(channel)));
+        let resource = try!(factory.view_texture_as_shader_resource::<D>(
+            &texture, (0, 0), format::Swizzle::new()));
+        let sampler = factory.create_sampler(texture::SamplerInfo {
+            comparison: Some(state::Comparison::LessEqual),
+            .. texture::SamplerInfo::new(texture::FilterMethod::Bilinear,
+                                         texture::WrapMode::Clamp)
+        });
+        let mut targets = Vec::new();
+        match mode {
+            CubeShadowMode::Layered => {
+                targets.push(try!(factory.view_texture_as_depth_stencil(
+                    &texture, 0, None, texture::DepthStencilFlags::empty())));
+            },
+            CubeShadowMode::PerFace => for layer in 0 .. CUBE_FACES.len() {
+                targets.push(try!(factory.view_texture_as_depth_stencil(
+                    &texture, 0, Some(layer as texture::Layer),
+                    texture::DepthStencilFlags::empty())));
+            },
+        }
+        Ok(CubeShadowMap {
+            texture: texture,
+            resource: resource,
+            sampler: sampler,
+            mode: mode,
+            targets: targets,
+        })
+    }
+
+    /// Get the cube map texture.
+    pub fn get_texture(&self) -> &handle::Texture<R, D::Surface> {
+        &self.texture
+    }
+
+    /// Get the mode the faces are rendered in.
+    pub fn get_mode(&self) -> CubeShadowMode {
+        self.mode
+    }
+
+    /// Get the data of the `TextureSampler` component sampling the map.
+    pub fn get_param(&self) -> (handle::ShaderResourceView<R, D::View>, handle::Sampler<R>) {
+        (self.resource.clone(), self.sampler.clone())
+    }
+
+    /// Render the faces, calling `pass` once with all of them in the
+    /// `Layered` mode, or once per face otherwise.
+    pub fn render<F>(&self, mut pass: F) where
+        F: FnMut(ShadowPass<R, D>)
+    {
+        match self.mode {
+            CubeShadowMode::Layered => pass(ShadowPass {
+                face: None,
+                target: &self.targets[0],
+            }),
+            CubeShadowMode::PerFace => for (&face, target) in CUBE_FACES.iter().zip(&self.targets) {
+                pass(ShadowPass {
+                    face: Some(face),
+                    target: target,
+                })
+            },
+        }
+    }
+}
+
+/// Get the direction and the up vector of the camera rendering a face.
+fn face_axes(face: CubeFace) -> ([f32; 3], [f32; 3]) {
+    match face {
+        CubeFace::PosX => ([1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+        CubeFace::NegX => ([-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+        CubeFace::PosY => ([0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+        CubeFace::NegY => ([0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+        CubeFace::PosZ => ([0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+        CubeFace::NegZ => ([0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+    }
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Get the view matrix of a face, seen from the light at `position`. The
+/// matrix is column-major, like the ones of `cgmath`.
+pub fn face_view(face: CubeFace, position: [f32; 3]) -> [[f32; 4]; 4] {
+    let (f, up) = face_axes(face);
+    let s = cross(f, up);
+    let u = cross(s, f);
+    [
+        [s[0], u[0], -f[0], 0.0],
+        [s[1], u[1], -f[1], 0.0],
+        [s[2], u[2], -f[2], 0.0],
+        [-dot(s, position), -dot(u, position), dot(f, position), 1.0],
+    ]
+}
+
+/// Get the projection matrix of the faces, covering 90 degrees with the
+/// given depth range. The matrix is column-major.
+pub fn face_projection(near: f32, far: f32) -> [[f32; 4]; 4] {
+    [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, (far + near) / (near - far), -1.0],
+        [0.0, 0.0, 2.0 * far * near / (near - far), 0.0],
+    ]
+}
@@ -16,8 +16,10 @@
 //!
 //! See `Slice`-structure documentation for more information on this module.
 
+use std::error::Error;
+use std::fmt;
 use core::{handle, buffer};
-use core::{Primitive, Resources, VertexCount};
+use core::{IndexType, Primitive, Resources, VertexCount};
 use core::command::InstanceParams;
 use core::factory::Factory;
 use core::memory::Bind;
@@ -75,11 +77,18 @@ pub struct Slice<R: Resources> {
 
 impl<R: Resources> Slice<R> {
     /// Creates a new `Slice` to match the supplied vertex buffer, from start to end, in order.
+    /// The role of the buffer is checked by the validation of the draws binding it.
     pub fn new_match_vertex_buffer<V>(vbuf: &handle::Buffer<R, V>) -> Self
                                       where V: pso::buffer::Structure<Format> {
+        Slice::new_auto(0, vbuf.len() as u32)
+    }
+
+    /// Creates a new `Slice` drawing the vertices from `start` to `end`, in order,
+    /// without instancing.
+    pub fn new_auto(start: VertexCount, end: VertexCount) -> Self {
         Slice {
-            start: 0,
-            end: vbuf.len() as u32,
+            start: start,
+            end: end,
             base_vertex: 0,
             instances: None,
             buffer: IndexBuffer::Auto,
@@ -141,48 +150,132 @@ pub enum IndexBuffer<R: Resources> {
     /// Represents a hypothetical index-buffer from 0 to infinity. In other words, all vertices
     /// get processed in order.
     Auto,
+    /// An index-buffer with unsigned 8 bit indices, for tiny meshes.
+    Index8(handle::Buffer<R, u8>),
     /// An index-buffer with unsigned 16 bit indices.
     Index16(handle::Buffer<R, u16>),
     /// An index-buffer with unsigned 32 bit indices.
     Index32(handle::Buffer<R, u32>),
 }
 
+impl<R: Resources> IndexBuffer<R> {
+    /// Get the type of the indices, or `None` for the `Auto` variant.
+    pub fn get_index_type(&self) -> Option<IndexType> {
+        match *self {
+            IndexBuffer::Auto => None,
+            IndexBuffer::Index8(_) => Some(IndexType::U8),
+            IndexBuffer::Index16(_) => Some(IndexType::U16),
+            IndexBuffer::Index32(_) => Some(IndexType::U32),
+        }
+    }
+
+    /// Get the number of indices in the buffer, or `None` for the `Auto` variant.
+    pub fn get_index_count(&self) -> Option<usize> {
+        match *self {
+            IndexBuffer::Auto => None,
+            IndexBuffer::Index8(ref buf) => Some(buf.len()),
+            IndexBuffer::Index16(ref buf) => Some(buf.len()),
+            IndexBuffer::Index32(ref buf) => Some(buf.len()),
+        }
+    }
+}
+
 impl<R: Resources> Default for IndexBuffer<R> {
     fn default() -> Self {
         IndexBuffer::Auto
     }
 }
+/// An error turning indices into an `IndexBuffer`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IndexBufferError {
+    /// The device doesn't support 8-bit indices, and a buffer of them
+    /// can't be widened.
+    UnsupportedU8,
+    /// The buffer has another role than `Index`.
+    Role(buffer::Role),
+    /// The creation of the buffer failed.
+    Creation(buffer::CreationError),
+}
+
+impl fmt::Display for IndexBufferError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IndexBufferError::Creation(ref e) => write!(f, "{}: {}", self.description(), e),
+            IndexBufferError::Role(ref role) => write!(f, "{}: {:?}", self.description(), role),
+            _ => write!(f, "{}", self.description()),
+        }
+    }
+}
+
+impl Error for IndexBufferError {
+    fn description(&self) -> &str {
+        match *self {
+            IndexBufferError::UnsupportedU8 => "8-bit indices are not supported by the device",
+            IndexBufferError::Role(_) => "The buffer is not an index buffer",
+            IndexBufferError::Creation(_) => "Could not create the index buffer",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            IndexBufferError::Creation(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<buffer::CreationError> for IndexBufferError {
+    fn from(e: buffer::CreationError) -> IndexBufferError {
+        IndexBufferError::Creation(e)
+    }
+}
+
 /// A helper trait to create `IndexBuffers` from different kinds of data.
 pub trait IntoIndexBuffer<R: Resources> {
     /// Turns self into an `IndexBuffer`.
-    fn into_index_buffer<F: Factory<R> + ?Sized>(self, factory: &mut F) -> IndexBuffer<R>;
+    fn into_index_buffer<F: Factory<R> + ?Sized>(self, factory: &mut F)
+                                                 -> Result<IndexBuffer<R>, IndexBufferError>;
 }
 
 impl<R: Resources> IntoIndexBuffer<R> for IndexBuffer<R> {
-    fn into_index_buffer<F: Factory<R> + ?Sized>(self, _: &mut F) -> IndexBuffer<R> {
-        self
+    fn into_index_buffer<F: Factory<R> + ?Sized>(self, _: &mut F)
+                                                 -> Result<IndexBuffer<R>, IndexBufferError> {
+        Ok(self)
     }
 }
 
 impl<R: Resources> IntoIndexBuffer<R> for () {
-    fn into_index_buffer<F: Factory<R> + ?Sized>(self, _: &mut F) -> IndexBuffer<R> {
-        IndexBuffer::Auto
+    fn into_index_buffer<F: Factory<R> + ?Sized>(self, _: &mut F)
+                                                 -> Result<IndexBuffer<R>, IndexBufferError> {
+        Ok(IndexBuffer::Auto)
+    }
+}
+
+/// The element type of a buffer handle is checked by the type system, but
+/// its role only at run time: a vertex buffer of `u16` isn't an index buffer.
+fn check_index_role(info: &buffer::Info) -> Result<(), IndexBufferError> {
+    match info.role {
+        buffer::Role::Index => Ok(()),
+        role => Err(IndexBufferError::Role(role)),
     }
 }
 
 macro_rules! impl_index_buffer {
     ($prim_ty:ty, $buf_ty:ident) => (
         impl<R: Resources> IntoIndexBuffer<R> for handle::Buffer<R, $prim_ty> {
-            fn into_index_buffer<F: Factory<R> + ?Sized>(self, _: &mut F) -> IndexBuffer<R> {
-                IndexBuffer::$buf_ty(self)
+            fn into_index_buffer<F: Factory<R> + ?Sized>(self, _: &mut F)
+                                 -> Result<IndexBuffer<R>, IndexBufferError> {
+                try!(check_index_role(self.get_info()));
+                Ok(IndexBuffer::$buf_ty(self))
             }
         }
 
         impl<'s, R: Resources> IntoIndexBuffer<R> for &'s [$prim_ty] {
-            fn into_index_buffer<F: Factory<R> + ?Sized>(self, factory: &mut F) -> IndexBuffer<R> {
-                factory.create_buffer_immutable(self, buffer::Role::Index, Bind::empty())
-                       .unwrap()
-                       .into_index_buffer(factory)
+            fn into_index_buffer<F: Factory<R> + ?Sized>(self, factory: &mut F)
+                                 -> Result<IndexBuffer<R>, IndexBufferError> {
+                let buffer = try!(factory.create_buffer_immutable(self, buffer::Role::Index,
+                                                                  Bind::empty()));
+                buffer.into_index_buffer(factory)
             }
         }
     )
@@ -190,3 +283,30 @@ macro_rules! impl_index_buffer {
 
 impl_index_buffer!(u16, Index16);
 impl_index_buffer!(u32, Index32);
+
+impl<R: Resources> IntoIndexBuffer<R> for handle::Buffer<R, u8> {
+    fn into_index_buffer<F: Factory<R> + ?Sized>(self, factory: &mut F)
+                                                 -> Result<IndexBuffer<R>, IndexBufferError> {
+        try!(check_index_role(self.get_info()));
+        if factory.get_capabilities().index_u8_supported {
+            Ok(IndexBuffer::Index8(self))
+        } else {
+            Err(IndexBufferError::UnsupportedU8)
+        }
+    }
+}
+
+/// Widened to 16-bit indices if the device doesn't support 8-bit ones.
+impl<'s, R: Resources> IntoIndexBuffer<R> for &'s [u8] {
+    fn into_index_buffer<F: Factory<R> + ?Sized>(self, factory: &mut F)
+                                                 -> Result<IndexBuffer<R>, IndexBufferError> {
+        if factory.get_capabilities().index_u8_supported {
+            let buffer = try!(factory.create_buffer_immutable(self, buffer::Role::Index,
+                                                              Bind::empty()));
+            buffer.into_index_buffer(factory)
+        } else {
+            let wide: Vec<u16> = self.iter().map(|&i| i as u16).collect();
+            wide.as_slice().into_index_buffer(factory)
+        }
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rendering a scene to both eyes at once.
+//!
+//! The eyes are rendered into the two layers of array targets, created by
+//! `create_stereo_targets`, with a single pass over the scene. Depending on
+//! the `StereoMode`, the draws are expanded to both layers either:
+//!
+//! - by the driver, with `OVR_multiview` shaders (`layout(num_views = 2) in;`
+//!   and `gl_ViewID_OVR`). The targets have to be viewed by the
+//!   `view_texture_as_multiview_*` methods of the GL factory.
+//! - by instancing, drawing twice the instances of each slice with
+//!   `expand_slice`. The vertex shader gets the eye from the lowest bit of
+//!   the instance index and writes it to `gl_Layer`, or
+//!   `SV_RenderTargetArrayIndex`. The instance buffers need a rate of 2, so
+//!   that both eyes of an instance fetch the same data.
+//!
+//! In both modes, the values specific to each eye, like the view-projection
+//! matrices, are passed as a two-element uniform array with a `PerView`
+//! component:
+//!
+//! ```ignore
+//! gfx_defines! {
+//!     pipeline pipe {
+//!         vbuf: gfx::VertexBuffer<Vertex> = (),
+//!         view_proj: gfx::PerView<[[f32; 4]; 4]> = "u_ViewProj",
+//!         out: gfx::RenderTarget<ColorFormat> = "Target0",
+//!     }
+//! }
+//! ```
+
+use core::{format, handle, texture, Capabilities, Resources};
+use core::factory::{CombinedError, Factory};
+use core::memory::{Usage, DEPTH_STENCIL, RENDER_TARGET, SHADER_RESOURCE};
+use slice::Slice;
+
+/// Number of views rendered at once, one per eye.
+pub const VIEW_COUNT: texture::Layer = 2;
+
+/// How the draws are expanded to both views.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum StereoMode {
+    /// The driver renders each draw into every layer, with multiview shaders.
+    Multiview,
+    /// Each draw gets twice the instances, half of them for each layer.
+    Instanced,
+}
+
+impl StereoMode {
+    /// Pick the most efficient mode supported by a device.
+    pub fn new(caps: &Capabilities) -> StereoMode {
+        if caps.multiview_supported {
+            StereoMode::Multiview
+        } else {
+            StereoMode::Instanced
+        }
+    }
+}
+
+/// Get the slice drawing `slice` into both views in the given mode.
+pub fn expand_slice<R: Resources>(slice: &Slice<R>, mode: StereoMode) -> Slice<R> {
+    let views = VIEW_COUNT as u32;
+    let instances = match mode {
+        StereoMode::Multiview => slice.instances,
+        StereoMode::Instanced => match slice.instances {
+            Some((count, base)) => Some((count * views, base * views)),
+            None => Some((views, 0)),
+        },
+    };
+    Slice {
+        instances: instances,
+        .. slice.clone()
+    }
+}
+
+/// The textures of both views, along with the views to sample them, one
+/// layer per eye, and to render into all their layers at once.
+#[allow(missing_docs)]
+#[derive(Derivative)]
+#[derivative(Clone, Debug)]
+pub struct StereoTargets<R: Resources, C: format::Formatted, D: format::Formatted> {
+    pub color: handle::Texture<R, C::Surface>,
+    pub color_resource: handle::ShaderResourceView<R, C::View>,
+    pub color_target: handle::RenderTargetView<R, C>,
+    pub depth: handle::Texture<R, D::Surface>,
+    pub depth_target: handle::DepthStencilView<R, D>,
+}
+
+/// Create the color and depth layers of both views, of `width` by `height`
+/// each. The targets are layered ones, as used by the `Instanced` mode.
+pub fn create_stereo_targets<R, F, C, D>(factory: &mut F, width: texture::Size,
+                                         height: texture::Size)
+                                         -> Result<StereoTargets<R, C, D>, CombinedError> where
+    R: Resources,
+    F: Factory<R> + ?Sized,
+    C: format::RenderFormat + format::TextureFormat,
+    D: format::DepthFormat + format::TextureFormat,
+{
+    let kind = texture::Kind::D2Array(width, height, VIEW_COUNT, texture::AaMode::Single);
+    let color_channel = <C::Channel as format::ChannelTyped>::get_channel_type();
+    let color = try!(factory.create_texture(kind, 1, SHADER_RESOURCE | RENDER_TARGET,
+                                            Usage::Data, Some(color_channel)));
+    let color_resource = try!(factory.view_texture_as_shader_resource::<C>(
+        &color, (0, 0), format::Swizzle::new()));
+    let color_target = try!(factory.view_texture_as_render_target(&color, 0, None));
+    let depth_channel = <D::Channel as format::ChannelTyped>::get_channel_type();
+    let depth = try!(factory.create_texture(kind, 1, DEPTH_STENCIL | SHADER_RESOURCE,
+                                            Usage::Data, Some(depth_channel)));
+    let depth_target = try!(factory.view_texture_as_depth_stencil(
+        &depth, 0, None, texture::DepthStencilFlags::empty()));
+    Ok(StereoTargets {
+        color: color,
+        color_resource: color_resource,
+        color_target: color_target,
+        depth: depth,
+        depth_target: depth_target,
+    })
+}
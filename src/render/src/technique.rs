@@ -0,0 +1,97 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Techniques with feature fallbacks.
+//!
+//! A technique is a list of variants of the same rendering effect, from the
+//! most to the least demanding one, each declaring the device features it
+//! requires. At run time the first variant supported by the device is
+//! picked, so that, for example, shaders reading their parameters from a
+//! constant buffer can fall back to plain uniforms on older drivers:
+//!
+//! ```ignore
+//! let technique = Technique::new()
+//!     .variant(&[Feature::ConstantBuffer], (VS_BLOCK, PS_BLOCK))
+//!     .variant(&[], (VS_UNIFORMS, PS_UNIFORMS));
+//! let &(vs, ps) = try!(technique.select(factory.get_capabilities()));
+//! ```
+//!
+//! Some features need no variant at all, because the backend emulates them.
+//! OpenGL drivers without sampler objects get their sampler state applied
+//! to each texture, for instance.
+
+use std::error::Error;
+use std::fmt;
+use core::{Capabilities, Feature};
+
+/// Error returned when no variant of a technique is supported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MissingFeatures(pub Vec<Feature>);
+
+impl fmt::Display for MissingFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {:?}", self.description(), self.0)
+    }
+}
+
+impl Error for MissingFeatures {
+    fn description(&self) -> &str {
+        "The device lacks the features of the least demanding variant"
+    }
+}
+
+/// A list of variants, in order of preference.
+#[derive(Clone, Debug)]
+pub struct Technique<T> {
+    variants: Vec<(Vec<Feature>, T)>,
+}
+
+impl<T> Technique<T> {
+    /// Create a technique without variants.
+    pub fn new() -> Technique<T> {
+        Technique {
+            variants: Vec::new(),
+        }
+    }
+
+    /// Add a variant, to be used when the preceding ones are not supported.
+    pub fn variant(mut self, required: &[Feature], variant: T) -> Technique<T> {
+        self.variants.push((required.to_vec(), variant));
+        self
+    }
+
+    /// Get the index of the first variant supported by the device.
+    pub fn select_index(&self, caps: &Capabilities) -> Result<usize, MissingFeatures> {
+        match self.variants.iter().position(|&(ref required, _)| {
+            required.iter().all(|&f| caps.supports(f))
+        }) {
+            Some(index) => Ok(index),
+            None => Err(MissingFeatures(match self.variants.last() {
+                Some(&(ref required, _)) =>
+                    required.iter().cloned().filter(|&f| !caps.supports(f)).collect(),
+                None => Vec::new(),
+            })),
+        }
+    }
+
+    /// Get the first variant supported by the device.
+    pub fn select(&self, caps: &Capabilities) -> Result<&T, MissingFeatures> {
+        self.select_index(caps).map(|index| &self.variants[index].1)
+    }
+
+    /// Consume the technique, keeping the first variant supported by the device.
+    pub fn into_selected(mut self, caps: &Capabilities) -> Result<T, MissingFeatures> {
+        self.select_index(caps).map(|index| self.variants.swap_remove(index).1)
+    }
+}
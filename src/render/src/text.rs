@@ -0,0 +1,380 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bitmap text overlay.
+//!
+//! An embedded 5x7 font covering printable ASCII is enough to show frame
+//! statistics or log lines on top of a scene. `TextBatch` lays out strings
+//! in screen-space quads, then flushes them into a `DynamicBuffer` to be
+//! drawn with a single call, using the provided shaders:
+//!
+//! ```ignore
+//! gfx_defines! {
+//!     pipeline text {
+//!         vbuf: gfx::VertexBuffer<gfx::text::Vertex> = (),
+//!         font: gfx::TextureSampler<f32> = "t_Font",
+//!         out: gfx::BlendTarget<ColorFormat> =
+//!             ("Target0", gfx::state::MASK_ALL, gfx::preset::blend::ALPHA),
+//!     }
+//! }
+//!
+//! batch.print(&format!("{:.1} ms", frame_time), [4.0, 4.0], [1.0; 4]);
+//! let slice = try!(batch.flush(&mut quads, &mut factory, &mut encoder, (width, height)));
+//! data.vbuf = quads.get_buffer().clone();
+//! encoder.draw(&slice, &pso, &data);
+//! ```
+
+use std::error::Error;
+use std::{fmt, mem};
+use core::{format, handle, texture, Resources};
+use core::command::Buffer as CommandBuffer;
+use core::factory::{CombinedError, Factory};
+use core::format::Formatted;
+use core::memory::Pod;
+use dynamic::{DynamicBuffer, WriteError};
+use encoder::Encoder;
+use pso::buffer::{Element, ElemOffset, Structure};
+use slice::Slice;
+
+/// Width of a character cell in the atlas, in pixels, spacing included.
+pub const GLYPH_WIDTH: u16 = 6;
+/// Height of a character cell in the atlas, in pixels, spacing included.
+pub const GLYPH_HEIGHT: u16 = 8;
+const ATLAS_COLUMNS: u16 = 16;
+const ATLAS_ROWS: u16 = 6;
+const FIRST_CHAR: u8 = b' ';
+
+/// GLSL 1.50 vertex shader for the text pipeline.
+pub const VERTEX_SHADER_GLSL: &'static [u8] = b"
+#version 150 core
+in vec2 a_Pos;
+in vec2 a_TexCoord;
+in vec4 a_Color;
+out vec2 v_TexCoord;
+out vec4 v_Color;
+void main() {
+    v_TexCoord = a_TexCoord;
+    v_Color = a_Color;
+    gl_Position = vec4(a_Pos, 0.0, 1.0);
+}
+";
+
+/// GLSL 1.50 pixel shader for the text pipeline, sampling `t_Font`.
+pub const PIXEL_SHADER_GLSL: &'static [u8] = b"
+#version 150 core
+uniform sampler2D t_Font;
+in vec2 v_TexCoord;
+in vec4 v_Color;
+out vec4 Target0;
+void main() {
+    Target0 = v_Color * vec4(1.0, 1.0, 1.0, texture(t_Font, v_TexCoord).r);
+}
+";
+
+/// Rows of the glyphs from `' '` to `'~'`, with the leftmost pixel in the
+/// 5th bit.
+const GLYPHS: [[u8; 7]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // '!'
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // '#'
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // '$'
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // '%'
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // '&'
+    [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '\''
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // '('
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // ')'
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // '*'
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ','
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // '.'
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // '/'
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // '0'
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // '1'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // '2'
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // '3'
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // '4'
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // '5'
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // '6'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // '7'
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // '8'
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ';'
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // '<'
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // '='
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // '>'
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // '?'
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // '@'
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // 'A'
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // 'B'
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // 'C'
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // 'D'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // 'E'
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // 'F'
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // 'G'
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // 'H'
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'I'
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // 'J'
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // 'K'
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // 'L'
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // 'M'
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // 'N'
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'O'
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // 'P'
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // 'Q'
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // 'R'
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // 'S'
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // 'T'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // 'U'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'V'
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // 'W'
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // 'X'
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // 'Y'
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // 'Z'
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // '['
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // '\\'
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ']'
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // '_'
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F], // 'a'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E], // 'b'
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E], // 'c'
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F], // 'd'
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E], // 'e'
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08], // 'f'
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'g'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11], // 'h'
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E], // 'i'
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C], // 'j'
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12], // 'k'
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // 'l'
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11], // 'm'
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11], // 'n'
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E], // 'o'
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10], // 'p'
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01], // 'q'
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10], // 'r'
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E], // 's'
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06], // 't'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D], // 'u'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04], // 'v'
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A], // 'w'
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11], // 'x'
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E], // 'y'
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F], // 'z'
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02], // '{'
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // '|'
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08], // '}'
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00], // '~'
+];
+
+/// Get the width, height and 8-bit pixels of the font atlas, with the
+/// characters from `' '` to `'~'` laid out row by row.
+pub fn get_font_atlas() -> (u16, u16, Vec<u8>) {
+    let width = ATLAS_COLUMNS * GLYPH_WIDTH;
+    let height = ATLAS_ROWS * GLYPH_HEIGHT;
+    let mut pixels = vec![0u8; width as usize * height as usize];
+    for (i, glyph) in GLYPHS.iter().enumerate() {
+        let x0 = (i % ATLAS_COLUMNS as usize) * GLYPH_WIDTH as usize;
+        let y0 = (i / ATLAS_COLUMNS as usize) * GLYPH_HEIGHT as usize;
+        for (y, &row) in glyph.iter().enumerate() {
+            for x in 0 .. 5 {
+                if row & (0x10 >> x) != 0 {
+                    pixels[(y0 + y) * width as usize + x0 + x] = 0xFF;
+                }
+            }
+        }
+    }
+    (width, height, pixels)
+}
+
+/// Create the font atlas texture, to be sampled by the pixel shader.
+pub fn create_font_texture<R, F>(factory: &mut F)
+                                 -> Result<(handle::Texture<R, format::R8>,
+                                            handle::ShaderResourceView<R, f32>),
+                                           CombinedError> where
+    R: Resources,
+    F: Factory<R> + ?Sized,
+{
+    let (width, height, pixels) = get_font_atlas();
+    let kind = texture::Kind::D2(width, height, texture::AaMode::Single);
+    factory.create_texture_immutable_u8::<(format::R8, format::Unorm)>(kind, &[&pixels])
+}
+
+/// A corner of a character quad.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct Vertex {
+    /// Position in clip space, bound to `a_Pos`
+    pub pos: [f32; 2],
+    /// Atlas coordinates, bound to `a_TexCoord`
+    pub tex_coord: [f32; 2],
+    /// Color, bound to `a_Color`
+    pub color: [f32; 4],
+}
+
+unsafe impl Pod for Vertex {}
+
+impl Structure<format::Format> for Vertex {
+    fn query(name: &str) -> Option<Element<format::Format>> {
+        let float = mem::size_of::<f32>() as ElemOffset;
+        let (format, offset) = match name {
+            "a_Pos" => (<[f32; 2] as Formatted>::get_format(), 0),
+            "a_TexCoord" => (<[f32; 2] as Formatted>::get_format(), 2 * float),
+            "a_Color" => (<[f32; 4] as Formatted>::get_format(), 4 * float),
+            _ => return None,
+        };
+        Some(Element {
+            format: format,
+            offset: offset,
+        })
+    }
+}
+
+/// An error flushing a `TextBatch`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FlushError {
+    /// The target has a zero width or height.
+    TargetSize(texture::Size, texture::Size),
+    /// The vertices could not be written.
+    Write(WriteError),
+}
+
+impl fmt::Display for FlushError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FlushError::TargetSize(w, h) => write!(f, "{}: {}x{}", self.description(), w, h),
+            FlushError::Write(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for FlushError {
+    fn description(&self) -> &str {
+        match *self {
+            FlushError::TargetSize(..) => "The text target is empty",
+            FlushError::Write(ref e) => e.description(),
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            FlushError::TargetSize(..) => None,
+            FlushError::Write(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<WriteError> for FlushError {
+    fn from(e: WriteError) -> FlushError {
+        FlushError::Write(e)
+    }
+}
+
+/// Accumulator of the text of a frame, as a triangle list with 6 vertices
+/// per character.
+#[derive(Clone, Debug)]
+pub struct TextBatch {
+    vertices: Vec<Vertex>,
+    /// The vertices converted to clip space by the last flush.
+    scratch: Vec<Vertex>,
+    scale: f32,
+}
+
+impl TextBatch {
+    /// Create an empty batch, drawing each atlas pixel as `scale` by
+    /// `scale` screen pixels.
+    pub fn new(scale: f32) -> TextBatch {
+        TextBatch {
+            vertices: Vec::new(),
+            scratch: Vec::new(),
+            scale: scale,
+        }
+    }
+
+    /// Get the vertices accumulated so far, with positions in pixels from
+    /// the top left corner until the batch is flushed.
+    pub fn get_vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    /// Lay out `text` from the top left corner at `pos`, in pixels. Line
+    /// breaks start a new line and the characters out of the printable
+    /// ASCII range are shown as `'?'`. Returns the position after the last
+    /// character.
+    pub fn print(&mut self, text: &str, pos: [f32; 2], color: [f32; 4]) -> [f32; 2] {
+        let (cell_w, cell_h) = (GLYPH_WIDTH as f32 * self.scale, GLYPH_HEIGHT as f32 * self.scale);
+        let (atlas_w, atlas_h) = ((ATLAS_COLUMNS * GLYPH_WIDTH) as f32,
+                                  (ATLAS_ROWS * GLYPH_HEIGHT) as f32);
+        let mut cursor = pos;
+        for c in text.chars() {
+            if c == '\n' {
+                cursor = [pos[0], cursor[1] + cell_h];
+                continue
+            }
+            let index = match c {
+                ' ' => {
+                    cursor[0] += cell_w;
+                    continue
+                },
+                '!' ... '~' => c as u16 - FIRST_CHAR as u16,
+                _ => b'?' as u16 - FIRST_CHAR as u16,
+            };
+            let u0 = (index % ATLAS_COLUMNS * GLYPH_WIDTH) as f32 / atlas_w;
+            let v0 = (index / ATLAS_COLUMNS * GLYPH_HEIGHT) as f32 / atlas_h;
+            let (u1, v1) = (u0 + GLYPH_WIDTH as f32 / atlas_w, v0 + GLYPH_HEIGHT as f32 / atlas_h);
+            let (x0, y0) = (cursor[0], cursor[1]);
+            let (x1, y1) = (x0 + cell_w, y0 + cell_h);
+            let vertex = |x, y, u, v| Vertex {
+                pos: [x, y],
+                tex_coord: [u, v],
+                color: color,
+            };
+            self.vertices.extend_from_slice(&[
+                vertex(x0, y0, u0, v0), vertex(x0, y1, u0, v1), vertex(x1, y1, u1, v1),
+                vertex(x1, y1, u1, v1), vertex(x1, y0, u1, v0), vertex(x0, y0, u0, v0),
+            ]);
+            cursor[0] += cell_w;
+        }
+        cursor
+    }
+
+    /// Convert the accumulated quads to the clip space of a target of the
+    /// given size, write them into `buffer` and clear the batch, returning
+    /// the slice drawing them. On error, the batch is left as it was.
+    pub fn flush<R, F, C>(&mut self, buffer: &mut DynamicBuffer<R, Vertex>,
+                          factory: &mut F, encoder: &mut Encoder<R, C>,
+                          target_size: (texture::Size, texture::Size))
+                          -> Result<Slice<R>, FlushError> where
+        R: Resources,
+        F: Factory<R> + ?Sized,
+        C: CommandBuffer<R>,
+    {
+        if target_size.0 == 0 || target_size.1 == 0 {
+            return Err(FlushError::TargetSize(target_size.0, target_size.1))
+        }
+        let (w, h) = (target_size.0 as f32, target_size.1 as f32);
+        self.scratch.clear();
+        self.scratch.extend(self.vertices.iter().map(|v| Vertex {
+            pos: [v.pos[0] / w * 2.0 - 1.0, 1.0 - v.pos[1] / h * 2.0],
+            .. *v
+        }));
+        let slice = try!(buffer.write(factory, encoder, &self.scratch));
+        self.vertices.clear();
+        Ok(slice)
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Frame timing statistics.
+//!
+//! A `FrameTimer` records the CPU time taken to record and submit each
+//! frame, its GPU time, measured by a timer query, the time waiting for the
+//! swap and the draws issued through it. The last frames are kept in a
+//! history, to drive the graphs of a debug overlay:
+//!
+//! ```ignore
+//! timer.begin_frame(&mut encoder);
+//! timer.draw(&mut encoder, &slice, &pso, &data);
+//! timer.end_frame(&mut encoder);
+//! timer.submit(&mut encoder, &mut device);
+//! timer.swap(|| window.swap_buffers().unwrap());
+//! timer.update(&mut device);
+//! let graph: Vec<f32> = timer.get_frames().iter().map(|f| as_millis(f.interval)).collect();
+//! ```
+//!
+//! The `interval` between the starts of the frames shows their pacing. The
+//! GPU times are read back without waiting for them, so they arrive a few
+//! frames late, and never on devices without timer queries.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use core::{Device, Primitive, QuerySlot, Resources};
+use core::command::Buffer as CommandBuffer;
+use encoder::Encoder;
+use pso::{PipelineData, PipelineState};
+use slice::Slice;
+
+/// Get a duration in milliseconds, for display.
+pub fn as_millis(duration: Duration) -> f32 {
+    duration.as_secs() as f32 * 1000.0 + duration.subsec_nanos() as f32 / 1_000_000.0
+}
+
+/// The statistics of a frame.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FrameTiming {
+    /// Index of the frame
+    pub frame: usize,
+    /// Time since the start of the previous frame
+    pub interval: Duration,
+    /// CPU time recording the commands, from `begin_frame` to `end_frame`
+    pub record: Duration,
+    /// CPU time submitting the commands
+    pub submit: Duration,
+    /// GPU time of the commands between `begin_frame` and `end_frame`,
+    /// once known
+    pub gpu: Option<Duration>,
+    /// CPU time waiting for the swap
+    pub swap: Duration,
+    /// Number of draw calls
+    pub draw_count: usize,
+    /// Number of primitives drawn, over all the instances
+    pub primitive_count: usize,
+}
+
+/// A recorder of the statistics of the last frames.
+#[derive(Clone, Debug)]
+pub struct FrameTimer {
+    base: QuerySlot,
+    slot_count: QuerySlot,
+    next_slot: QuerySlot,
+    capacity: usize,
+    frames: VecDeque<FrameTiming>,
+    current: FrameTiming,
+    started: Option<Instant>,
+    query: Option<QuerySlot>,
+    /// Frames waiting for their GPU time, with the slot of their query.
+    pending: VecDeque<(usize, QuerySlot)>,
+}
+
+impl FrameTimer {
+    /// Create a timer keeping the last `capacity` frames, using the timer
+    /// query slots from `base` to `base + slot_count`. The frames whose
+    /// GPU time is still unknown hold a slot each, a frame finding none
+    /// left is not measured on the GPU.
+    pub fn new(base: QuerySlot, slot_count: QuerySlot, capacity: usize) -> FrameTimer {
+        FrameTimer {
+            base: base,
+            slot_count: slot_count,
+            next_slot: 0,
+            capacity: capacity,
+            frames: VecDeque::with_capacity(capacity + 1),
+            current: FrameTiming::default(),
+            started: None,
+            query: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Get the frames recorded, from the oldest to the last one.
+    pub fn get_frames(&self) -> &VecDeque<FrameTiming> {
+        &self.frames
+    }
+
+    /// Get the last frame recorded.
+    pub fn get_last(&self) -> Option<&FrameTiming> {
+        self.frames.back()
+    }
+
+    /// Get the longest interval between two frames of the history, to
+    /// catch the stutters an average would hide.
+    pub fn get_max_interval(&self) -> Duration {
+        self.frames.iter().map(|f| f.interval).max().unwrap_or_default()
+    }
+
+    /// Start recording a frame.
+    pub fn begin_frame<R, C>(&mut self, encoder: &mut Encoder<R, C>) where
+        R: Resources,
+        C: CommandBuffer<R>,
+    {
+        let now = Instant::now();
+        let frame = if self.started.is_some() { self.current.frame + 1 } else { 0 };
+        self.current = FrameTiming {
+            frame: frame,
+            interval: self.started.map(|start| now.duration_since(start)).unwrap_or_default(),
+            .. FrameTiming::default()
+        };
+        self.started = Some(now);
+        if (self.pending.len() as QuerySlot) < self.slot_count {
+            let slot = self.base + self.next_slot;
+            self.next_slot = (self.next_slot + 1) % self.slot_count;
+            encoder.begin_timer_query(slot);
+            self.query = Some(slot);
+        }
+    }
+
+    /// Count a draw call of `slice`, issued without the timer.
+    pub fn count_draw<R: Resources>(&mut self, slice: &Slice<R>, primitive: Primitive) {
+        let instances = slice.instances.map_or(1, |(count, _)| count as usize);
+        self.current.draw_count += 1;
+        self.current.primitive_count += slice.get_prim_count(primitive) as usize * instances;
+    }
+
+    /// Draw and count a draw call.
+    pub fn draw<R, C, D>(&mut self, encoder: &mut Encoder<R, C>, slice: &Slice<R>,
+                         pipeline: &PipelineState<R, D::Meta>, data: &D) where
+        R: Resources,
+        C: CommandBuffer<R>,
+        D: PipelineData<R>,
+    {
+        encoder.draw(slice, pipeline, data);
+        self.count_draw(slice, pipeline.get_primitive());
+    }
+
+    /// Finish recording a frame, adding it to the history.
+    pub fn end_frame<R, C>(&mut self, encoder: &mut Encoder<R, C>) where
+        R: Resources,
+        C: CommandBuffer<R>,
+    {
+        if let Some(slot) = self.query.take() {
+            encoder.end_timer_query(slot);
+            self.pending.push_back((self.current.frame, slot));
+        }
+        if let Some(start) = self.started {
+            self.current.record = start.elapsed();
+        }
+        self.frames.push_back(self.current);
+        if self.frames.len() > self.capacity {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Flush the commands of the frame, measuring the submission.
+    pub fn submit<R, C, D>(&mut self, encoder: &mut Encoder<R, C>, device: &mut D) where
+        R: Resources,
+        C: CommandBuffer<R>,
+        D: Device<Resources=R, CommandBuffer=C>,
+    {
+        let start = Instant::now();
+        encoder.flush(device);
+        if let Some(frame) = self.frames.back_mut() {
+            frame.submit = start.elapsed();
+        }
+    }
+
+    /// Present the frame with `swap`, measuring the wait.
+    pub fn swap<F: FnOnce()>(&mut self, swap: F) {
+        let start = Instant::now();
+        swap();
+        if let Some(frame) = self.frames.back_mut() {
+            frame.swap = start.elapsed();
+        }
+    }
+
+    /// Read back the GPU times available, after submitting the frames.
+    pub fn update<D: Device>(&mut self, device: &mut D) {
+        // the queries complete in order
+        while let Some(&(frame, slot)) = self.pending.front() {
+            let nanos = match device.get_timer_result(slot) {
+                Some(nanos) => nanos,
+                None => break,
+            };
+            self.pending.pop_front();
+            if let Some(timing) = self.frames.iter_mut().find(|f| f.frame == frame) {
+                timing.gpu = Some(Duration::new(nanos / 1_000_000_000,
+                                                (nanos % 1_000_000_000) as u32));
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GLSL version translation.
+//!
+//! Shaders are written once against GLSL 1.50, and `translate` rewrites
+//! them for the version supported by the device:
+//!
+//! - the `#version` directive is replaced by the one of the target, and
+//!   GLSL ES pixel shaders get a default float precision.
+//! - for GLSL 1.20 and GLSL ES 1.00, which predate `in` and `out`, the
+//!   global inputs and outputs become `attribute` and `varying`
+//!   declarations, and the pixel shader outputs are replaced by
+//!   `gl_FragColor`, or `gl_FragData` if there are several of them.
+//!   Calls to `texture`, `textureLod` and `textureProj` are renamed after
+//!   the type of the sampler they use, as declared by the shader, like
+//!   `texture2D` or `textureCube`. Layout and interpolation qualifiers
+//!   are dropped.
+//!
+//! The translation works on the text of the shader, line by line, so the
+//! global declarations are expected to fit on a line each.
+
+use std::collections::HashMap;
+use core::shade::Stage;
+
+/// A GLSL version to translate the shaders to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GlslTarget {
+    /// Version number, like 120 for GLSL 1.20.
+    pub version: u16,
+    /// Whether the target is GLSL ES.
+    pub embedded: bool,
+}
+
+impl GlslTarget {
+    /// Create a new target.
+    pub fn new(version: u16, embedded: bool) -> GlslTarget {
+        GlslTarget {
+            version: version,
+            embedded: embedded,
+        }
+    }
+
+    /// Check whether the target predates `in` and `out` declarations.
+    pub fn is_legacy(&self) -> bool {
+        self.version < if self.embedded { 300 } else { 130 }
+    }
+
+    /// Get the `#version` directive of the target.
+    pub fn get_directive(&self) -> String {
+        match (self.embedded, self.version) {
+            (true, v) if v >= 300 => format!("#version {} es", v),
+            (false, v) if v >= 150 => format!("#version {} core", v),
+            (_, v) => format!("#version {}", v),
+        }
+    }
+}
+
+/// Rewrite the source of a shader for the `stage`, written against
+/// GLSL 1.50, to the `target` version.
+pub fn translate(source: &str, stage: Stage, target: GlslTarget) -> String {
+    let mut header = target.get_directive();
+    header.push('\n');
+    if target.embedded && stage == Stage::Pixel && !source.contains("precision ") {
+        header.push_str("precision mediump float;\n");
+    }
+
+    let legacy = target.is_legacy();
+    let mut out = String::with_capacity(source.len() + header.len());
+    let mut has_header = false;
+    let mut samplers = HashMap::new();
+    let mut outputs = Vec::new();
+    let mut depth = 0;
+    for line in source.lines() {
+        let trimmed = line.trim_left();
+        if trimmed.starts_with('#') {
+            if trimmed[1..].trim_left().starts_with("version") && !has_header {
+                out.push_str(&header);
+                has_header = true;
+            } else {
+                out.push_str(line);
+                out.push('\n');
+            }
+            continue
+        }
+        if !legacy {
+            out.push_str(line);
+            out.push('\n');
+            continue
+        }
+        let code = if depth == 0 {
+            match translate_declaration(trimmed, stage, &mut samplers, &mut outputs) {
+                Some(decl) => format!("{}{}", &line[..line.len() - trimmed.len()], decl),
+                None => continue,
+            }
+        } else {
+            line.to_string()
+        };
+        depth += code.matches('{').count() as isize - code.matches('}').count() as isize;
+        out.push_str(&rename(&code, &samplers, &outputs));
+        out.push('\n');
+    }
+    if !has_header {
+        out.insert_str(0, &header);
+    }
+    out
+}
+
+/// Split the first word of a piece of code from the rest.
+fn split_word(code: &str) -> (&str, &str) {
+    let end = code.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(code.len());
+    (&code[..end], code[end..].trim_left())
+}
+
+/// Get the names of the variables declared by a list, like `a, b[2];`.
+fn declared_names(list: &str) -> Vec<String> {
+    list.split(|c| c == ',' || c == ';')
+        .map(|name| name.split('[').next().unwrap().trim())
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Get the legacy texture function of a sampler type.
+fn sampler_function(sampler: &str) -> Option<&'static str> {
+    Some(match sampler {
+        "sampler1D" => "texture1D",
+        "sampler2D" => "texture2D",
+        "sampler3D" => "texture3D",
+        "samplerCube" => "textureCube",
+        "sampler1DShadow" => "shadow1D",
+        "sampler2DShadow" => "shadow2D",
+        _ => return None,
+    })
+}
+
+/// Translate a global declaration to legacy GLSL, or get `None` if it
+/// doesn't exist there.
+fn translate_declaration(line: &str, stage: Stage, samplers: &mut HashMap<String, &'static str>,
+                         outputs: &mut Vec<String>) -> Option<String> {
+    let mut decl = line;
+    if decl.starts_with("layout") {
+        if let Some(end) = decl.find(')') {
+            decl = decl[end + 1..].trim_left();
+        }
+    }
+    let (mut qualifier, mut rest) = split_word(decl);
+    match qualifier {
+        "flat" | "smooth" | "noperspective" | "centroid" => {
+            let (q, r) = split_word(rest);
+            qualifier = q;
+            rest = r;
+        },
+        _ => (),
+    }
+    Some(match (qualifier, stage) {
+        ("in", Stage::Vertex) => format!("attribute {}", rest),
+        ("in", Stage::Pixel) | ("out", Stage::Vertex) => format!("varying {}", rest),
+        ("out", Stage::Pixel) => {
+            outputs.extend(declared_names(split_word(rest).1));
+            return None
+        },
+        ("uniform", _) => {
+            let (mut ty, mut names) = split_word(rest);
+            match ty {
+                "lowp" | "mediump" | "highp" => {
+                    let (t, n) = split_word(names);
+                    ty = t;
+                    names = n;
+                },
+                _ => (),
+            }
+            if let Some(function) = sampler_function(ty) {
+                for name in declared_names(names) {
+                    samplers.insert(name, function);
+                }
+            }
+            format!("uniform {}", rest)
+        },
+        _ => line.to_string(),
+    })
+}
+
+/// Rename the pixel shader outputs and the texture functions of a line.
+fn rename(line: &str, samplers: &HashMap<String, &'static str>, outputs: &[String]) -> String {
+    let (code, comment) = line.split_at(line.find("//").unwrap_or(line.len()));
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(line.len());
+    let mut rest = code;
+    while let Some(start) = rest.find(&is_word) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c: char| !is_word(c)).unwrap_or(rest.len());
+        let (word, after) = rest.split_at(end);
+        rest = after;
+        if let Some(index) = outputs.iter().position(|o| o == word) {
+            if outputs.len() == 1 {
+                out.push_str("gl_FragColor");
+            } else {
+                out.push_str(&format!("gl_FragData[{}]", index));
+            }
+        } else if (word == "texture" || word == "textureLod" || word == "textureProj") &&
+                  after.trim_left().starts_with('(') {
+            let arg = after.trim_left()[1..].trim_left();
+            let sampler = &arg[..arg.find(|c: char| !is_word(c)).unwrap_or(arg.len())];
+            out.push_str(samplers.get(sampler).cloned().unwrap_or("texture2D"));
+            out.push_str(&word[7..]);
+        } else {
+            out.push_str(word);
+        }
+    }
+    out.push_str(rest);
+    out.push_str(comment);
+    out
+}
@@ -0,0 +1,385 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Draw call validation.
+//!
+//! The pipeline state checks the formats of the components when it is
+//! linked, but not the resources given to each draw call. With validation
+//! enabled on an `Encoder`, each draw checks that:
+//!
+//! - the vertex and constant buffers bound have the role of their
+//!   component.
+//! - the slice stays within the index buffer, and within the vertex
+//!   buffers for non-indexed draws, and the instances within the instance
+//!   buffers.
+//! - every vertex attribute of the program is fed by a buffer.
+//! - the textures bound have the dimensionality and multisampling
+//!   expected by the samplers, and are viewed as their base type.
+//! - all the targets have the same dimensions, and contain the scissor.
+//!
+//! ```ignore
+//! encoder.set_validation(true);
+//! if let Err(e) = encoder.try_draw(&slice, &pso, &data) {
+//!     warn!("Skipped draw: {}", e);
+//! }
+//! ```
+//!
+//! The values of the indices are not known on the CPU, so the vertex
+//! buffers are not checked against them.
+
+use std::error::Error;
+use std::fmt;
+use core::{ResourceViewSlot, VertexCount, MAX_VERTEX_ATTRIBUTES};
+use core::buffer::Role;
+use core::format::{ChannelType, SurfaceType};
+use core::pso::{ElemStride, InstanceRate};
+use core::shade::{self, BaseType, IsArray, IsMultiSample, TextureType};
+use core::target::Rect;
+use core::texture::{AaMode, Dimensions, Kind};
+use core::Resources;
+use slice::{IndexBuffer, Slice};
+
+/// An error found in a draw call.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawError {
+    /// A buffer is bound to a component of another role.
+    BufferRole {
+        /// Role of the component.
+        expected: Role,
+        /// Role of the buffer bound.
+        found: Role,
+    },
+    /// The slice ends past the end of the index buffer.
+    IndexOutOfBounds {
+        /// End of the slice.
+        end: VertexCount,
+        /// Number of indices in the buffer.
+        count: usize,
+    },
+    /// A vertex or instance buffer has fewer elements than needed.
+    VertexOutOfBounds {
+        /// Name of the attribute reading from the buffer.
+        attribute: String,
+        /// Number of elements needed.
+        needed: usize,
+        /// Number of elements in the buffer, past its offset.
+        count: usize,
+    },
+    /// A vertex attribute of the program has no buffer bound.
+    MissingAttribute(String),
+    /// A texture can't be sampled by the type of its sampler.
+    TextureType {
+        /// Name of the texture in the program.
+        name: String,
+        /// Type of the sampler.
+        expected: TextureType,
+        /// Kind of the texture bound.
+        kind: Kind,
+    },
+    /// A texture view can't be sampled by the base type of its sampler.
+    TextureFormat {
+        /// Name of the texture in the program.
+        name: String,
+        /// Base type of the sampler.
+        expected: BaseType,
+        /// Format of the texture bound.
+        format: SurfaceType,
+        /// Channel type the texture is viewed as.
+        channel: ChannelType,
+    },
+    /// A texture is expected by the program, but a buffer is bound.
+    BufferView(String),
+    /// The targets have different dimensions.
+    TargetDimensions {
+        /// Dimensions of the first target.
+        expected: Dimensions,
+        /// Dimensions of a mismatching target.
+        found: Dimensions,
+    },
+    /// The scissor extends past the targets.
+    ScissorOutOfBounds {
+        /// The scissor rectangle.
+        scissor: Rect,
+        /// Dimensions of the targets.
+        dimensions: Dimensions,
+    },
+}
+
+impl fmt::Display for DrawError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DrawError::BufferRole { ref expected, ref found } =>
+                write!(f, "{}: {:?} bound as {:?}", self.description(), found, expected),
+            DrawError::IndexOutOfBounds { end, count } =>
+                write!(f, "{}: slice ends at {} with {} indices", self.description(), end, count),
+            DrawError::VertexOutOfBounds { ref attribute, needed, count } =>
+                write!(f, "{}: attribute {:?} needs {} elements, the buffer has {}",
+                       self.description(), attribute, needed, count),
+            DrawError::MissingAttribute(ref name) |
+            DrawError::BufferView(ref name) =>
+                write!(f, "{}: {:?}", self.description(), name),
+            DrawError::TextureType { ref name, ref expected, ref kind } =>
+                write!(f, "{}: {:?} is a {:?}, got {:?}", self.description(), name, expected, kind),
+            DrawError::TextureFormat { ref name, ref expected, ref format, ref channel } =>
+                write!(f, "{}: {:?} samples {:?}, got {:?} viewed as {:?}",
+                       self.description(), name, expected, format, channel),
+            DrawError::TargetDimensions { ref expected, ref found } =>
+                write!(f, "{}: {:?} and {:?}", self.description(), expected, found),
+            DrawError::ScissorOutOfBounds { ref scissor, ref dimensions } =>
+                write!(f, "{}: {:?} in {:?}", self.description(), scissor, dimensions),
+        }
+    }
+}
+
+impl Error for DrawError {
+    fn description(&self) -> &str {
+        match *self {
+            DrawError::BufferRole { .. } => "Buffer of the wrong role",
+            DrawError::IndexOutOfBounds { .. } => "Slice out of the index buffer",
+            DrawError::VertexOutOfBounds { .. } => "Draw out of a vertex buffer",
+            DrawError::MissingAttribute(_) => "Vertex attribute without a buffer",
+            DrawError::TextureType { .. } => "Texture of the wrong type",
+            DrawError::TextureFormat { .. } => "Texture of the wrong format",
+            DrawError::BufferView(_) => "Buffer bound in place of a texture",
+            DrawError::TargetDimensions { .. } => "Targets of different dimensions",
+            DrawError::ScissorOutOfBounds { .. } => "Scissor out of the targets",
+        }
+    }
+}
+
+/// A buffer bound to a vertex attribute.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct VertexBinding {
+    /// Size of the buffer past the offset, in bytes.
+    pub size: usize,
+    /// Size of an element, in bytes.
+    pub stride: ElemStride,
+    /// Instance rate, or 0 for per-vertex data.
+    pub rate: InstanceRate,
+}
+
+/// A texture bound to a shader resource.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TextureBinding {
+    /// Kind of the texture.
+    pub kind: Kind,
+    /// Format of the texture surface.
+    pub surface: SurfaceType,
+    /// Channel type the texture is viewed as.
+    pub channel: ChannelType,
+}
+
+/// The resources bound by the components of a pipeline, recorded in a
+/// `RawDataSet` when validation is enabled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    /// Buffers of the vertex attributes, by slot.
+    pub vertex_buffers: [Option<VertexBinding>; MAX_VERTEX_ATTRIBUTES],
+    /// Textures of the resource views, by slot, or `None` for buffer views.
+    pub resource_views: Vec<(ResourceViewSlot, Option<TextureBinding>)>,
+    /// Dimensions of the targets.
+    pub dimensions: Option<Dimensions>,
+    /// Dimensions of the first target not matching the others.
+    pub mismatch: Option<Dimensions>,
+    /// The scissor, if the pipeline has one.
+    pub scissor: Option<Rect>,
+    /// The expected and actual roles of the first buffer bound to a
+    /// component of another role.
+    pub role_mismatch: Option<(Role, Role)>,
+}
+
+impl Bindings {
+    /// Create an empty set.
+    pub fn new() -> Bindings {
+        Bindings {
+            vertex_buffers: [None; MAX_VERTEX_ATTRIBUTES],
+            resource_views: Vec::new(),
+            dimensions: None,
+            mismatch: None,
+            scissor: None,
+            role_mismatch: None,
+        }
+    }
+
+    /// Clear all contained data.
+    pub fn clear(&mut self) {
+        self.vertex_buffers = [None; MAX_VERTEX_ATTRIBUTES];
+        self.resource_views.clear();
+        self.dimensions = None;
+        self.mismatch = None;
+        self.scissor = None;
+        self.role_mismatch = None;
+    }
+
+    /// Record the role of a buffer bound to a component expecting
+    /// `expected`, and return whether they match.
+    pub fn add_buffer(&mut self, expected: Role, found: Role) -> bool {
+        if expected == found {
+            return true
+        }
+        if self.role_mismatch.is_none() {
+            self.role_mismatch = Some((expected, found));
+        }
+        false
+    }
+
+    /// Record the dimensions of a target, and return whether they match
+    /// the ones of the previous targets.
+    pub fn add_target(&mut self, dim: Dimensions) -> bool {
+        match self.dimensions {
+            Some(d) if d != dim => {
+                if self.mismatch.is_none() {
+                    self.mismatch = Some(dim);
+                }
+                false
+            },
+            _ => {
+                self.dimensions = Some(dim);
+                true
+            },
+        }
+    }
+}
+
+/// Check whether a texture `kind` can be sampled as the type of a sampler.
+fn match_texture_type(ty: TextureType, kind: Kind) -> bool {
+    let multi = match kind {
+        Kind::D2(_, _, aa) | Kind::D2Array(_, _, _, aa) => aa != AaMode::Single,
+        _ => false,
+    };
+    match (ty, kind) {
+        (TextureType::D1(IsArray::NoArray), Kind::D1(..)) |
+        (TextureType::D1(IsArray::Array), Kind::D1Array(..)) |
+        (TextureType::D3, Kind::D3(..)) |
+        (TextureType::Cube(IsArray::NoArray), Kind::Cube(..)) |
+        (TextureType::Cube(IsArray::Array), Kind::CubeArray(..)) => true,
+        (TextureType::D2(IsArray::NoArray, ms), Kind::D2(..)) |
+        (TextureType::D2(IsArray::Array, ms), Kind::D2Array(..)) |
+        // the faces of cube maps can be viewed as a 2D array
+        (TextureType::D2(IsArray::Array, ms), Kind::Cube(..)) |
+        (TextureType::D2(IsArray::Array, ms), Kind::CubeArray(..)) =>
+            multi == (ms == IsMultiSample::MultiSample),
+        _ => false,
+    }
+}
+
+/// Check whether a texture viewed as `channel` can be sampled with a
+/// `base_type`. Normalized and depth views are read as floats.
+fn match_texture_format(base_type: BaseType, channel: ChannelType) -> bool {
+    match channel {
+        ChannelType::Int => base_type == BaseType::I32,
+        ChannelType::Uint => base_type == BaseType::U32,
+        ChannelType::Inorm | ChannelType::Unorm |
+        ChannelType::Float | ChannelType::Srgb => base_type == BaseType::F32,
+    }
+}
+
+/// Get the number of elements a vertex binding needs to have.
+fn get_needed_elements<R: Resources>(slice: &Slice<R>, rate: InstanceRate) -> Option<usize> {
+    if rate == 0 {
+        match slice.buffer {
+            IndexBuffer::Auto => Some((slice.base_vertex + slice.end) as usize),
+            _ => None,
+        }
+    } else {
+        slice.instances.map(|(count, base)| {
+            let end = (base + count) as usize;
+            (end + rate as usize - 1) / rate as usize
+        })
+    }
+}
+
+/// Check a draw of `slice` with a program and the resources bound to it.
+pub fn check_draw<R: Resources>(slice: &Slice<R>, program: &shade::ProgramInfo,
+                                bindings: &Bindings) -> Result<(), DrawError> {
+    if let Some((expected, found)) = bindings.role_mismatch {
+        return Err(DrawError::BufferRole {
+            expected: expected,
+            found: found,
+        })
+    }
+    if let Some(count) = slice.buffer.get_index_count() {
+        if slice.end as usize > count {
+            return Err(DrawError::IndexOutOfBounds {
+                end: slice.end,
+                count: count,
+            })
+        }
+    }
+
+    for attribute in &program.vertex_attributes {
+        let binding = match bindings.vertex_buffers.get(attribute.slot as usize) {
+            Some(&Some(binding)) => binding,
+            _ => return Err(DrawError::MissingAttribute(attribute.name.clone())),
+        };
+        if slice.start == slice.end || binding.stride == 0 {
+            continue
+        }
+        if let Some(needed) = get_needed_elements(slice, binding.rate) {
+            let count = binding.size / binding.stride as usize;
+            if needed > count {
+                return Err(DrawError::VertexOutOfBounds {
+                    attribute: attribute.name.clone(),
+                    needed: needed,
+                    count: count,
+                })
+            }
+        }
+    }
+
+    for &(slot, view) in &bindings.resource_views {
+        let var = match program.textures.iter().find(|t| t.slot == slot) {
+            Some(var) => var,
+            None => continue,
+        };
+        match (var.ty, view) {
+            (TextureType::Buffer, _) => (),
+            (_, None) => return Err(DrawError::BufferView(var.name.clone())),
+            (ty, Some(texture)) => {
+                if !match_texture_type(ty, texture.kind) {
+                    return Err(DrawError::TextureType {
+                        name: var.name.clone(),
+                        expected: ty,
+                        kind: texture.kind,
+                    })
+                }
+                if !match_texture_format(var.base_type, texture.channel) {
+                    return Err(DrawError::TextureFormat {
+                        name: var.name.clone(),
+                        expected: var.base_type,
+                        format: texture.surface,
+                        channel: texture.channel,
+                    })
+                }
+            },
+        }
+    }
+
+    if let (Some(expected), Some(found)) = (bindings.dimensions, bindings.mismatch) {
+        return Err(DrawError::TargetDimensions {
+            expected: expected,
+            found: found,
+        })
+    }
+    if let (Some(dim), Some(rect)) = (bindings.dimensions, bindings.scissor) {
+        if rect.x as u32 + rect.w as u32 > dim.0 as u32 ||
+           rect.y as u32 + rect.h as u32 > dim.1 as u32 {
+            return Err(DrawError::ScissorOutOfBounds {
+                scissor: rect,
+                dimensions: dim,
+            })
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,227 @@
+// Copyright 2017 The Gfx-rs Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Shader permutations.
+//!
+//! A `VariantCache` holds the pipeline states compiled out of a single pair
+//! of GLSL sources, one per set of defines. Variants are built on first use,
+//! so that features can be toggled per material without a hand-written
+//! table of programs:
+//!
+//! ```ignore
+//! let mut cache = VariantCache::new(VS_SRC, PS_SRC, Primitive::TriangleList,
+//!                                   Rasterizer::new_fill(), pipe::new());
+//! let pso = try!(cache.get(&mut factory, &mut preprocessor, &[("NORMAL_MAP", "")]));
+//! encoder.draw(&slice, pso, &data);
+//! ```
+//!
+//! With a target set, the preprocessed sources are also translated to the
+//! GLSL version of the device. The order of the defines doesn't matter.
+//! Variants ending up with the same program interface share the linking of
+//! the pipeline initializer, which is only done once per interface.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use core::{Primitive, Resources};
+use core::factory::Factory;
+use core::pso::Descriptor;
+use core::shade::{ProgramInfo, Stage};
+use core::state::Rasterizer;
+use factory::{FactoryExt, PipelineStateError};
+use preprocess::{IncludeResolver, PreprocessError, Preprocessor};
+use pso::{PipelineInit, PipelineState};
+use shade::ProgramError;
+use translate::{translate, GlslTarget};
+
+/// An error building a variant.
+#[derive(Clone, Debug, PartialEq)]
+pub enum VariantError {
+    /// The shader sources failed to preprocess.
+    Preprocess(PreprocessError),
+    /// The pipeline state failed to be created.
+    Pipeline(PipelineStateError<String>),
+}
+
+impl fmt::Display for VariantError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VariantError::Preprocess(ref e) => write!(f, "{}: {}", self.description(), e),
+            VariantError::Pipeline(ref e) => write!(f, "{}: {}", self.description(), e),
+        }
+    }
+}
+
+impl Error for VariantError {
+    fn description(&self) -> &str {
+        match *self {
+            VariantError::Preprocess(_) => "Shader sources failed to preprocess",
+            VariantError::Pipeline(_) => "Pipeline state failed to be created",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            VariantError::Preprocess(ref e) => Some(e),
+            VariantError::Pipeline(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<PreprocessError> for VariantError {
+    fn from(e: PreprocessError) -> Self {
+        VariantError::Preprocess(e)
+    }
+}
+
+impl From<PipelineStateError<String>> for VariantError {
+    fn from(e: PipelineStateError<String>) -> Self {
+        VariantError::Pipeline(e)
+    }
+}
+
+impl From<ProgramError> for VariantError {
+    fn from(e: ProgramError) -> Self {
+        VariantError::Pipeline(PipelineStateError::Program(e))
+    }
+}
+
+/// The defines of a variant, sorted by name.
+type DefineSet = Vec<(String, String)>;
+
+/// Repeating a define doesn't make another variant.
+fn make_key(defines: &[(&str, &str)]) -> DefineSet {
+    let mut key: DefineSet = defines.iter()
+        .map(|&(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    key.sort();
+    key.dedup();
+    key
+}
+
+/// The variants of a pipeline state, built on demand.
+pub struct VariantCache<R: Resources, I: PipelineInit> {
+    vertex_source: String,
+    pixel_source: String,
+    primitive: Primitive,
+    rasterizer: Rasterizer,
+    init: I,
+    target: Option<GlslTarget>,
+    variants: HashMap<DefineSet, PipelineState<R, I::Meta>>,
+    links: HashMap<ProgramInfo, (Descriptor, I::Meta)>,
+}
+
+impl<R: Resources, I: PipelineInit> VariantCache<R, I> where I::Meta: Clone {
+    /// Create a cache of the variants of the given GLSL sources.
+    pub fn new(vertex_source: &str, pixel_source: &str, primitive: Primitive,
+               rasterizer: Rasterizer, init: I) -> VariantCache<R, I> {
+        VariantCache {
+            vertex_source: vertex_source.to_string(),
+            pixel_source: pixel_source.to_string(),
+            primitive: primitive,
+            rasterizer: rasterizer,
+            init: init,
+            target: None,
+            variants: HashMap::new(),
+            links: HashMap::new(),
+        }
+    }
+
+    /// Get the number of variants built so far.
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+
+    /// Set the GLSL version the sources get translated to, if any. This
+    /// drops the variants built so far.
+    pub fn set_target(&mut self, target: Option<GlslTarget>) {
+        self.target = target;
+        self.clear();
+    }
+
+    /// Drop all the variants, to be built again on their next use. This is
+    /// needed after changing the included files provided by the resolver.
+    pub fn clear(&mut self) {
+        self.variants.clear();
+        self.links.clear();
+    }
+
+    /// Get the variant with `defines`, preprocessing, compiling and linking
+    /// it if it's the first time it's used.
+    pub fn get<F, P>(&mut self, factory: &mut F, preprocessor: &mut Preprocessor<P>,
+                     defines: &[(&str, &str)])
+                     -> Result<&PipelineState<R, I::Meta>, VariantError> where
+        F: Factory<R>,
+        P: IncludeResolver,
+    {
+        let key = make_key(defines);
+        if !self.variants.contains_key(&key) {
+            let pso = try!(self.build(factory, preprocessor, &key));
+            self.variants.insert(key.clone(), pso);
+        }
+        Ok(&self.variants[&key])
+    }
+
+    /// Get the vertex and pixel sources of the variant with `defines`, as
+    /// they are compiled.
+    pub fn get_sources<P>(&self, preprocessor: &mut Preprocessor<P>, defines: &[(&str, &str)])
+                          -> Result<(String, String), PreprocessError> where
+        P: IncludeResolver,
+    {
+        self.process(preprocessor, &make_key(defines))
+    }
+
+    fn process<P>(&self, preprocessor: &mut Preprocessor<P>, key: &DefineSet)
+                  -> Result<(String, String), PreprocessError> where
+        P: IncludeResolver,
+    {
+        let defines: Vec<(&str, &str)> = key.iter()
+            .map(|&(ref name, ref value)| (&name[..], &value[..]))
+            .collect();
+        let mut vs = try!(preprocessor.process("vertex", &self.vertex_source, &defines));
+        let mut ps = try!(preprocessor.process("pixel", &self.pixel_source, &defines));
+        if let Some(target) = self.target {
+            vs = translate(&vs, Stage::Vertex, target);
+            ps = translate(&ps, Stage::Pixel, target);
+        }
+        Ok((vs, ps))
+    }
+
+    fn build<F, P>(&mut self, factory: &mut F, preprocessor: &mut Preprocessor<P>,
+                   key: &DefineSet)
+                   -> Result<PipelineState<R, I::Meta>, VariantError> where
+        F: Factory<R>,
+        P: IncludeResolver,
+    {
+        let (vs, ps) = try!(self.process(preprocessor, key));
+        let program = try!(factory.link_program(vs.as_bytes(), ps.as_bytes()));
+
+        let linked = self.links.get(program.get_info()).cloned();
+        let (descriptor, meta) = match linked {
+            Some(link) => link,
+            None => {
+                let mut descriptor = Descriptor::new(self.primitive, self.rasterizer);
+                let meta = try!(self.init.link_to(&mut descriptor, program.get_info())
+                                    .map_err(|e| VariantError::Pipeline(
+                                        PipelineStateError::DescriptorInit(e.into()))));
+                self.links.insert(program.get_info().clone(), (descriptor, meta.clone()));
+                (descriptor, meta)
+            },
+        };
+        let raw = try!(factory.create_pipeline_state_raw(&program, &descriptor)
+                              .map_err(|e| VariantError::Pipeline(
+                                  PipelineStateError::DeviceCreate(e))));
+        Ok(PipelineState::new(raw, self.primitive, meta))
+    }
+}
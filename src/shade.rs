@@ -15,6 +15,7 @@
 
 use std::error::Error;
 use std::fmt;
+use gfx::translate::GlslTarget;
 
 pub use gfx_device_gl::Version as GlslVersion;
 #[cfg(target_os = "windows")]
@@ -34,6 +35,18 @@ pub enum Backend {
     Vulkan,
 }
 
+impl Backend {
+    /// Get the GLSL version to translate the shaders to, for the GLSL backends.
+    #[allow(unreachable_patterns)]
+    pub fn get_glsl_target(&self) -> Option<GlslTarget> {
+        match *self {
+            Backend::Glsl(v) => Some(GlslTarget::new((v.major * 100 + v.minor) as u16, false)),
+            Backend::GlslEs(v) => Some(GlslTarget::new((v.major * 100 + v.minor) as u16, true)),
+            _ => None,
+        }
+    }
+}
+
 pub const EMPTY: &'static [u8] = &[];
 
 /// A type storing shader source for different graphics APIs and versions.
@@ -14,11 +14,14 @@
 
 use std::os::raw::c_void;
 
-use device_gl::{Device, Factory, Resources as Res, create as gl_create, create_main_targets_raw};
+use device_gl::{Device, Factory, Resources as Res, create as gl_create,
+                create_main_targets_raw, create_offscreen_targets_raw};
 use glutin::{HeadlessContext};
 
-use core::format::{Format, DepthFormat, RenderFormat};
-use core::handle::{DepthStencilView, RawDepthStencilView, RawRenderTargetView, RenderTargetView};
+use core::factory::CombinedError;
+use core::format::{Format, DepthFormat, RenderFormat, TextureFormat};
+use core::handle::{DepthStencilView, RawDepthStencilView, RawRenderTargetView, RenderTargetView,
+                   RawTexture, Texture};
 use core::memory::Typed;
 use core::texture::Dimensions;
 
@@ -82,6 +85,50 @@ pub fn init_headless_raw(context: &HeadlessContext, dim: Dimensions, color: Form
     (device, factory, color_view, ds_view)
 }
 
+/// Initializes device and factory from a headless context, rendering into
+/// an offscreen framebuffer instead of the context's default one.
+///
+/// Headless contexts (EGL pbuffers, surfaceless or OSMesa contexts) are not
+/// guaranteed to have a usable default framebuffer, so the returned views are
+/// backed by textures. The color texture can be copied into a download buffer
+/// to inspect the rendered image, e.g. for thumbnails or reference tests.
+///
+/// Only compiled with `headless` feature.
+pub fn init_headless_offscreen<Cf, Df>(context: &HeadlessContext, dim: Dimensions)
+                                       -> Result<(Device, Factory, Texture<Res, Cf::Surface>,
+                                                  RenderTargetView<Res, Cf>, DepthStencilView<Res, Df>),
+                                                 CombinedError>
+    where
+        Cf: RenderFormat + TextureFormat,
+        Df: DepthFormat + TextureFormat,
+{
+    let (device, factory, color_tex, color_view, ds_view) =
+        try!(init_headless_offscreen_raw(context, dim, Cf::get_format(), Df::get_format()));
+    Ok((device, factory, Typed::new(color_tex), Typed::new(color_view), Typed::new(ds_view)))
+}
+
+/// Raw version of [`init_headless_offscreen`].
+///
+/// [`init_headless_offscreen`]: fn.init_headless_offscreen.html
+pub fn init_headless_offscreen_raw(context: &HeadlessContext, dim: Dimensions,
+                                   color: Format, depth: Format)
+                                   -> Result<(Device, Factory, RawTexture<Res>,
+                                              RawRenderTargetView<Res>, RawDepthStencilView<Res>),
+                                             CombinedError>
+{
+    unsafe { context.make_current().unwrap() };
+
+    let (device, mut factory) = gl_create(|s|
+        context.get_proc_address(s) as *const c_void);
+
+    // create the offscreen color/depth targets
+    let (color_tex, color_view, ds_view) =
+        try!(create_offscreen_targets_raw(&mut factory, dim, color, depth));
+
+    // done
+    Ok((device, factory, color_tex, color_view, ds_view))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,4 +151,21 @@ mod tests {
 
         device.cleanup();
     }
+
+    #[test]
+    fn test_headless_offscreen() {
+        use glutin::{HeadlessRendererBuilder};
+
+        let dim = (64, 64, 1, AaMode::Single);
+
+        let context: HeadlessContext = HeadlessRendererBuilder::new(dim.0 as u32, dim.1 as u32)
+            .build()
+            .expect("Failed to build headless context");
+
+        let (mut device, _, _, color, _) = init_headless_offscreen::<Rgba8, DepthStencil>(&context, dim)
+            .expect("Failed to create offscreen targets");
+        assert_eq!(color.get_dimensions(), dim);
+
+        device.cleanup();
+    }
 }
@@ -19,9 +19,12 @@ extern crate gfx_device_gl as device_gl;
 extern crate glutin;
 
 #[cfg(feature = "headless")]
-pub use headless::{init_headless, init_headless_raw};
+pub use headless::{init_headless, init_headless_raw,
+                   init_headless_offscreen, init_headless_offscreen_raw};
 
-use core::{format, handle, texture};
+use std::error::Error;
+use std::fmt;
+use core::{format, handle, texture, Frame, SwapChainConfig};
 use core::memory::Typed;
 use device_gl::Resources as R;
 
@@ -61,6 +64,145 @@ where
     (window, device, factory, Typed::new(color_view), Typed::new(ds_view))
 }
 
+/// An invalid `SwapChainConfig`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SwapChainError {
+    /// The chain needs at least one buffer.
+    NoBuffer,
+}
+
+impl fmt::Display for SwapChainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+impl Error for SwapChainError {
+    fn description(&self) -> &str {
+        match *self {
+            SwapChainError::NoBuffer => "A swapchain needs at least one buffer",
+        }
+    }
+}
+
+/// Swapchain of a Glutin window.
+///
+/// Owns the window and presents its back buffer, keeping track of the
+/// frames that were acquired and presented. Its configuration is the one
+/// in effect, which may differ from the requested one: glutin only toggles
+/// vsync and double buffering, so the swap interval is 0 or 1, and there
+/// are 1 or 2 buffers.
+pub struct SwapChain {
+    window: glutin::Window,
+    config: SwapChainConfig,
+    frame_index: usize,
+    acquired: bool,
+    present_count: usize,
+}
+
+impl SwapChain {
+    /// Get the window that is presented to.
+    pub fn get_window(&self) -> &glutin::Window {
+        &self.window
+    }
+
+    /// Take the window back, destroying the swapchain.
+    pub fn into_window(self) -> glutin::Window {
+        self.window
+    }
+
+    /// Update the main framebuffer targets after the window has been resized.
+    /// See [`update_views`](fn.update_views.html).
+    pub fn update_views<Cf, Df>(&self, color_view: &mut handle::RenderTargetView<R, Cf>,
+                                ds_view: &mut handle::DepthStencilView<R, Df>)
+    where
+        Cf: format::RenderFormat,
+        Df: format::DepthFormat,
+    {
+        update_views(&self.window, color_view, ds_view)
+    }
+}
+
+impl core::SwapChain for SwapChain {
+    fn get_config(&self) -> SwapChainConfig {
+        self.config
+    }
+
+    fn acquire_frame(&mut self) -> Frame {
+        debug_assert!(!self.acquired, "The previous frame was not presented");
+        self.acquired = true;
+        Frame::new(self.frame_index)
+    }
+
+    fn present(&mut self) {
+        debug_assert!(self.acquired, "No frame was acquired before presenting");
+        self.window.swap_buffers().unwrap();
+        self.acquired = false;
+        self.frame_index = (self.frame_index + 1) % self.config.buffer_count as usize;
+        self.present_count += 1;
+    }
+
+    fn get_present_count(&self) -> usize {
+        self.present_count
+    }
+}
+
+/// Initialize with a window builder, wrapping the window into a `SwapChain`
+/// configured with the given presentation settings.
+/// Generically parametrized version over the main framebuffer format.
+pub fn init_swapchain<Cf, Df>(builder: glutin::WindowBuilder, events_loop: &glutin::EventsLoop,
+                              config: SwapChainConfig) ->
+            Result<(SwapChain, device_gl::Device, device_gl::Factory,
+                    handle::RenderTargetView<R, Cf>, handle::DepthStencilView<R, Df>),
+                   SwapChainError>
+where
+    Cf: format::RenderFormat,
+    Df: format::DepthFormat,
+{
+    let (swap_chain, device, factory, color_view, ds_view) = try!(
+        init_swapchain_raw(builder, events_loop, config, Cf::get_format(), Df::get_format()));
+    Ok((swap_chain, device, factory, Typed::new(color_view), Typed::new(ds_view)))
+}
+
+/// Initialize with a window builder and presentation settings. Raw version.
+pub fn init_swapchain_raw(builder: glutin::WindowBuilder, events_loop: &glutin::EventsLoop,
+                          config: SwapChainConfig,
+                          color_format: format::Format, ds_format: format::Format) ->
+                          Result<(SwapChain, device_gl::Device, device_gl::Factory,
+                                  handle::RawRenderTargetView<R>,
+                                  handle::RawDepthStencilView<R>),
+                                 SwapChainError>
+{
+    let config = try!(get_effective_config(config));
+    let builder = builder.with_double_buffer(Some(config.buffer_count > 1));
+    let builder = if config.is_vsync() {
+        builder.with_vsync()
+    } else {
+        builder
+    };
+    let (window, device, factory, color_view, ds_view) =
+        init_raw(builder, events_loop, color_format, ds_format);
+    let swap_chain = SwapChain {
+        window: window,
+        config: config,
+        frame_index: 0,
+        acquired: false,
+        present_count: 0,
+    };
+    Ok((swap_chain, device, factory, color_view, ds_view))
+}
+
+/// Get the configuration glutin can provide for the requested one.
+pub fn get_effective_config(config: SwapChainConfig) -> Result<SwapChainConfig, SwapChainError> {
+    if config.buffer_count == 0 {
+        return Err(SwapChainError::NoBuffer)
+    }
+    Ok(SwapChainConfig {
+        swap_interval: if config.is_vsync() { 1 } else { 0 },
+        buffer_count: if config.buffer_count > 1 { 2 } else { 1 },
+    })
+}
+
 /// Initialize with an existing Glutin window.
 /// Generically parametrized version over the main framebuffer format.
 ///
@@ -123,15 +265,22 @@ pub fn init_raw(builder: glutin::WindowBuilder, events_loop: &glutin::EventsLoop
     (window, device, factory, color_view, ds_view)
 }
 
+/// Create the device and factory for an existing Glutin window, making its
+/// context current and loading the GL functions from it.
+/// The main framebuffer targets are left to the caller.
+pub fn init_device(window: &glutin::Window) -> (device_gl::Device, device_gl::Factory) {
+    unsafe { window.make_current().unwrap() };
+    device_gl::create(|s|
+        window.get_proc_address(s) as *const std::os::raw::c_void)
+}
+
 /// Initialize with an existing Glutin window. Raw version.
 pub fn init_existing_raw(window: &glutin::Window,
                 color_format: format::Format, ds_format: format::Format) ->
                 (device_gl::Device, device_gl::Factory,
                 handle::RawRenderTargetView<R>, handle::RawDepthStencilView<R>)
 {
-    unsafe { window.make_current().unwrap() };
-    let (device, factory) = device_gl::create(|s|
-        window.get_proc_address(s) as *const std::os::raw::c_void);
+    let (device, factory) = init_device(window);
 
     // create the main color/depth targets
     let dim = get_window_dimensions(window);
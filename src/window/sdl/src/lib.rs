@@ -103,7 +103,6 @@ where
     Cf: RenderFormat,
     Df: DepthFormat,
 {
-    use core::memory::Typed;
     init_raw(builder, Cf::get_format(), Df::get_format())
         .map(|(w, gl, d, f, color_view, ds_view)|
             (w, gl, d, f, Typed::new(color_view), Typed::new(ds_view)))
@@ -111,8 +110,6 @@ where
 
 pub fn init_raw(mut builder: WindowBuilder, cf: Format, df: Format)
                 -> Result<InitRawOk, InitError> {
-    use core::texture::{AaMode, Size};
-
     let mut window = builder.opengl().build()?;
 
     let display_mode = DisplayMode {
@@ -134,19 +131,48 @@ pub fn init_raw(mut builder: WindowBuilder, cf: Format, df: Format)
 
     let context = window.gl_create_context()?;
 
+    let (device, factory, color_view, ds_view) = init_existing_raw(&window, &context, cf, df)?;
+
+    Ok((window, context, device, factory, color_view, ds_view))
+}
+
+/// Initialize with an existing SDL2 window and its GL context, for applications
+/// that manage the window themselves. The context is made current on the window,
+/// and stays owned by the caller.
+/// Generically parametrized version over the main framebuffer format.
+pub fn init_existing<Cf, Df>(window: &Window, context: &GLContext)
+                             -> Result<(Device, Factory,
+                                        handle::RenderTargetView<Resources, Cf>,
+                                        handle::DepthStencilView<Resources, Df>), InitError>
+where
+    Cf: RenderFormat,
+    Df: DepthFormat,
+{
+    init_existing_raw(window, context, Cf::get_format(), Df::get_format())
+        .map(|(d, f, color_view, ds_view)|
+            (d, f, Typed::new(color_view), Typed::new(ds_view)))
+}
+
+/// Initialize with an existing SDL2 window and its GL context. Raw version.
+pub fn init_existing_raw(window: &Window, context: &GLContext, cf: Format, df: Format)
+                         -> Result<(Device, Factory,
+                                    handle::RawRenderTargetView<Resources>,
+                                    handle::RawDepthStencilView<Resources>), InitError> {
+    window.gl_make_current(context)?;
+
     let (device, factory) = gfx_device_gl::create(|s| {
         window.subsystem().gl_get_proc_address(s) as *const std::os::raw::c_void
     });
 
-    let (width, height) = window.drawable_size();
-    let dim = (width as Size, height as Size, 1, AaMode::Single);
+    let dim = get_window_dimensions(window);
     let (color_view, ds_view) = gfx_device_gl::create_main_targets_raw(dim, cf.0, df.0);
 
-    Ok((window, context, device, factory, color_view, ds_view))
+    Ok((device, factory, color_view, ds_view))
 }
 
 fn get_window_dimensions(window: &sdl2::video::Window) -> texture::Dimensions {
-    let (width, height) = window.size();
+    // the drawable size is in pixels, which differs from the window size on HiDPI displays
+    let (width, height) = window.drawable_size();
     let aa = window.subsystem().gl_attr().multisample_samples() as texture::NumSamples;
     (width as texture::Size, height as texture::Size, 1, aa.into())
 }
@@ -0,0 +1,34 @@
+#[macro_use]
+extern crate gfx;
+extern crate gfx_core as core;
+
+use core::dummy::DummyResources;
+use gfx::{BundleContext, CoreBundle, Slice};
+
+gfx_defines!{
+    vertex Vertex {
+        pos: [f32; 2] = "a_Pos",
+    }
+
+    pipeline pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+    }
+}
+
+#[test]
+fn test_bundle_context() {
+    let mut context: BundleContext<DummyResources, pipe::Data<DummyResources>> =
+        BundleContext::new();
+    assert_eq!(context.add_slice(Slice::new_auto(0, 3)), 0);
+    assert_eq!(context.add_slice(Slice::new_auto(3, 9)), 1);
+    assert_eq!(context.get_slice(1).end, 9);
+
+    let mut bundles = vec![
+        CoreBundle { pso: 1, data: 0, slice: 0 },
+        CoreBundle { pso: 0, data: 1, slice: 1 },
+        CoreBundle { pso: 0, data: 0, slice: 1 },
+    ];
+    bundles.sort();
+    assert_eq!(bundles.iter().map(|b| (b.pso, b.data)).collect::<Vec<_>>(),
+               vec![(0, 0), (0, 1), (1, 0)]);
+}
@@ -0,0 +1,89 @@
+extern crate gfx_core as core;
+
+use core::{buffer, format, memory, texture, Factory, IndexType};
+use core::capture::{BufferDesc, Command, Recorder, TextureDesc};
+use core::command::{Buffer, ClearColor};
+use core::dummy::{DummyFactory, DummyResources};
+
+#[test]
+fn test_capture_replay() {
+    let mut recorder: Recorder<DummyResources> = Recorder::new();
+    recorder.update_buffer((), &[1, 2, 3, 4], 8);
+    recorder.push_debug_group("quad");
+    recorder.bind_index((), IndexType::U16);
+    recorder.clear_color((), ClearColor::Float([0.0, 0.0, 0.0, 1.0]));
+    recorder.call_draw_indexed(0, 6, 0, None);
+    recorder.memory_barrier(memory::SHADER_RESOURCE_BARRIER | memory::UNORDERED_ACCESS_BARRIER);
+    recorder.pop_debug_group();
+    assert_eq!(recorder.get_capture().commands[0],
+               Command::UpdateBuffer(0, vec![1, 2, 3, 4], 8));
+    assert_eq!(recorder.get_capture().commands[1],
+               Command::PushDebugGroup("quad".to_string()));
+    assert_eq!(recorder.get_capture().commands[5],
+               Command::MemoryBarrier(memory::SHADER_RESOURCE_BARRIER |
+                                      memory::UNORDERED_ACCESS_BARRIER));
+
+    let mut replayed: Recorder<DummyResources> = Recorder::new();
+    recorder.get_capture().replay(&recorder.get_resources(), &mut replayed);
+    assert_eq!(recorder.get_capture(), replayed.get_capture());
+
+    recorder.reset();
+    assert!(recorder.get_capture().commands.is_empty());
+}
+
+#[test]
+fn test_capture_descriptors() {
+    let mut factory = DummyFactory::new();
+    let mut recorder: Recorder<DummyResources> = Recorder::new();
+    let data = [1, 2, 3, 4];
+    let vbuf = factory.create_buffer_immutable_raw(&data, 4, buffer::Role::Vertex,
+                                                   memory::Bind::empty()).unwrap();
+    assert_eq!(recorder.describe_buffer(&vbuf, Some(&data)), 0);
+    let info = texture::Info {
+        kind: texture::Kind::D2(2, 2, texture::AaMode::Single),
+        levels: 1,
+        format: format::SurfaceType::R8_G8_B8_A8,
+        bind: memory::SHADER_RESOURCE,
+        usage: memory::Usage::Dynamic,
+    };
+    let tex = factory.create_texture_raw(info, Some(format::ChannelType::Unorm), None).unwrap();
+    assert_eq!(recorder.describe_texture(&tex, Some(format::ChannelType::Unorm), None), 0);
+    recorder.update_buffer((), &[5, 6], 0);
+
+    let capture = recorder.get_capture();
+    assert_eq!(capture.buffers, vec![Some(BufferDesc {
+        info: *vbuf.get_info(),
+        data: Some(data.to_vec()),
+    })]);
+    assert_eq!(capture.textures, vec![Some(TextureDesc {
+        info: info,
+        channel: Some(format::ChannelType::Unorm),
+        data: None,
+    })]);
+    assert!(capture.samplers.is_empty());
+
+    recorder.reset();
+    assert!(recorder.get_capture().commands.is_empty());
+    assert_eq!(recorder.get_capture().buffers.len(), 1);
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_capture_save_load() {
+    use core::capture::Capture;
+    let mut factory = DummyFactory::new();
+    let mut recorder: Recorder<DummyResources> = Recorder::new();
+    let sampler = factory.create_sampler(texture::SamplerInfo::new(
+        texture::FilterMethod::Bilinear, texture::WrapMode::Clamp));
+    recorder.describe_sampler(&sampler);
+    recorder.update_buffer((), &[1, 2, 3, 4], 8);
+    recorder.push_debug_group("quad");
+    recorder.call_draw(0, 3, None);
+    recorder.pop_debug_group();
+
+    let mut file = Vec::new();
+    recorder.get_capture().save(&mut file).unwrap();
+    let loaded = Capture::load(&mut &file[..]).unwrap();
+    assert_eq!(&loaded, recorder.get_capture());
+    assert_eq!(loaded.samplers, vec![Some(*sampler.get_info())]);
+}
@@ -0,0 +1,32 @@
+extern crate gfx;
+
+use gfx::debug_draw::DebugDraw;
+
+#[test]
+fn test_debug_shapes() {
+    let mut debug = DebugDraw::new();
+    debug.wire_box([-1.0; 3], [1.0; 3], [1.0; 4]);
+    assert_eq!(debug.get_vertices().len(), 24);
+    for pair in debug.get_vertices().chunks(2) {
+        let changed = (0 .. 3).filter(|&i| pair[0].pos[i] != pair[1].pos[i]).count();
+        assert_eq!(changed, 1);
+    }
+
+    let mut axis = DebugDraw::new();
+    axis.axis([0.0; 3], 2.0);
+    let ends: Vec<_> = axis.get_vertices().chunks(2).map(|pair| pair[1].pos).collect();
+    assert_eq!(ends, vec![[2.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 0.0, 2.0]]);
+}
+
+#[test]
+fn test_debug_frustum() {
+    let identity = [[1.0, 0.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0, 0.0],
+                    [0.0, 0.0, 1.0, 0.0],
+                    [0.0, 0.0, 0.0, 1.0]];
+    let mut frustum = DebugDraw::new();
+    frustum.frustum(identity, 0.0 .. 1.0, [1.0; 4]);
+    let mut cube = DebugDraw::new();
+    cube.wire_box([-1.0, -1.0, 0.0], [1.0; 3], [1.0; 4]);
+    assert_eq!(frustum.get_vertices(), cube.get_vertices());
+}
@@ -0,0 +1,52 @@
+extern crate gfx;
+extern crate gfx_core as core;
+
+use std::thread;
+use core::dummy::{DummyCommandBuffer, DummyDevice, DummyResources};
+use core::trace::{TraceCommandBuffer, TraceDevice};
+
+type Encoder = gfx::Encoder<DummyResources, DummyCommandBuffer>;
+type TraceEncoder = gfx::Encoder<DummyResources, TraceCommandBuffer<DummyCommandBuffer>>;
+
+fn assert_send<T: Send>() {}
+
+#[test]
+fn test_encoder_send() {
+    assert_send::<Encoder>();
+}
+
+#[test]
+fn test_parallel_recording() {
+    let workers: Vec<_> = (0..3)
+        .map(|i| {
+            let mut encoder: TraceEncoder = TraceCommandBuffer::new(DummyCommandBuffer).into();
+            thread::spawn(move || {
+                encoder.push_debug_group(&format!("chunk {}", i));
+                encoder.pop_debug_group();
+                encoder
+            })
+        })
+        .collect();
+    let mut encoders: Vec<TraceEncoder> = workers
+        .into_iter()
+        .map(|w| w.join().unwrap())
+        .collect();
+    let mut device = TraceDevice::new(DummyDevice::new(), Vec::new());
+    TraceEncoder::flush_all(encoders.iter_mut(), &mut device);
+    // the encoders are reset, so flushing them again submits nothing
+    TraceEncoder::flush_all(encoders.iter_mut(), &mut device);
+
+    let (_, output) = device.into_inner();
+    let log = String::from_utf8(output).unwrap();
+    let mut expected = "frame 0\n".to_string();
+    for i in 0..3 {
+        expected += &format!("  pin_submitted_resources(<0 handles>)\n\
+                              \x20 submit\n\
+                              \x20   push_debug_group(\"chunk {}\")\n\
+                              \x20   pop_debug_group()\n", i);
+    }
+    for _ in 0..3 {
+        expected += "  pin_submitted_resources(<0 handles>)\n  submit\n";
+    }
+    assert_eq!(log, expected);
+}
@@ -0,0 +1,21 @@
+extern crate gfx_core as core;
+
+use core::dummy::{DummyFactory, DummyResources};
+use core::factory::Factory;
+use core::format::DepthStencil;
+use core::handle::DepthStencilView;
+use core::memory::{Typed, DEPTH_STENCIL};
+use core::texture::{AaMode, Kind};
+
+#[test]
+fn test_depth_stencil_surface() {
+    let mut factory = DummyFactory::new();
+    let aa = AaMode::Multi(4);
+    let target: DepthStencilView<DummyResources, DepthStencil> =
+        factory.create_depth_stencil_surface(64, 32, aa).unwrap();
+    let info = target.raw().get_texture().get_info();
+    assert_eq!(info.kind, Kind::D2(64, 32, aa));
+    assert_eq!(info.kind.get_dimensions().3, aa);
+    assert_eq!(info.bind, DEPTH_STENCIL);
+    assert_eq!(info.levels, 1);
+}
@@ -0,0 +1,39 @@
+#[macro_use]
+extern crate gfx;
+
+use gfx::format::{self, BufferFormat, Formatted, F16, I10x3A2Norm, U10x3A2Norm};
+
+gfx_defines!{
+    vertex PackedVertex {
+        pos: [f32; 3] = "a_Pos",
+        normal: I10x3A2Norm = "a_Normal",
+        color: [u8; 4] = "a_Color",
+    }
+}
+
+fn assert_buffer_format<T: BufferFormat>() {}
+
+#[test]
+fn test_packed_formats() {
+    assert_buffer_format::<I10x3A2Norm>();
+    assert_buffer_format::<U10x3A2Norm>();
+    assert_eq!(I10x3A2Norm::get_format(),
+               format::Format(format::SurfaceType::R10_G10_B10_A2, format::ChannelType::Inorm));
+    assert_eq!(U10x3A2Norm::pack([1.0, 0.0, 2.0, 1.0]).0,
+               0x3FF | 0x3FF << 20 | 0x3 << 30);
+    assert_eq!(I10x3A2Norm::pack([1.0, -1.0, 0.0, -1.0]).0,
+               0x1FF | 0x201 << 10 | 0x3 << 30);
+}
+
+#[test]
+fn test_half_float() {
+    assert_eq!(<[F16; 4] as Formatted>::get_format(),
+               format::Format(format::SurfaceType::R16_G16_B16_A16, format::ChannelType::Float));
+    assert_eq!(F16::from_f32(1.0).0, 0x3C00);
+    assert_eq!(F16::from_f32(-2.5).0, 0xC100);
+    assert_eq!(F16::from_f32(1.0e6).0, 0x7C00);
+    for &v in [0.0f32, 0.5, -0.333, 65504.0, 6.0e-8].iter() {
+        let back = F16::from_f32(v).to_f32();
+        assert!((back - v).abs() <= v.abs() / 1024.0 + 6.0e-8, "{} became {}", v, back);
+    }
+}
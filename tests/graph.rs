@@ -0,0 +1,102 @@
+extern crate gfx;
+extern crate gfx_core as core;
+
+use core::dummy::{DummyCommandBuffer, DummyResources};
+use core::format::{DepthStencil, Formatted, Rgba8};
+use core::handle::{Manager, Producer, RenderTargetView};
+use core::memory::{Typed, Usage, RENDER_TARGET};
+use core::texture;
+use gfx::graph::{FrameGraph, GraphError, TargetDesc};
+
+type Graph = FrameGraph<DummyResources, DummyCommandBuffer>;
+
+fn mock_target() -> RenderTargetView<DummyResources, Rgba8> {
+    let mut manager = Manager::new();
+    let kind = texture::Kind::D2(4, 4, texture::AaMode::Single);
+    let tex = manager.make_texture((), texture::Info {
+        kind: kind,
+        levels: 1,
+        format: core::format::SurfaceType::R8_G8_B8_A8,
+        bind: RENDER_TARGET,
+        usage: Usage::Data,
+    });
+    Typed::new(manager.make_rtv((), &tex, kind.get_dimensions()))
+}
+
+fn desc() -> TargetDesc {
+    TargetDesc {
+        width: 4,
+        height: 4,
+        format: Rgba8::get_format(),
+    }
+}
+
+#[test]
+fn test_graph_order() {
+    let mut graph = Graph::new();
+    let albedo = graph.create_color::<Rgba8>(4, 4).id();
+    let unused = graph.create_target(desc());
+    let screen = graph.import_color(&mock_target()).id();
+    graph.add_pass("compose", &[albedo], &[screen], |_, _| ());
+    graph.add_pass("debug", &[], &[unused], |_, _| ());
+    graph.add_pass("geometry", &[], &[albedo], |_, _| ());
+    graph.add_pass("overlay", &[], &[screen], |_, _| ());
+    assert_eq!(graph.get_pass_order(), Ok(vec!["geometry", "compose", "overlay"]));
+}
+
+#[test]
+fn test_graph_errors() {
+    let mut graph = Graph::new();
+    let a = graph.create_target(desc());
+    let b = graph.create_target(desc());
+    let screen = graph.import_color(&mock_target()).id();
+    graph.add_pass("first", &[b], &[a], |_, _| ());
+    graph.add_pass("second", &[a], &[b, screen], |_, _| ());
+    assert_eq!(graph.get_pass_order(),
+               Err(GraphError::Cycle(vec!["first".to_string(), "second".to_string()])));
+
+    let mut graph = Graph::new();
+    let missing = graph.create_target(desc());
+    let screen = graph.import_color(&mock_target()).id();
+    graph.add_pass("compose", &[missing], &[screen], |_, _| ());
+    match graph.get_pass_order() {
+        Err(GraphError::Unwritten { ref pass, target }) if pass == "compose" && target == missing => (),
+        other => panic!("Unexpected order: {:?}", other),
+    }
+}
+
+#[test]
+fn test_graph_validation() {
+    let mut graph = Graph::new();
+    let screen = graph.import_color(&mock_target()).id();
+    let depth = graph.create_depth::<DepthStencil>(8, 8).id();
+    graph.add_pass("main", &[], &[screen, depth], |_, _| ());
+    assert_eq!(graph.get_target_desc(screen).map(|d| (d.width, d.height)), Some((4, 4)));
+    assert_eq!(graph.get_pass_order(), Err(GraphError::SizeMismatch {
+        pass: "main".to_string(),
+        target: depth,
+        expected: (4, 4),
+        found: (8, 8),
+    }));
+
+    let mut graph = Graph::new();
+    let screen = graph.import_color(&mock_target()).id();
+    let a = graph.create_depth::<DepthStencil>(4, 4).id();
+    let b = graph.create_depth::<DepthStencil>(4, 4).id();
+    graph.add_pass("main", &[], &[screen, a, b], |_, _| ());
+    assert_eq!(graph.get_pass_order(), Err(GraphError::MultipleDepth("main".to_string())));
+}
+
+#[test]
+fn test_graph_window_size() {
+    let mut graph = Graph::new();
+    graph.set_window_size(640, 480);
+    let full = graph.create_window_color::<Rgba8>(1).id();
+    let half = graph.create_window_depth::<DepthStencil>(2).id();
+    assert_eq!(graph.get_target_desc(full).map(|d| (d.width, d.height)), Some((640, 480)));
+
+    graph.set_window_size(99, 1);
+    assert_eq!(graph.get_target_desc(full).map(|d| (d.width, d.height)), Some((99, 1)));
+    assert_eq!(graph.get_target_desc(half).map(|d| (d.width, d.height)), Some((50, 1)));
+    assert_eq!(graph.get_window_size(), (99, 1));
+}
@@ -0,0 +1,44 @@
+extern crate gfx;
+
+use gfx::mesh::{generate, Geometry};
+
+fn check(geometry: &Geometry) {
+    assert_eq!(geometry.indices.len() % 3, 0);
+    for &index in &geometry.indices {
+        assert!((index as usize) < geometry.vertices.len());
+    }
+    for vertex in &geometry.vertices {
+        let n = vertex.normal;
+        let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        assert!((length - 1.0).abs() < 1e-5);
+        assert!(vertex.pos.iter().all(|&c| c >= -1.0 && c <= 1.0));
+    }
+}
+
+#[test]
+fn test_generate() {
+    let cube = generate::cube();
+    assert_eq!((cube.vertices.len(), cube.indices.len()), (24, 36));
+    check(&cube);
+    check(&generate::uv_sphere(16, 8));
+    check(&generate::plane(4));
+    check(&generate::cylinder(12));
+    assert_eq!(generate::fullscreen_triangle().indices, vec![0, 1, 2]);
+}
+
+#[test]
+fn test_fullscreen_triangle() {
+    let triangle = generate::fullscreen_triangle();
+    for v in &triangle.vertices {
+        assert_eq!(v.tex_coord, [(v.pos[0] + 1.0) / 2.0, (v.pos[1] + 1.0) / 2.0]);
+    }
+    let (a, b, c) = (triangle.vertices[0].pos, triangle.vertices[1].pos,
+                     triangle.vertices[2].pos);
+    let side = |p: [f32; 3], q: [f32; 3], r: [f32; 2]|
+        (q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0]);
+    for &corner in [[-1.0, -1.0], [1.0, -1.0], [-1.0, 1.0], [1.0, 1.0]].iter() {
+        assert!(side(a, b, corner) >= 0.0);
+        assert!(side(b, c, corner) >= 0.0);
+        assert!(side(c, a, corner) >= 0.0);
+    }
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "obj")]
+extern crate gfx;
+
+use gfx::mesh::obj::{load_mtl, load_obj};
+
+const QUAD: &'static str = "
+mtllib quad.mtl
+v -1 -1 0
+v 1 -1 0
+v 1 1 0
+v -1 1 0
+vt 0 0
+vt 1 1
+vn 0 0 1
+usemtl red
+f 1/1/1 2/1/1 3/2/1 4/2/1
+usemtl blue
+f -4 -2 -1
+";
+
+const MATERIALS: &'static str = "
+# two materials
+newmtl red
+Kd 1 0 0
+Ns 10
+newmtl blue
+Kd 0 0 1
+map_Kd -s 2 2 1 blue.png
+";
+
+#[test]
+fn test_load_obj() {
+    let model = load_obj(QUAD.as_bytes()).unwrap();
+    assert_eq!(model.material_libraries, vec!["quad.mtl".to_string()]);
+    assert_eq!(model.indices.len(), 9);
+    // the quad shares its 4 vertices, the triangle gets its own
+    assert_eq!(model.vertices.len(), 7);
+    assert_eq!(model.groups.len(), 2);
+    assert_eq!((model.groups[0].start, model.groups[0].end), (0, 6));
+    assert_eq!(model.groups[1].material, Some("blue".to_string()));
+    assert_eq!(model.vertices[6].normal, [0.0, 0.0, 1.0]);
+
+    assert!(load_obj("f 1 2 3".as_bytes()).is_err());
+}
+
+#[test]
+fn test_load_mtl() {
+    let materials = load_mtl(MATERIALS.as_bytes()).unwrap();
+    assert_eq!(materials["red"].diffuse, [1.0, 0.0, 0.0]);
+    assert_eq!(materials["red"].shininess, 10.0);
+    assert_eq!(materials["blue"].diffuse_map, Some("blue.png".to_string()));
+}
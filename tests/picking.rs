@@ -0,0 +1,45 @@
+#![cfg(feature = "picking")]
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::{CopyError, Encoder};
+use gfx::picking::{self, Picker, RequestError, LATENCY, NO_OBJECT};
+use core::dummy::{DummyCommandBuffer, DummyFactory, DummyResources};
+
+#[test]
+fn test_id_encoding() {
+    assert_eq!(picking::decode_id(NO_OBJECT), None);
+    for &index in [0, 1, 41, 1000].iter() {
+        let id = picking::encode_id(index);
+        assert!(id != NO_OBJECT);
+        assert_eq!(picking::decode_id(id), Some(index));
+    }
+}
+
+#[test]
+fn test_request_bookkeeping() {
+    let mut factory = DummyFactory::new();
+    let mut encoder: Encoder<DummyResources, DummyCommandBuffer> = DummyCommandBuffer.into();
+    let mut picker = Picker::new(&mut factory, 64, 32).unwrap();
+
+    match picker.request(&mut factory, &mut encoder, 64, 0) {
+        Err(RequestError::Copy(CopyError::OutOfSrcBounds { .. })) => (),
+        other => panic!("Unexpected request result: {:?}", other),
+    }
+    assert_eq!(picker.get_pending_count(), 0);
+    assert_eq!(picker.get_free_count(), 1);
+
+    picker.request(&mut factory, &mut encoder, 10, 20).unwrap();
+    assert_eq!(picker.get_free_count(), 0);
+    assert_eq!(picker.get_pending_count(), 1);
+    for _ in 0..LATENCY {
+        assert_eq!(picker.poll(&mut factory), Ok(None));
+        assert_eq!(picker.get_pending_count(), 1);
+    }
+    // the dummy buffers can't be mapped, so the read back fails, and the
+    // buffer is kept for the next request
+    assert!(picker.poll(&mut factory).is_err());
+    assert_eq!(picker.get_pending_count(), 0);
+    assert_eq!(picker.get_free_count(), 1);
+    assert_eq!(picker.poll(&mut factory), Ok(None));
+}
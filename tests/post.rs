@@ -0,0 +1,68 @@
+#[macro_use]
+extern crate gfx;
+extern crate gfx_core as core;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use gfx::Encoder;
+use gfx::format::Srgba8;
+use gfx::post::{FullScreenTriangle, PostChain, COPY_SHADER_GLSL, VERTEX_SHADER_GLSL};
+use gfx::traits::{Factory, FactoryExt};
+use core::dummy::{DummyCommandBuffer, DummyFactory, DummyResources};
+
+gfx_defines!{
+    pipeline copy {
+        vbuf: gfx::VertexBuffer<gfx::post::Vertex> = (),
+        source: gfx::TextureSampler<[f32; 4]> = "t_Source",
+        out: gfx::RenderTarget<Srgba8> = "Target0",
+    }
+}
+
+type Chain = PostChain<DummyResources, DummyCommandBuffer, Srgba8>;
+
+/// The dummy handles of the same kind compare equal, so the views of a
+/// stage are told apart by their address in the chain.
+fn address<T>(view: &T) -> usize {
+    view as *const T as usize
+}
+
+#[test]
+fn test_post_execute() {
+    let mut factory = DummyFactory::new();
+    let mut encoder: Encoder<DummyResources, DummyCommandBuffer> = DummyCommandBuffer.into();
+    let triangle = FullScreenTriangle::new(&mut factory).unwrap();
+    let (_, source, main) = factory.create_render_target::<Srgba8>(64, 32).unwrap();
+    let sampler = factory.create_sampler_linear();
+    let mut chain = Chain::new(&mut factory, triangle.clone(), 64, 32).unwrap();
+    let stages = Rc::new(RefCell::new(Vec::new()));
+    for _ in 0..3 {
+        let pso = factory.create_pipeline_simple(VERTEX_SHADER_GLSL, COPY_SHADER_GLSL, copy::new())
+                         .unwrap();
+        let data = copy::Data {
+            vbuf: triangle.vertices.clone(),
+            source: (source.clone(), sampler.clone()),
+            out: main.clone(),
+        };
+        let stages = stages.clone();
+        chain.add_pass(pso, data, move |data, stage| {
+            stages.borrow_mut().push((stage.index, address(stage.source),
+                                      stage.target.map(address)));
+            data.source = (stage.source.clone(), stage.sampler.clone());
+            if let Some(target) = stage.target {
+                data.out = target.clone();
+            }
+        });
+    }
+    chain.execute(&mut encoder);
+
+    let stages = stages.borrow();
+    let scene = Some(address(chain.get_target()));
+    assert_eq!(stages.iter().map(|s| s.0).collect::<Vec<_>>(), vec![0, 1, 2]);
+    // the passes read and write the two targets in turn
+    assert!(stages[0].1 != stages[1].1);
+    assert_eq!(stages[0].1, stages[2].1);
+    assert!(stages[0].2.is_some() && stages[0].2 != scene);
+    assert_eq!(stages[1].2, scene);
+    // the last pass draws into the target of its own data
+    assert_eq!(stages[2].2, None);
+}
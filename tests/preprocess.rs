@@ -0,0 +1,46 @@
+extern crate gfx;
+
+use std::collections::HashMap;
+use gfx::preprocess::{PreprocessError, Preprocessor};
+
+const MAIN: &'static str = "#version 150 core
+#include \"lighting.glsl\"
+#include <common.glsl>
+void main() {}
+";
+
+fn library() -> HashMap<&'static str, &'static str> {
+    let mut lib = HashMap::new();
+    lib.insert("common.glsl", "#pragma once\nfloat saturate(float x);\n");
+    lib.insert("lighting.glsl", "#include \"common.glsl\"\nvec3 light();\n");
+    lib.insert("loop.glsl", "#include \"loop.glsl\"\n");
+    lib
+}
+
+#[test]
+fn test_include_and_define() {
+    let lib = library();
+    let mut pp = Preprocessor::new(|name: &str| lib.get(name).map(|s| s.to_string()));
+    let out = pp.process("main.glsl", MAIN, &[("SHADOWS", ""), ("TAPS", "4")]).unwrap();
+    assert_eq!(out, "#version 150 core
+#define SHADOWS
+#define TAPS 4
+float saturate(float x);
+vec3 light();
+void main() {}
+");
+}
+
+#[test]
+fn test_include_errors() {
+    let lib = library();
+    let mut pp = Preprocessor::new(|name: &str| lib.get(name).map(|s| s.to_string()));
+    assert_eq!(pp.process("main.glsl", "\n#include \"missing.glsl\"\n", &[]),
+               Err(PreprocessError::NotFound("main.glsl".to_string(), 2,
+                                             "missing.glsl".to_string())));
+    assert_eq!(pp.process("main.glsl", "#include \"loop.glsl\"\n", &[]),
+               Err(PreprocessError::Recursive("loop.glsl".to_string(), 1,
+                                              "loop.glsl".to_string())));
+    assert_eq!(pp.process("main.glsl", "#include common.glsl\n", &[]),
+               Err(PreprocessError::Syntax("main.glsl".to_string(), 1)));
+}
@@ -0,0 +1,106 @@
+#[macro_use]
+extern crate gfx;
+extern crate gfx_core;
+
+use gfx::pso::{Descriptor, InitError, PipelineInit, PointInfo, PointOrigin};
+use gfx::{Primitive, ProgramInfo};
+use gfx::state::Rasterizer;
+use gfx_core::dummy::{dummy_attribute, dummy_constant};
+use gfx_core::shade::{self, AttributeVar, AtomicCounterBufferVar, BaseType, ConstVar, ContainerType};
+
+gfx_defines!{
+    vertex Position {
+        pos: [f32; 3] = "a_Pos",
+    }
+
+    vertex Skinning {
+        weights: [f32; 4] = "a_Weights",
+    }
+
+    pipeline split {
+        positions: gfx::VertexBuffer<Position> = (),
+        skinning: gfx::VertexBufferOffset<Skinning> = (),
+    }
+
+    pipeline particles {
+        positions: gfx::VertexBuffer<Position> = (),
+        points: gfx::PointSprite = PointInfo {
+            program_size: false,
+            size: 8,
+            fade_threshold: 1,
+            origin: PointOrigin::LowerLeft,
+        },
+    }
+
+    pipeline counting {
+        positions: gfx::VertexBuffer<Position> = (),
+        visible: gfx::AtomicCounterBuffer = "u_Visible",
+    }
+}
+
+fn counter(name: &str, location: usize) -> ConstVar {
+    ConstVar {
+        base_type: BaseType::U32,
+        container: ContainerType::Single,
+        .. dummy_constant(name, location, 1)
+    }
+}
+
+fn program(attributes: Vec<AttributeVar>) -> ProgramInfo {
+    ProgramInfo {
+        vertex_attributes: attributes,
+        .. ProgramInfo::default()
+    }
+}
+
+#[test]
+fn test_multiple_vertex_buffers() {
+    let info = program(vec![dummy_attribute("a_Weights", 0), dummy_attribute("a_Pos", 1)]);
+    let mut desc = Descriptor::new(Primitive::TriangleList, Rasterizer::new_fill());
+    assert!(split::new().link_to(&mut desc, &info).is_ok());
+    assert_eq!(desc.vertex_buffers[0].map(|vb| vb.stride), Some(12));
+    assert_eq!(desc.vertex_buffers[1].map(|vb| vb.stride), Some(16));
+    assert_eq!(desc.attributes[0].map(|at| at.0), Some(1));
+    assert_eq!(desc.attributes[1].map(|at| at.0), Some(0));
+}
+
+#[test]
+fn test_uncovered_attribute() {
+    let info = program(vec![dummy_attribute("a_Pos", 0), dummy_attribute("a_Color", 1)]);
+    let mut desc = Descriptor::new(Primitive::TriangleList, Rasterizer::new_fill());
+    match split::new().link_to(&mut desc, &info) {
+        Err(InitError::VertexImport("a_Color", None)) => (),
+        other => panic!("Unexpected link result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_atomic_counter_buffer() {
+    let mut info = program(vec![dummy_attribute("a_Pos", 0)]);
+    info.atomic_counter_buffers.push(AtomicCounterBufferVar {
+        slot: 2,
+        size: 8,
+        usage: shade::PIXEL,
+        counters: vec![counter("u_Total", 0), counter("u_Visible", 4)],
+    });
+    let mut desc = Descriptor::new(Primitive::TriangleList, Rasterizer::new_fill());
+    assert!(counting::new().link_to(&mut desc, &info).is_ok());
+    assert_eq!(desc.atomic_counter_buffers[2], Some(shade::PIXEL));
+
+    info.atomic_counter_buffers[0].counters.truncate(1);
+    let mut desc = Descriptor::new(Primitive::TriangleList, Rasterizer::new_fill());
+    match counting::new().link_to(&mut desc, &info) {
+        Err(InitError::AtomicCounterBuffer("u_Total", None)) => (),
+        other => panic!("Unexpected link result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_point_sprite() {
+    let info = program(vec![dummy_attribute("a_Pos", 0)]);
+    let mut desc = Descriptor::new(Primitive::PointList, Rasterizer::new_fill());
+    assert_eq!(desc.point, PointInfo::default());
+    assert!(particles::new().link_to(&mut desc, &info).is_ok());
+    assert_eq!(desc.point.size, 8);
+    assert_eq!(desc.point.origin, PointOrigin::LowerLeft);
+}
@@ -0,0 +1,64 @@
+extern crate gfx;
+
+use std::fmt;
+use std::error::Error;
+use gfx::recreate::ResourceRegistry;
+
+#[derive(Debug)]
+struct Failure;
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+impl Error for Failure {
+    fn description(&self) -> &str {
+        "Failure"
+    }
+}
+
+#[test]
+fn test_recreate() {
+    let mut registry = ResourceRegistry::new();
+    let mut generation = 1u32;
+    let a = registry.register(&mut generation, |g: &mut u32| Ok::<_, Failure>(*g)).unwrap();
+    let b = registry.register(&mut generation, |g: &mut u32| Ok::<_, Failure>(*g * 10)).unwrap();
+    assert_eq!((a.get(), b.get()), (1, 10));
+
+    generation = 2;
+    registry.recreate(&mut generation).unwrap();
+    assert_eq!((a.get(), b.get()), (2, 20));
+}
+
+#[test]
+fn test_drop() {
+    let mut registry = ResourceRegistry::new();
+    let mut generation = 1u32;
+    let a = registry.register(&mut generation, |g: &mut u32| Ok::<_, Failure>(*g)).unwrap();
+    let b = registry.register(&mut generation, |g: &mut u32| Ok::<_, Failure>(*g)).unwrap();
+    let a2 = a.clone();
+    drop(a);
+    drop(b);
+    assert_eq!(registry.len(), 2);
+
+    generation = 2;
+    registry.recreate(&mut generation).unwrap();
+    assert_eq!(registry.len(), 1);
+    assert_eq!(a2.get(), 2);
+}
+
+#[test]
+fn test_failure() {
+    let mut registry = ResourceRegistry::new();
+    let mut generation = 1u32;
+    let a = registry.register(&mut generation, |g: &mut u32| {
+        if *g < 3 { Ok(*g) } else { Err(Failure) }
+    }).unwrap();
+
+    generation = 3;
+    assert!(registry.recreate(&mut generation).is_err());
+    assert_eq!(a.get(), 1);
+    assert_eq!(registry.len(), 1);
+}
@@ -0,0 +1,56 @@
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::ProgramInfo;
+use gfx::shade::{ConstantBufferVar, Usage};
+use core::dummy::{dummy_attribute, dummy_constant};
+
+fn program() -> ProgramInfo {
+    ProgramInfo {
+        vertex_attributes: vec![dummy_attribute("a_Pos", 0)],
+        globals: vec![dummy_constant("u_Color", 3, 1)],
+        constant_buffers: vec![ConstantBufferVar {
+            name: "Locals".to_string(),
+            slot: 0,
+            size: 80,
+            usage: Usage::all(),
+            elements: vec![dummy_constant("u_Offset", 0, 1), dummy_constant("u_Lights[0]", 16, 4)],
+        }],
+        .. ProgramInfo::default()
+    }
+}
+
+#[test]
+fn test_reflection() {
+    let info = program();
+    assert_eq!(info.get_attribute("a_Pos").map(|a| a.slot), Some(0));
+    assert!(info.get_attribute("a_Normal").is_none());
+    let locals = info.get_constant_buffer("Locals").unwrap();
+    assert_eq!(locals.get_element("u_Lights").map(|e| (e.location, e.count)), Some((16, 4)));
+    assert_eq!(locals.get_element("u_Lights[0]").map(|e| e.location), Some(16));
+    match info.get_constant("u_Offset") {
+        Some((Some(cb), var)) => assert_eq!((&cb.name[..], var.location), ("Locals", 0)),
+        other => panic!("Unexpected constant {:?}", other),
+    }
+    match info.get_constant("u_Color") {
+        Some((None, var)) => assert_eq!(var.location, 3),
+        other => panic!("Unexpected constant {:?}", other),
+    }
+    assert!(info.get_constant("u_Missing").is_none());
+}
+
+#[test]
+fn test_find_member() {
+    use gfx::shade::{find_member, MemberError};
+    let info = program();
+    let locals = info.get_constant_buffer("Locals").unwrap();
+    let member = find_member(locals, "Locals.u_Lights[2]").unwrap();
+    assert_eq!((member.offset, member.size), (48, 16));
+    assert_eq!(find_member(locals, "u_Lights").map(|m| m.offset), Ok(16));
+    assert_eq!(find_member(locals, "u_Offset").map(|m| m.offset), Ok(0));
+    assert_eq!(find_member(locals, "u_Lights[4]"), Err(MemberError::IndexOutOfBounds {
+        path: "u_Lights[4]".to_string(),
+        count: 4,
+    }));
+    assert_eq!(find_member(locals, "u_Color"), Err(MemberError::NotFound("u_Color".to_string())));
+}
@@ -0,0 +1,44 @@
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::shadow::{face_projection, face_view, CubeShadowMode};
+use core::Device;
+use core::dummy::DummyDevice;
+use core::texture::{CubeFace, CUBE_FACES};
+
+fn transform(m: [[f32; 4]; 4], v: [f32; 3]) -> [f32; 4] {
+    let mut out = [0.0; 4];
+    for (i, o) in out.iter_mut().enumerate() {
+        *o = m[0][i] * v[0] + m[1][i] * v[1] + m[2][i] * v[2] + m[3][i];
+    }
+    out
+}
+
+#[test]
+fn test_mode() {
+    let device = DummyDevice::new();
+    assert_eq!(CubeShadowMode::new(device.get_capabilities()), CubeShadowMode::PerFace);
+}
+
+#[test]
+fn test_face_view() {
+    let light = [1.0, 2.0, 3.0];
+    // the center of each face is straight ahead of its camera
+    let centers = [[2.0, 2.0, 3.0], [0.0, 2.0, 3.0], [1.0, 3.0, 3.0],
+                   [1.0, 1.0, 3.0], [1.0, 2.0, 4.0], [1.0, 2.0, 2.0]];
+    for (&face, &center) in CUBE_FACES.iter().zip(centers.iter()) {
+        assert_eq!(transform(face_view(face, light), center), [0.0, 0.0, -1.0, 1.0]);
+    }
+    // the top of the +X face looks down the Y axis
+    assert_eq!(transform(face_view(CubeFace::PosX, [0.0; 3]), [1.0, -1.0, 0.0]),
+               [0.0, 1.0, -1.0, 1.0]);
+}
+
+#[test]
+fn test_face_projection() {
+    let proj = face_projection(1.0, 10.0);
+    let near = transform(proj, [0.0, 0.0, -1.0]);
+    let far = transform(proj, [0.0, 0.0, -10.0]);
+    assert!((near[2] / near[3] + 1.0).abs() < 1e-6);
+    assert!((far[2] / far[3] - 1.0).abs() < 1e-6);
+}
@@ -0,0 +1,56 @@
+#[macro_use]
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::{IndexBuffer, IndexBufferError, IntoIndexBuffer};
+use gfx::buffer::Role;
+use gfx::memory::Bind;
+use gfx::traits::{Factory, FactoryExt};
+use core::dummy::DummyFactory;
+
+gfx_defines!{
+    vertex Vertex {
+        pos: [f32; 2] = "a_Pos",
+    }
+}
+
+#[test]
+fn test_index_role() {
+    let mut factory = DummyFactory::new();
+    let indices = factory.create_buffer_immutable(&[0u16, 1, 2], Role::Index, Bind::empty())
+                         .unwrap();
+    match indices.into_index_buffer(&mut factory) {
+        Ok(IndexBuffer::Index16(ref buf)) => assert_eq!(buf.len(), 3),
+        other => panic!("Unexpected index buffer: {:?}", other),
+    }
+    let vertices = factory.create_buffer_immutable(&[0u16, 1, 2], Role::Vertex, Bind::empty())
+                          .unwrap();
+    assert_eq!(vertices.into_index_buffer(&mut factory),
+               Err(IndexBufferError::Role(Role::Vertex)));
+}
+
+#[test]
+fn test_index_u8() {
+    let mut factory = DummyFactory::new();
+    // the dummy device doesn't support 8-bit indices
+    match (&[0u8, 1, 2][..]).into_index_buffer(&mut factory) {
+        Ok(IndexBuffer::Index16(ref buf)) => assert_eq!(buf.len(), 3),
+        other => panic!("Unexpected index buffer: {:?}", other),
+    }
+    let indices = factory.create_buffer_immutable(&[0u8, 1, 2], Role::Index, Bind::empty())
+                         .unwrap();
+    assert_eq!(indices.into_index_buffer(&mut factory), Err(IndexBufferError::UnsupportedU8));
+}
+
+#[test]
+fn test_try_create_with_slice() {
+    let mut factory = DummyFactory::new();
+    let vertices = [Vertex { pos: [0.0, 0.0] }; 4];
+    let (_, slice) = factory.try_create_vertex_buffer_with_slice(&vertices, &[0u16, 1, 2][..])
+                            .unwrap();
+    assert_eq!((slice.start, slice.end), (0, 3));
+    let indices = factory.create_buffer_immutable(&[0u8, 1, 2], Role::Index, Bind::empty())
+                         .unwrap();
+    assert_eq!(factory.try_create_vertex_buffer_with_slice(&vertices, indices).map(|_| ()),
+               Err(IndexBufferError::UnsupportedU8));
+}
@@ -0,0 +1,63 @@
+#[macro_use]
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::{Primitive, ProgramInfo, Slice};
+use gfx::pso::{Descriptor, InitError, PipelineInit};
+use gfx::format::{Depth, Srgba8};
+use gfx::state::Rasterizer;
+use gfx::stereo::{self, StereoMode};
+use gfx::texture::{AaMode, Kind};
+use core::Device;
+use core::dummy::{dummy_constant, DummyDevice, DummyFactory, DummyResources};
+use core::memory::{DEPTH_STENCIL, RENDER_TARGET, SHADER_RESOURCE};
+use core::shade::{CompatibilityError, ConstVar, ContainerType, MatrixFormat};
+
+gfx_defines!{
+    pipeline eyes {
+        view_proj: gfx::PerView<[[f32; 4]; 4]> = "u_ViewProj",
+    }
+}
+
+fn program(count: usize) -> ProgramInfo {
+    ProgramInfo {
+        globals: vec![ConstVar {
+            container: ContainerType::Matrix(MatrixFormat::ColumnMajor, 4, 4),
+            .. dummy_constant("u_ViewProj[0]", 3, count)
+        }],
+        .. ProgramInfo::default()
+    }
+}
+
+#[test]
+fn test_expand_slice() {
+    let device = DummyDevice::new();
+    let mode = StereoMode::new(device.get_capabilities());
+    assert_eq!(mode, StereoMode::Instanced);
+    let slice: Slice<DummyResources> = Slice::new_auto(0, 6);
+    assert_eq!(stereo::expand_slice(&slice, mode).instances, Some((2, 0)));
+    let instanced = Slice { instances: Some((10, 5)), .. slice.clone() };
+    assert_eq!(stereo::expand_slice(&instanced, mode).instances, Some((20, 10)));
+    assert_eq!(stereo::expand_slice(&instanced, StereoMode::Multiview), instanced);
+}
+
+#[test]
+fn test_per_view_link() {
+    let mut desc = Descriptor::new(Primitive::TriangleList, Rasterizer::new_fill());
+    assert!(eyes::new().link_to(&mut desc, &program(2)).is_ok());
+    match eyes::new().link_to(&mut desc, &program(1)) {
+        Err(InitError::GlobalConstant("u_ViewProj[0]", Some(CompatibilityError::ErrorArraySize))) => (),
+        other => panic!("Unexpected link result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_stereo_targets_bind() {
+    let mut factory = DummyFactory::new();
+    let targets: stereo::StereoTargets<DummyResources, Srgba8, Depth> =
+        stereo::create_stereo_targets(&mut factory, 64, 32).unwrap();
+    assert_eq!(targets.color.get_info().bind, SHADER_RESOURCE | RENDER_TARGET);
+    assert_eq!(targets.depth.get_info().bind, SHADER_RESOURCE | DEPTH_STENCIL);
+    assert_eq!(targets.depth.get_info().kind,
+               Kind::D2Array(64, 32, stereo::VIEW_COUNT, AaMode::Single));
+}
@@ -0,0 +1,16 @@
+extern crate gfx_core as core;
+extern crate gfx_window_glutin;
+
+use core::SwapChainConfig;
+use gfx_window_glutin::{get_effective_config, SwapChainError};
+
+#[test]
+fn test_effective_config() {
+    let config = SwapChainConfig::new().with_swap_interval(3).with_buffer_count(3);
+    assert_eq!(get_effective_config(config),
+               Ok(SwapChainConfig::new().with_swap_interval(1).with_buffer_count(2)));
+    let config = SwapChainConfig::new().with_vsync(false).with_buffer_count(1);
+    assert_eq!(get_effective_config(config), Ok(config));
+    assert_eq!(get_effective_config(SwapChainConfig::new().with_buffer_count(0)),
+               Err(SwapChainError::NoBuffer));
+}
@@ -0,0 +1,22 @@
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::{Feature, MissingFeatures, Technique};
+use core::Device;
+use core::dummy::DummyDevice;
+
+#[test]
+fn test_technique_fallback() {
+    let device = DummyDevice::new();
+    let caps = device.get_capabilities();
+    let technique = Technique::new()
+        .variant(&[Feature::ConstantBuffer], "block")
+        .variant(&[], "uniforms");
+    assert_eq!(technique.select_index(caps), Ok(1));
+    assert_eq!(technique.select(caps), Ok(&"uniforms"));
+
+    let required = Technique::new()
+        .variant(&[Feature::InstanceCall, Feature::ConstantBuffer], ());
+    assert_eq!(required.into_selected(caps),
+               Err(MissingFeatures(vec![Feature::InstanceCall, Feature::ConstantBuffer])));
+}
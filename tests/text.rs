@@ -0,0 +1,51 @@
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::{DynamicBuffer, Encoder, UpdateError, WriteError};
+use gfx::text::{self, FlushError, TextBatch, GLYPH_HEIGHT, GLYPH_WIDTH};
+use core::dummy::{DummyCommandBuffer, DummyFactory, DummyResources};
+
+#[test]
+fn test_font_atlas() {
+    let (width, height, pixels) = text::get_font_atlas();
+    assert_eq!((width, height), (16 * GLYPH_WIDTH, 6 * GLYPH_HEIGHT));
+    assert_eq!(pixels.len(), width as usize * height as usize);
+    // the space is blank, while '!' has its top pixel in the middle column
+    assert!(pixels[.. GLYPH_WIDTH as usize].iter().all(|&p| p == 0));
+    assert_eq!(pixels[GLYPH_WIDTH as usize + 2], 0xFF);
+}
+
+#[test]
+fn test_text_layout() {
+    let mut batch = TextBatch::new(2.0);
+    let end = batch.print("a b\nc", [10.0, 20.0], [1.0; 4]);
+    assert_eq!(batch.get_vertices().len(), 3 * 6);
+    let cell = [GLYPH_WIDTH as f32 * 2.0, GLYPH_HEIGHT as f32 * 2.0];
+    assert_eq!(batch.get_vertices()[6].pos, [10.0 + 2.0 * cell[0], 20.0]);
+    assert_eq!(batch.get_vertices()[12].pos, [10.0, 20.0 + cell[1]]);
+    assert_eq!(end, [10.0 + cell[0], 20.0 + cell[1]]);
+}
+
+#[test]
+fn test_text_flush() {
+    let mut factory = DummyFactory::new();
+    let mut encoder: Encoder<DummyResources, _> = DummyCommandBuffer.into();
+    let mut quads = DynamicBuffer::new(&mut factory, 6).unwrap();
+    let mut batch = TextBatch::new(1.0);
+    batch.print("ab", [0.0, 0.0], [1.0; 4]);
+    // neither a failed write nor an empty target lose the text
+    assert_eq!(batch.flush(&mut quads, &mut factory, &mut encoder, (0, 10)),
+               Err(FlushError::TargetSize(0, 10)));
+    assert_eq!(batch.flush(&mut quads, &mut factory, &mut encoder, (10, 10)),
+               Err(FlushError::Write(WriteError::Update(UpdateError::OutOfBounds {
+                   target: 6,
+                   source: 12,
+               }))));
+    assert_eq!(batch.get_vertices()[0].pos, [0.0, 0.0]);
+
+    let mut batch = TextBatch::new(1.0);
+    batch.print("a", [0.0, 0.0], [1.0; 4]);
+    let slice = batch.flush(&mut quads, &mut factory, &mut encoder, (10, 10)).unwrap();
+    assert_eq!((slice.start, slice.end), (0, 6));
+    assert!(batch.get_vertices().is_empty());
+}
@@ -0,0 +1,37 @@
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::{Encoder, Primitive, Slice};
+use gfx::timing::FrameTimer;
+use core::dummy::{DummyCommandBuffer, DummyDevice, DummyResources};
+use core::trace::{TraceCommandBuffer, TraceDevice};
+
+#[test]
+fn test_frame_timer() {
+    let mut device = TraceDevice::new(DummyDevice::new(), Vec::new());
+    let mut encoder: Encoder<DummyResources, _> = TraceCommandBuffer::new(DummyCommandBuffer).into();
+    let slice: Slice<DummyResources> = Slice {
+        instances: Some((3, 0)),
+        .. Slice::new_auto(0, 6)
+    };
+    let mut timer = FrameTimer::new(4, 2, 2);
+    for _ in 0 .. 3 {
+        timer.begin_frame(&mut encoder);
+        timer.count_draw(&slice, Primitive::TriangleList);
+        timer.end_frame(&mut encoder);
+        timer.submit(&mut encoder, &mut device);
+        timer.swap(|| ());
+        timer.update(&mut device);
+    }
+    let frames: Vec<_> = timer.get_frames().iter().map(|f| f.frame).collect();
+    assert_eq!(frames, vec![1, 2]);
+    let last = timer.get_last().unwrap();
+    assert_eq!((last.draw_count, last.primitive_count, last.gpu), (1, 6, None));
+
+    // the results never come with the dummy device, so the third frame
+    // finds no free slot
+    let (_, output) = device.into_inner();
+    let log = String::from_utf8(output).unwrap();
+    assert_eq!(log.matches("begin_timer_query").count(), 2);
+    assert!(log.contains("end_timer_query(5)"));
+}
@@ -0,0 +1,42 @@
+extern crate gfx_core as core;
+
+use core::{Device, IndexType};
+use core::command::{AccessInfo, Buffer};
+use core::dummy::{DummyCommandBuffer, DummyDevice};
+use core::trace::{TraceCommandBuffer, TraceDevice};
+
+#[test]
+fn test_trace() {
+    let mut device = TraceDevice::new(DummyDevice::new(), Vec::new());
+    let mut cb = TraceCommandBuffer::new(DummyCommandBuffer);
+    for _ in 0 .. 2 {
+        cb.update_buffer((), &[1, 2, 3, 4], 8);
+        cb.bind_index((), IndexType::U16);
+        cb.call_draw_indexed(0, 6, 0, None);
+        device.submit(&mut cb, &AccessInfo::new()).unwrap();
+        cb.reset();
+        device.cleanup();
+    }
+    assert_eq!(device.get_frame(), 2);
+    assert!(cb.get_calls().is_empty());
+
+    let (_, output) = device.into_inner();
+    let log = String::from_utf8(output).unwrap();
+    let frame = "  submit\n\
+                 \x20   update_buffer((), <4 bytes>, 8)\n\
+                 \x20   bind_index((), U16)\n\
+                 \x20   call_draw_indexed(0, 6, 0, None)\n\
+                 \x20 cleanup\n";
+    assert_eq!(log, format!("frame 0\n{}frame 1\n{}", frame, frame));
+}
+
+#[test]
+fn test_trace_pipeline_statistics() {
+    // the dummy device keeps the default of not counting anything
+    let mut device = TraceDevice::new(DummyDevice::new(), Vec::new());
+    assert!(!device.set_pipeline_statistics(true));
+    assert_eq!(device.get_pipeline_statistics(), None);
+    let (_, output) = device.into_inner();
+    assert_eq!(String::from_utf8(output).unwrap(),
+               "frame 0\n  set_pipeline_statistics(true) -> false\n");
+}
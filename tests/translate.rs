@@ -0,0 +1,55 @@
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::translate::{translate, GlslTarget};
+use core::shade::Stage;
+
+const VERTEX: &'static str = "#version 150 core
+in vec2 a_Pos;
+out vec2 v_Uv;
+void main() {
+    v_Uv = a_Pos;
+    gl_Position = vec4(a_Pos, 0.0, 1.0);
+}
+";
+
+const PIXEL: &'static str = "#version 150 core
+uniform samplerCube t_Sky;
+in vec2 v_Uv;
+flat in float v_Lod;
+out vec4 Target0;
+void main() {
+    Target0 = texture(t_Sky, vec3(v_Uv, 1.0)); // sample
+}
+";
+
+#[test]
+fn test_translate_legacy() {
+    assert_eq!(translate(VERTEX, Stage::Vertex, GlslTarget::new(120, false)),
+               "#version 120
+attribute vec2 a_Pos;
+varying vec2 v_Uv;
+void main() {
+    v_Uv = a_Pos;
+    gl_Position = vec4(a_Pos, 0.0, 1.0);
+}
+");
+    assert_eq!(translate(PIXEL, Stage::Pixel, GlslTarget::new(100, true)),
+               "#version 100
+precision mediump float;
+uniform samplerCube t_Sky;
+varying vec2 v_Uv;
+varying float v_Lod;
+void main() {
+    gl_FragColor = textureCube(t_Sky, vec3(v_Uv, 1.0)); // sample
+}
+");
+}
+
+#[test]
+fn test_translate_modern() {
+    let out = translate(PIXEL, Stage::Pixel, GlslTarget::new(300, true));
+    assert!(out.starts_with("#version 300 es\nprecision mediump float;\nuniform samplerCube"));
+    assert_eq!(translate(VERTEX, Stage::Vertex, GlslTarget::new(430, false)),
+               VERTEX.replace("150", "430"));
+}
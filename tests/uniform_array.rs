@@ -0,0 +1,51 @@
+#[macro_use]
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::{Primitive, ProgramInfo};
+use gfx::pso::{Descriptor, PipelineData, PipelineInit, RawDataSet};
+use gfx::shade::{ToUniformArray, UniformValue};
+use gfx::state::Rasterizer;
+use core::command::AccessInfo;
+use core::dummy::{dummy_constant, DummyResources};
+use core::handle::Manager;
+use core::shade::{BaseType, ConstVar, ContainerType};
+
+gfx_defines!{
+    pipeline lights {
+        colors: gfx::GlobalArray<Vec<[f32; 4]>> = ("u_Colors", "u_ColorCount"),
+    }
+}
+
+fn program() -> ProgramInfo {
+    ProgramInfo {
+        globals: vec![dummy_constant("u_Colors[0]", 2, 2), ConstVar {
+            base_type: BaseType::I32,
+            container: ContainerType::Single,
+            .. dummy_constant("u_ColorCount", 7, 1)
+        }],
+        .. ProgramInfo::default()
+    }
+}
+
+#[test]
+fn test_array_elements() {
+    let values = vec![1.0f32, 2.0];
+    assert_eq!(values.get_elements(), &[1.0, 2.0]);
+    assert_eq!((&values[.. 1]).get_elements(), &[1.0]);
+}
+
+#[test]
+fn test_global_array_bind() {
+    let mut desc = Descriptor::new(Primitive::TriangleList, Rasterizer::new_fill());
+    let meta = lights::new().link_to(&mut desc, &program()).unwrap();
+    let mut out = RawDataSet::<DummyResources>::new();
+    let data = lights::Data { colors: vec![[1.0; 4], [0.5; 4], [0.0; 4]] };
+    data.bake_to(&mut out, &meta, &mut Manager::new(), &mut AccessInfo::new());
+    // the third color doesn't fit in the array
+    assert_eq!(out.global_constants, vec![
+        (2, UniformValue::F32Vector4([1.0; 4])),
+        (3, UniformValue::F32Vector4([0.5; 4])),
+        (7, UniformValue::I32(2)),
+    ]);
+}
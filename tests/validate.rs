@@ -0,0 +1,125 @@
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::{ProgramInfo, Rect, Slice};
+use gfx::validate::{check_draw, Bindings, DrawError, TextureBinding, VertexBinding};
+use core::buffer::Role;
+use core::dummy::{dummy_attribute, DummyResources};
+use core::format::{ChannelType, SurfaceType};
+use core::shade::{BaseType, IsArray, IsMultiSample, TextureType, TextureVar, Usage};
+use core::texture::{AaMode, Kind};
+
+fn program() -> ProgramInfo {
+    ProgramInfo {
+        vertex_attributes: vec![dummy_attribute("a_Pos", 0)],
+        textures: vec![TextureVar {
+            name: "t_Color".to_string(),
+            slot: 0,
+            base_type: BaseType::F32,
+            ty: TextureType::D2(IsArray::NoArray, IsMultiSample::NoMultiSample),
+            usage: Usage::all(),
+        }],
+        .. ProgramInfo::default()
+    }
+}
+
+fn slice(end: u32) -> Slice<DummyResources> {
+    Slice::new_auto(0, end)
+}
+
+fn bindings(vertices: usize) -> Bindings {
+    let mut bindings = Bindings::new();
+    bindings.vertex_buffers[0] = Some(VertexBinding {
+        size: vertices * 12,
+        stride: 12,
+        rate: 0,
+    });
+    bindings.resource_views.push((0, Some(texture(Kind::D2(4, 4, AaMode::Single)))));
+    bindings
+}
+
+fn texture(kind: Kind) -> TextureBinding {
+    TextureBinding {
+        kind: kind,
+        surface: SurfaceType::R8_G8_B8_A8,
+        channel: ChannelType::Unorm,
+    }
+}
+
+#[test]
+fn test_valid() {
+    assert_eq!(check_draw(&slice(6), &program(), &bindings(6)), Ok(()));
+}
+
+#[test]
+fn test_vertex_bounds() {
+    match check_draw(&slice(6), &program(), &bindings(4)) {
+        Err(DrawError::VertexOutOfBounds { needed: 6, count: 4, .. }) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_missing_attribute() {
+    let mut bindings = bindings(6);
+    bindings.vertex_buffers[0] = None;
+    assert_eq!(check_draw(&slice(6), &program(), &bindings),
+               Err(DrawError::MissingAttribute("a_Pos".to_string())));
+}
+
+#[test]
+fn test_texture_type() {
+    let mut bindings = bindings(6);
+    bindings.resource_views[0].1 = Some(texture(Kind::Cube(4)));
+    match check_draw(&slice(6), &program(), &bindings) {
+        Err(DrawError::TextureType { .. }) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    bindings.resource_views[0].1 = Some(texture(Kind::D2(4, 4, AaMode::Multi(4))));
+    match check_draw(&slice(6), &program(), &bindings) {
+        Err(DrawError::TextureType { .. }) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_texture_channel() {
+    let mut bindings = bindings(6);
+    let mut program = program();
+    program.textures[0].base_type = BaseType::U32;
+    match check_draw(&slice(6), &program, &bindings) {
+        Err(DrawError::TextureFormat { expected: BaseType::U32, channel: ChannelType::Unorm, .. }) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    bindings.resource_views[0].1 = Some(TextureBinding {
+        channel: ChannelType::Uint,
+        .. texture(Kind::D2(4, 4, AaMode::Single))
+    });
+    assert_eq!(check_draw(&slice(6), &program, &bindings), Ok(()));
+}
+
+#[test]
+fn test_buffer_role() {
+    let mut bindings = bindings(6);
+    assert!(bindings.add_buffer(Role::Vertex, Role::Vertex));
+    assert!(!bindings.add_buffer(Role::Constant, Role::Staging));
+    assert!(!bindings.add_buffer(Role::Vertex, Role::Index));
+    assert_eq!(check_draw(&slice(6), &program(), &bindings),
+               Err(DrawError::BufferRole { expected: Role::Constant, found: Role::Staging }));
+}
+
+#[test]
+fn test_targets() {
+    let mut bindings = bindings(6);
+    assert!(bindings.add_target((8, 8, 1, AaMode::Single)));
+    bindings.scissor = Some(Rect { x: 4, y: 4, w: 8, h: 2 });
+    match check_draw(&slice(6), &program(), &bindings) {
+        Err(DrawError::ScissorOutOfBounds { .. }) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    assert!(!bindings.add_target((4, 4, 1, AaMode::Single)));
+    match check_draw(&slice(6), &program(), &bindings) {
+        Err(DrawError::TargetDimensions { .. }) => (),
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
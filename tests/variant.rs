@@ -0,0 +1,56 @@
+#[macro_use]
+extern crate gfx;
+extern crate gfx_core as core;
+
+use gfx::{Primitive, VariantCache};
+use gfx::preprocess::Preprocessor;
+use gfx::state::Rasterizer;
+use core::dummy::{DummyFactory, DummyResources};
+
+gfx_defines!{
+    pipeline pipe {
+        out: gfx::RenderTarget<gfx::format::Srgba8> = "Target0",
+    }
+}
+
+const VS: &'static str = "#version 150 core
+void main() {}
+";
+const PS: &'static str = "#version 150 core
+out vec4 Target0;
+void main() {}
+";
+
+fn cache() -> VariantCache<DummyResources, pipe::Init<'static>> {
+    VariantCache::new(VS, PS, Primitive::TriangleList, Rasterizer::new_fill(), pipe::new())
+}
+
+#[test]
+fn test_variant_key() {
+    let mut factory = DummyFactory::new();
+    let mut pp = Preprocessor::new(|_: &str| -> Option<String> { None });
+    let mut cache = cache();
+    cache.get(&mut factory, &mut pp, &[("SHADOWS", ""), ("TAPS", "4")]).unwrap();
+    cache.get(&mut factory, &mut pp, &[("TAPS", "4"), ("SHADOWS", "")]).unwrap();
+    cache.get(&mut factory, &mut pp, &[("SHADOWS", ""), ("TAPS", "4"), ("SHADOWS", "")])
+         .unwrap();
+    assert_eq!(cache.len(), 1);
+    cache.get(&mut factory, &mut pp, &[("TAPS", "8")]).unwrap();
+    assert_eq!(cache.len(), 2);
+    cache.clear();
+    assert_eq!(cache.len(), 0);
+}
+
+#[test]
+fn test_variant_defines() {
+    let mut pp = Preprocessor::new(|_: &str| -> Option<String> { None });
+    let (vs, ps) = cache()
+        .get_sources(&mut pp, &[("TAPS", "4"), ("SHADOWS", ""), ("TAPS", "4")])
+        .unwrap();
+    assert_eq!(vs, "#version 150 core
+#define SHADOWS
+#define TAPS 4
+void main() {}
+");
+    assert!(ps.starts_with("#version 150 core\n#define SHADOWS\n#define TAPS 4\nout vec4"));
+}
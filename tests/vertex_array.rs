@@ -0,0 +1,37 @@
+extern crate gfx_core as core;
+extern crate gfx_device_gl;
+
+use core::format::{ChannelType, Format, SurfaceType};
+use core::pso::{Element, VertexBufferDesc};
+use gfx_device_gl::{BufferElement, VertexArrayCache};
+
+fn attribute(offset: u32) -> BufferElement {
+    BufferElement {
+        desc: VertexBufferDesc { stride: 16, rate: 0 },
+        elem: Element {
+            format: Format(SurfaceType::R32_G32_B32_A32, ChannelType::Float),
+            offset: offset,
+        },
+    }
+}
+
+#[test]
+fn test_offsets_share_vertex_array() {
+    let mut cache = VertexArrayCache::new();
+    let mut vertex_array = [None; core::MAX_VERTEX_ATTRIBUTES];
+    vertex_array[0] = Some((1, attribute(0)));
+    assert_eq!(cache.rebind(&vertex_array), None);
+    cache.insert(&vertex_array, 7);
+    for i in 1 .. 100 {
+        vertex_array[0] = Some((1, attribute(i * 16)));
+        let (vao, offsets) = cache.rebind(&vertex_array).unwrap();
+        assert_eq!((vao, offsets[0]), (7, (i - 1) * 16));
+    }
+    assert_eq!(cache.len(), 1);
+    // another buffer needs its own object
+    vertex_array[0] = Some((2, attribute(0)));
+    assert_eq!(cache.rebind(&vertex_array), None);
+    cache.insert(&vertex_array, 8);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.remove_unless(|va| va[0].map(|(buffer, _)| buffer) != Some(1)), vec![7]);
+}